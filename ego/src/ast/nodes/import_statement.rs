@@ -9,6 +9,9 @@ pub struct ImportStatement {
     pub module: Vec<String>,
     pub module_type: ModuleType,
     pub members: Vec<String>,
+    // path of the module with the import, custom modules are
+    // looked up next to it
+    pub importer: String,
     pub at: usize,
     pub line: usize,
 }
@@ -18,6 +21,7 @@ impl ImportStatement {
         module: Vec<String>,
         module_type: ModuleType,
         members: Vec<String>,
+        importer: String,
         at: usize,
         line: usize,
     ) -> ImportStatement {
@@ -25,6 +29,7 @@ impl ImportStatement {
             module,
             module_type,
             members,
+            importer,
             at,
             line,
         }
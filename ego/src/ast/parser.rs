@@ -869,6 +869,7 @@ impl Module {
             module,
             modtype,
             vec![],
+            self.module_name.clone(),
             at,
             line,
         ));
@@ -0,0 +1,106 @@
+use std::path::Path;
+
+use self_vm::utils::foreign_handlers_utils::{
+    find_runtime, get_foreign_handlers, ForeignHandler, ForeignProtocol,
+};
+
+use crate::core::error;
+use crate::core::error::ErrorType;
+
+// ego foreign list [module.ego]
+pub struct Foreign {
+    args: Vec<String>,
+}
+
+impl Foreign {
+    pub fn new(args: Vec<String>) -> Foreign {
+        Foreign { args }
+    }
+    pub fn exec(&self) {
+        match self.args.first().map(|s| s.as_str()) {
+            Some("list") => self.list(),
+            Some(other) => error::throw(
+                ErrorType::EgoUsageError,
+                format!("unknown foreign command '{}', expected: list", other).as_str(),
+                None,
+            ),
+            None => error::throw(
+                ErrorType::EgoUsageError,
+                "a foreign command is required: list",
+                None,
+            ),
+        }
+    }
+
+    fn list(&self) {
+        let entry = self.args.get(1).map(Path::new);
+        let config = get_foreign_handlers(entry);
+        let mut failed = !config.errors.is_empty();
+
+        if config.files.is_empty() {
+            println!("\n ◔ No foreign handler files found\n");
+        }
+        for file in &config.files {
+            println!("\n ◔ {}", file.display());
+            for handler in config.handlers.iter().filter(|h| &h.source == file) {
+                if !print_handler(handler) {
+                    failed = true;
+                }
+            }
+        }
+
+        if !config.errors.is_empty() {
+            println!("\n ⅹ Invalid handlers:");
+            for err in &config.errors {
+                println!("   {}", err.to_string());
+            }
+        }
+        println!();
+
+        if failed {
+            std::process::exit(1);
+        }
+    }
+}
+
+// prints the resolved handler, false if it cannot be run
+fn print_handler(handler: &ForeignHandler) -> bool {
+    let runtime = find_runtime(&handler.runtime);
+    let script_exists = Path::new(&handler.script).exists();
+
+    let args = match &handler.args {
+        Some(args) => args
+            .iter()
+            .map(|a| format!("{}: {}", a.name, a.arg_type))
+            .collect::<Vec<_>>()
+            .join(", "),
+        None => "...".to_string(),
+    };
+    let returns = handler.returns.clone().unwrap_or("any".to_string());
+    let protocol = match handler.protocol {
+        ForeignProtocol::Args => "args",
+        ForeignProtocol::Json if handler.worker => "json, worker",
+        ForeignProtocol::Json => "json",
+    };
+    let timeout = match handler.timeout {
+        Some(t) => format!(", timeout {}ms", t),
+        None => "".to_string(),
+    };
+
+    let mark = if runtime.is_some() { "⚈" } else { "ⅹ" };
+    println!(
+        "   {} {}({}) -> {} [{}{}]",
+        mark, handler.name, args, returns, protocol, timeout
+    );
+    match &runtime {
+        Some(path) => println!("       runtime: {}", path.display()),
+        None => println!("       runtime: {} (not found)", handler.runtime),
+    }
+    if script_exists {
+        println!("       script:  {}", handler.script);
+    } else {
+        println!("       script:  {} (not found)", handler.script);
+    }
+
+    runtime.is_some()
+}
@@ -1,8 +1,10 @@
 pub mod compile;
+pub mod foreign;
 pub mod logo;
 pub mod new;
 pub mod run;
 
+use self::foreign::Foreign;
 use self::logo::Logo;
 use self::new::New;
 use self::run::Run;
@@ -17,6 +19,7 @@ pub enum Command {
     Logo(Logo),
     New(New),
     Compile(Compile),
+    Foreign(Foreign),
 }
 
 impl Command {
@@ -28,11 +31,7 @@ impl Command {
             return Command::cmd_from_str(command.as_str(), remaining_args.to_vec());
         } else {
             // print help message instead of error
-            error::throw(
-                ErrorType::EgoUsageError,
-                "a command is required",
-                None,
-            );
+            error::throw(ErrorType::EgoUsageError, "a command is required", None);
             std::process::exit(1); // to avoid types error
         };
     }
@@ -42,6 +41,7 @@ impl Command {
             "logo" => Command::Logo(Logo::new(args)),
             "new" => Command::New(New::new(args)),
             "compile" => Command::Compile(Compile::new(args)),
+            "foreign" => Command::Foreign(Foreign::new(args)),
             _ => Command::Run(Run::new(
                 [command.to_string()]
                     .into_iter()
@@ -56,6 +56,7 @@ impl Command {
             Command::Logo(v) => v.exec(),
             Command::New(v) => v.exec(),
             Command::Compile(v) => v.exec(),
+            Command::Foreign(v) => v.exec(),
         }
     }
 }
@@ -1,12 +1,18 @@
 use std::fs;
 use std::fs::File;
 use std::io::Write;
+use std::path::Path;
 
 use crate::ast::lex;
 use crate::ast::Module;
 use crate::compiler::Compiler;
 use crate::core::error;
 use crate::core::error::ErrorType;
+use self_vm::{
+    extension::VMExecutionResult,
+    output::OutputStream,
+    vm::{has_flag, Vm},
+};
 
 pub struct Run {
     args: Vec<String>,
@@ -17,7 +23,7 @@ impl Run {
         Run { args }
     }
     pub fn debug(&self) -> bool {
-        self.args.contains(&"-d".to_string())
+        has_flag(&self.args, "-d")
     }
     pub async fn exec(&self) {
         let module_name = if self.args.len() > 0 {
@@ -27,7 +33,7 @@ impl Run {
         };
 
         // run from compiled file
-        if self.args.contains(&"--bytes".to_string()) {
+        if has_flag(&self.args, "--bytes") {
             let bytecode = fs::read(&module_name).unwrap_or_else(|_| {
                 error::throw(
                     ErrorType::FatalError,
@@ -37,11 +43,9 @@ impl Run {
                 std::process::exit(1); // to avoid types error
             });
             let mut vm = self_vm::new(bytecode);
+            vm.load_project(Path::new(&module_name));
             let execution = vm.run(&self.args).await;
-            if let Some(err) = execution.error {
-                let error_msg = format!("{}: {}", err.message, err.semantic_message);
-                eprintln!("\x1b[31m[ERR] \x1b[0m{error_msg}");
-            }
+            finish(&vm, execution);
             return;
         }
 
@@ -62,7 +66,7 @@ impl Run {
             }
         }
 
-        let mut module = Module::new(module_name, tokens);
+        let mut module = Module::new(module_name.clone(), tokens);
         let ast = module.parse();
         if self.debug() {
             println!("\nAst nodes: \n---------------\n{:#?}", ast);
@@ -70,10 +74,23 @@ impl Run {
         let mut compiler = Compiler::new(ast);
         let bytecode = compiler.gen_bytecode();
         let mut vm = self_vm::new(bytecode);
+        vm.load_project(Path::new(&module_name));
         let execution = vm.run(&self.args).await;
-        if let Some(err) = execution.error {
-            let error_msg = format!("{}: {}", err.message, err.semantic_message);
-            eprintln!("\x1b[31m[ERR] \x1b[0m{error_msg}");
-        }
+        finish(&vm, execution);
+    }
+}
+
+// reports the execution and exits with the os.exit code, if any
+fn finish(vm: &Vm, execution: VMExecutionResult) {
+    let exit_code = execution.exit_code();
+    if let (None, Some(err)) = (exit_code, &execution.error) {
+        vm.report_error(err);
+    }
+    if let Some(report) = vm.ai_usage_report() {
+        vm.write_output(OutputStream::Stderr, &format!("{report}\n"));
+    }
+    vm.flush_output();
+    if let Some(code) = exit_code {
+        std::process::exit(code);
     }
 }
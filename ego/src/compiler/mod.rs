@@ -2,6 +2,7 @@ mod bytecode;
 mod handlers;
 
 use std::fs;
+use std::path::Path;
 
 use crate::ast::export_statement::ExportStatement;
 use crate::ast::return_statement::ReturnStatement;
@@ -17,6 +18,7 @@ use crate::{
 };
 use bytecode::get_bytecode;
 use self_vm::utils::{
+    path::normalize,
     to_bytes::{bytes_from_32, bytes_from_64, bytes_from_float},
     Number,
 };
@@ -526,10 +528,20 @@ impl Compiler {
                 // on imports, to avoid an infinite loop of
                 // compilation
                 let module_name = node.module[0].clone();
-                let path = format!("{}.ego", module_name);
-                let code =
-                    fs::read_to_string(&path).expect(&format!("Failed to read module '{}'", path));
-                let mod_bytecode = gen_bytecode(module_name.to_string(), code, &vec![]);
+                // relative to the importing module, not to the cwd
+                let dir = Path::new(&node.importer).parent().unwrap_or(Path::new(""));
+                let path = normalize(&dir.join(format!("{}.ego", module_name)));
+                let code = fs::read_to_string(&path).unwrap_or_else(|_| {
+                    error::throw(
+                        ErrorType::CompilationError,
+                        format!("Cannot read module '{}'", path.display()).as_str(),
+                        Some(node.line),
+                    );
+                    std::process::exit(1);
+                });
+                // named after its path, so its own imports are
+                // resolved next to it
+                let mod_bytecode = gen_bytecode(path.display().to_string(), code, &vec![]);
 
                 // push module_name to stack
                 bytecode.extend_from_slice(&Compiler::compile_expression(
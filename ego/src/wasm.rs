@@ -4,6 +4,7 @@ use crate::{
     core::logs::get_log_history,
     log,
 };
+use self_vm::output::{BufferOutput, OutputStream};
 
 pub fn run_ego(code: String, vm: bool) -> Vec<String> {
     log!("Executing ego:");
@@ -25,8 +26,25 @@ pub fn run_ego(code: String, vm: bool) -> Vec<String> {
     let mut compiler = Compiler::new(ast);
     let bytecode = compiler.gen_bytecode();
     let mut vm = self_vm::vm::Vm::new(bytecode);
-    vm.run(&vec![]);
-    vec!["Logs with executions are not implemented yet".to_string()]
+    // the program output is captured to hand it back to the playground
+    let output = BufferOutput::new();
+    vm.set_output(output.clone());
+    let runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(e) => return vec![format!("cannot start the vm runtime: {}", e)],
+    };
+    let execution = runtime.block_on(vm.run(&vec![]));
+    // no terminal colors on the playground
+    if let (None, Some(err)) = (execution.exit_code(), &execution.error) {
+        vm.write_output(
+            OutputStream::Stderr,
+            &format!("[ERR] {}: {}\n", err.message, err.semantic_message),
+        );
+    }
+    output.lines()
     // } else {
     //     let mut interpreter = Interpreter::new(ast.clone());
     //     interpreter.exec(false);
@@ -0,0 +1,119 @@
+// mcp.serve over stdio owns the process stdout, these tests run the
+// ego binary as a server and talk json-rpc to it through its pipes
+
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    process::{Command, Stdio},
+    sync::mpsc,
+    time::Duration,
+};
+
+const SERVER: &str = "import mcp
+fn add(a, b) {
+    println(\"adding\")
+    print(a)
+    return a + b
+}
+mcp.serve({ name: \"calc\" }, [add])
+";
+
+const REQUESTS: [&str; 3] = [
+    r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2024-11-05","capabilities":{},"clientInfo":{"name":"test","version":"0"}}}"#,
+    r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#,
+    r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"add","arguments":{"a":1,"b":2}}}"#,
+];
+
+// stdout lines until the response to the tool call, and the stderr
+// once the server exits
+fn serve(args: &[&str]) -> (Vec<String>, String) {
+    let dir = std::env::temp_dir().join(format!("ego_mcp_serve_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let script = dir.join(format!("server_{}.ego", args.len()));
+    std::fs::write(&script, SERVER).unwrap();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ego"))
+        .arg(&script)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let (sender, lines) = mpsc::channel();
+    let stdout = child.stdout.take().unwrap();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stdout).lines() {
+            let Ok(line) = line else { break };
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut stdin = child.stdin.take().unwrap();
+    for request in REQUESTS {
+        writeln!(stdin, "{}", request).unwrap();
+    }
+    stdin.flush().unwrap();
+
+    let mut received = vec![];
+    loop {
+        let line = lines
+            .recv_timeout(Duration::from_secs(10))
+            .expect("the server didn't answer the tool call");
+        let done = line.contains(r#""id":2"#);
+        received.push(line);
+        if done {
+            break;
+        }
+    }
+
+    // closing stdin disconnects the client and ends the server
+    drop(stdin);
+    let mut stderr = String::new();
+    child
+        .stderr
+        .take()
+        .unwrap()
+        .read_to_string(&mut stderr)
+        .unwrap();
+    child.wait().unwrap();
+    let _ = std::fs::remove_file(&script);
+    (received, stderr)
+}
+
+#[test]
+fn keeps_tool_output_off_the_protocol() {
+    let (frames, stderr) = serve(&[]);
+
+    for frame in &frames {
+        assert!(
+            frame.starts_with(r#"{"jsonrpc":"2.0""#),
+            "not a frame: {}",
+            frame
+        );
+    }
+    assert!(frames.last().unwrap().contains(r#""text":"3""#));
+    assert!(stderr.contains("adding\n1"));
+}
+
+#[test]
+fn keeps_debug_traces_off_the_protocol() {
+    let (lines, stderr) = serve(&["-d"]);
+
+    // the compiler dump comes first, the vm traces shouldn't follow
+    let first_frame = lines
+        .iter()
+        .position(|line| line.starts_with(r#"{"jsonrpc":"2.0""#))
+        .unwrap();
+    assert!(!lines.iter().any(|line| line.contains("MCP.SERVE")));
+    for frame in &lines[first_frame..] {
+        assert!(
+            frame.starts_with(r#"{"jsonrpc":"2.0""#),
+            "not a frame: {}",
+            frame
+        );
+    }
+    assert!(stderr.contains("MCP.SERVE -> "));
+}
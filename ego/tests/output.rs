@@ -0,0 +1,43 @@
+// the vm output can be captured by embedders, these tests run ego
+// code with a BufferOutput in place of the terminal
+
+use self_vm::output::{BufferOutput, OutputStream};
+
+fn run(code: &str, args: Vec<String>) -> BufferOutput {
+    let bytecode = ego::gen_bytecode("main.ego".to_string(), code.to_string(), &vec![]);
+    let mut vm = self_vm::new(bytecode);
+    let output = BufferOutput::new();
+    vm.set_output(output.clone());
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let execution = runtime.block_on(vm.run(&args));
+    assert!(execution.error.is_none(), "the code failed to run");
+    output
+}
+
+#[test]
+fn captures_print_and_println() {
+    let output = run(
+        "print(\"a\")\nprint(\"b\")\nprintln(\"c\")\nprintln(1 + 2)\n",
+        vec![],
+    );
+
+    assert_eq!(output.contents(OutputStream::Stdout), "abc\n3\n");
+    assert_eq!(output.contents(OutputStream::Stderr), "");
+    assert_eq!(output.lines(), vec!["abc", "3"]);
+}
+
+#[test]
+fn keeps_debug_traces_apart() {
+    let output = run(
+        "import fs\nprintln(fs.exists(\"/\"))\n",
+        vec!["-d".to_string()],
+    );
+
+    let traces = output.contents(OutputStream::Debug);
+    assert!(traces.contains("FS.EXISTS <- / -> true\n"));
+    assert!(!output.contents(OutputStream::Stdout).contains("FS.EXISTS"));
+}
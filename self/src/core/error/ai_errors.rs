@@ -4,4 +4,6 @@ pub enum AIError {
     AIEngineNotSet(),
     AIEngineNotImplemented(String),
     AIActionForcedAbort(String),
+    AIInvalidResponse(String),
+    AIBudgetExceeded(String),
 }
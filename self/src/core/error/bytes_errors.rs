@@ -0,0 +1,9 @@
+#[derive(Debug)]
+pub enum BytesError {
+    // position of the first invalid byte
+    InvalidUtf8(usize),
+    InvalidHex(String),
+    InvalidBase64(String),
+    InvalidByte(String),
+    OutOfBounds { index: usize, len: usize },
+}
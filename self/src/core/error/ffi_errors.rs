@@ -0,0 +1,27 @@
+#[derive(Debug)]
+pub enum FfiError {
+    SymbolNotFound {
+        lib: String,
+        symbol: String,
+    },
+    UnknownType(String),
+    ArgsCount {
+        symbol: String,
+        expected: u32,
+        received: u32,
+    },
+    ArgType {
+        symbol: String,
+        index: usize,
+        expected: String,
+        received: String,
+    },
+    ReturnType {
+        symbol: String,
+        expected: String,
+        received: String,
+    },
+    InvalidCString(String),
+    UnsupportedSignature(String),
+    PluginError(String),
+}
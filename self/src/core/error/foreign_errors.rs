@@ -0,0 +1,37 @@
+#[derive(Debug)]
+pub enum ForeignError {
+    UnknownHandler(String),
+    InvalidConfig(String),
+    ArgsCount {
+        handler: String,
+        expected: u32,
+        received: u32,
+    },
+    ArgType {
+        handler: String,
+        arg: String,
+        expected: String,
+        received: String,
+    },
+    ReturnType {
+        handler: String,
+        expected: String,
+        received: String,
+    },
+    SpawnError {
+        handler: String,
+        message: String,
+    },
+    Timeout {
+        handler: String,
+        millis: u64,
+    },
+    ProtocolError {
+        handler: String,
+        message: String,
+    },
+    HandlerError {
+        handler: String,
+        message: String,
+    },
+}
@@ -1,9 +1,32 @@
+use std::io::ErrorKind;
+
+// io failures keep the kind of the underlying error (permission
+// denied, already exists...)
 #[derive(Debug)]
 pub enum FsError {
     FileNotFound(String),
     NotAFile(String),
     NotADir(String),
-    ReadError(String),
-    WriteError(String),
-    DeleteError(String),
+    ReadError(String, ErrorKind),
+    WriteError(String, ErrorKind),
+    DeleteError(String, ErrorKind),
+    CreateDirError(String, ErrorKind),
+    CopyError {
+        from: String,
+        to: String,
+        kind: ErrorKind,
+    },
+    CopyIntoItself {
+        from: String,
+        to: String,
+    },
+    RenameError {
+        from: String,
+        to: String,
+        kind: ErrorKind,
+    },
+    InvalidPattern {
+        pattern: String,
+        message: String,
+    },
 }
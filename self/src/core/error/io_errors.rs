@@ -0,0 +1,5 @@
+#[derive(Debug)]
+pub enum IoError {
+    ReadError(String),
+    InvalidStream(String),
+}
@@ -0,0 +1,9 @@
+#[derive(Debug)]
+pub enum McpError {
+    NotConnected(String),
+    InitError(String),
+    RequestError(String),
+    ToolError { tool: String, message: String },
+    ServeError(String),
+    ModuleTaken(String),
+}
@@ -1,17 +1,27 @@
 pub mod action_errors;
 pub mod ai_errors;
+pub mod bytes_errors;
+pub mod ffi_errors;
+pub mod foreign_errors;
 pub mod fs_errors;
+pub mod io_errors;
+pub mod mcp_errors;
 pub mod memory_errors;
 pub mod net_errors;
 pub mod os_errors;
+pub mod process_errors;
 pub mod struct_errors;
+pub mod time_errors;
 pub mod type_errors;
+pub mod ws_errors;
 
 use crate::{
     core::error::{
-        action_errors::ActionError, ai_errors::AIError, fs_errors::FsError,
-        memory_errors::MemoryError, net_errors::NetErrors, os_errors::OsError,
-        struct_errors::StructError, type_errors::TypeError,
+        action_errors::ActionError, ai_errors::AIError, bytes_errors::BytesError,
+        ffi_errors::FfiError, foreign_errors::ForeignError, fs_errors::FsError, io_errors::IoError,
+        mcp_errors::McpError, memory_errors::MemoryError, net_errors::NetErrors,
+        os_errors::OsError, process_errors::ProcessError, struct_errors::StructError,
+        time_errors::TimeError, type_errors::TypeError, ws_errors::WsErrors,
     },
     opcodes::DataType,
     stack::OperandsStackValue,
@@ -30,13 +40,23 @@ pub enum VMErrorType {
     ModuleNotFound(String),
     ExportInvalidMemberType,
     Fs(FsError),
+    Bytes(BytesError),
+    Io(IoError),
     Os(OsError),
     AI(AIError),
     Action(ActionError),
     Net(NetErrors),
+    Ws(WsErrors),
+    Time(TimeError),
+    Mcp(McpError),
+    Ffi(FfiError),
+    Foreign(ForeignError),
+    Process(ProcessError),
     Struct(StructError),
     Memory(MemoryError),
     Any(String),
+    // not an error, os.exit unwinds the execution through it
+    Exit(i32),
 }
 
 #[derive(Debug)]
@@ -111,12 +131,67 @@ pub fn throw(error_type: VMErrorType, vm: &Vm) -> VMError {
             FsError::FileNotFound(s) => ("File not found".to_string(), format!("{}", s)),
             FsError::NotAFile(s) => ("Not a file".to_string(), format!("{}", s)),
             FsError::NotADir(s) => ("Not a directory".to_string(), format!("{}", s)),
-            FsError::ReadError(s) => ("Read error".to_string(), format!("{}", s)),
-            FsError::WriteError(s) => ("Write error".to_string(), format!("{}", s)),
-            FsError::DeleteError(s) => ("Delete error".to_string(), format!("{}", s)),
+            FsError::ReadError(s, kind) => ("Read error".to_string(), format!("{}: {}", s, kind)),
+            FsError::WriteError(s, kind) => ("Write error".to_string(), format!("{}: {}", s, kind)),
+            FsError::DeleteError(s, kind) => {
+                ("Delete error".to_string(), format!("{}: {}", s, kind))
+            }
+            FsError::CreateDirError(s, kind) => (
+                "Cannot create directory".to_string(),
+                format!("{}: {}", s, kind),
+            ),
+            FsError::CopyError { from, to, kind } => (
+                "Copy error".to_string(),
+                format!("{} -> {}: {}", from, to, kind),
+            ),
+            FsError::CopyIntoItself { from, to } => (
+                "Copy error".to_string(),
+                format!("{} is inside the copied directory {}", to, from),
+            ),
+            FsError::RenameError { from, to, kind } => (
+                "Rename error".to_string(),
+                format!("{} -> {}: {}", from, to, kind),
+            ),
+            FsError::InvalidPattern { pattern, message } => (
+                "Invalid glob pattern".to_string(),
+                format!("{}: {}", pattern, message),
+            ),
+        },
+        VMErrorType::Bytes(bytes) => match bytes {
+            BytesError::InvalidUtf8(position) => (
+                "Invalid UTF-8".to_string(),
+                format!("invalid byte at position {}", position),
+            ),
+            BytesError::InvalidHex(s) => ("Invalid hex".to_string(), format!("{}", s)),
+            BytesError::InvalidBase64(s) => ("Invalid base64".to_string(), format!("{}", s)),
+            BytesError::InvalidByte(s) => (
+                "Invalid byte".to_string(),
+                format!("{} is not a number between 0 and 255", s),
+            ),
+            BytesError::OutOfBounds { index, len } => (
+                "Index out of bounds".to_string(),
+                format!("index {}, length {}", index, len),
+            ),
+        },
+        VMErrorType::Io(io) => match io {
+            IoError::ReadError(s) => ("Cannot read from stdin".to_string(), format!("{}", s)),
+            IoError::InvalidStream(s) => (
+                "Invalid stream".to_string(),
+                format!("{} is not one of stdin, stdout or stderr", s),
+            ),
         },
         VMErrorType::Os(os) => match os {
-            OsError::__placeholder(s) => ("dev note: implement this".to_string(), s.clone()),
+            OsError::InvalidPath(s) => ("Invalid path".to_string(), format!("{}", s)),
+            OsError::ChdirError { path, message } => (
+                "Cannot change directory".to_string(),
+                format!("{}: {}", path, message),
+            ),
+            OsError::HostnameError(s) => ("Cannot get hostname".to_string(), format!("{}", s)),
+            OsError::UnknownSignal(s) => ("Unknown signal".to_string(), format!("{}", s)),
+            OsError::SignalError { signal, message } => (
+                "Cannot handle signal".to_string(),
+                format!("{}: {}", signal, message),
+            ),
         },
         VMErrorType::AI(ai) => match ai {
             AIError::AIFetchError(s) => ("AI fetch error".to_string(), format!("{}", s)),
@@ -130,6 +205,8 @@ pub fn throw(error_type: VMErrorType, vm: &Vm) -> VMError {
             AIError::AIActionForcedAbort(s) => {
                 ("AI action forced abort".to_string(), format!("{}", s))
             }
+            AIError::AIInvalidResponse(s) => ("AI invalid response".to_string(), format!("{}", s)),
+            AIError::AIBudgetExceeded(s) => ("AI budget exceeded".to_string(), format!("{}", s)),
         },
         VMErrorType::Action(a) => match a {
             ActionError::InvalidModule(s) => (
@@ -150,9 +227,200 @@ pub fn throw(error_type: VMErrorType, vm: &Vm) -> VMError {
                 format!("couldn't write to {}", s),
             ),
             NetErrors::ReadError(s) => (
-                "Socket write error".to_string(),
+                "Socket read error".to_string(),
                 format!("couldn't read from {}", s),
             ),
+            NetErrors::ListenError { host, message } => (
+                "Cannot listen".to_string(),
+                format!("{}: {}", host, message),
+            ),
+            NetErrors::AcceptError(s) => ("Cannot accept connection".to_string(), format!("{}", s)),
+            NetErrors::TlsError(s) => ("TLS error".to_string(), format!("{}", s)),
+            NetErrors::Timeout { host, ms } => (
+                "Socket timeout".to_string(),
+                format!("no data from {} after {}ms", host, ms),
+            ),
+            NetErrors::Closed(s) => ("Socket closed".to_string(), format!("{}", s)),
+            NetErrors::Busy(s) => (
+                "Socket busy".to_string(),
+                format!("{} is used by another pending operation", s),
+            ),
+        },
+        VMErrorType::Ws(ws) => match ws {
+            WsErrors::InvalidUrl(s) => (
+                "Invalid WebSocket url".to_string(),
+                format!("{} (expected ws:// or wss://)", s),
+            ),
+            WsErrors::InvalidHeader(s) => ("Invalid header".to_string(), format!("{}", s)),
+            WsErrors::ConnectError { url, message } => (
+                "WebSocket connection error".to_string(),
+                format!("{}: {}", url, message),
+            ),
+            WsErrors::ServeError { host, message } => (
+                "Cannot serve WebSocket".to_string(),
+                format!("{}: {}", host, message),
+            ),
+            WsErrors::SendError { url, message } => (
+                "WebSocket send error".to_string(),
+                format!("couldn't send to {}: {}", url, message),
+            ),
+            WsErrors::Closed(s) => ("WebSocket closed".to_string(), format!("{}", s)),
+            WsErrors::Busy(s) => (
+                "WebSocket busy".to_string(),
+                format!("{} is used by another pending send", s),
+            ),
+        },
+        VMErrorType::Time(time) => match time {
+            TimeError::ParseError { text, message } => (
+                "Cannot parse time".to_string(),
+                format!("'{}': {}", text, message),
+            ),
+            TimeError::InvalidPattern(s) => ("Invalid time pattern".to_string(), format!("{}", s)),
+            TimeError::InvalidZone(s) => (
+                "Invalid time zone".to_string(),
+                format!("{} (expected utc, local, an offset like +02:00 or an IANA name like Europe/Paris)", s),
+            ),
+            TimeError::InvalidDuration(s) => (
+                "Invalid duration".to_string(),
+                format!("{} (expected a number of ms or a text like 1h30m)", s),
+            ),
+            TimeError::OutOfRange(s) => ("Time out of range".to_string(), format!("{}", s)),
+        },
+        VMErrorType::Mcp(mcp) => match mcp {
+            McpError::NotConnected(s) => (
+                "MCP client not connected".to_string(),
+                format!("{} was shut down", s),
+            ),
+            McpError::InitError(s) => ("MCP init error".to_string(), format!("{}", s)),
+            McpError::RequestError(s) => ("MCP request error".to_string(), format!("{}", s)),
+            McpError::ServeError(s) => ("MCP serve error".to_string(), format!("{}", s)),
+            McpError::ModuleTaken(s) => (
+                "MCP module name taken".to_string(),
+                format!("'{}' is already a module, register the client with another name", s),
+            ),
+            McpError::ToolError { tool, message } => (
+                "MCP tool error".to_string(),
+                format!("'{}' failed: {}", tool, message),
+            ),
+        },
+        VMErrorType::Ffi(ffi) => match ffi {
+            FfiError::SymbolNotFound { lib, symbol } => (
+                "FFI symbol not found".to_string(),
+                format!("'{}' in {}", symbol, lib),
+            ),
+            FfiError::UnknownType(s) => ("FFI unknown type".to_string(), format!("{}", s)),
+            FfiError::ArgsCount {
+                symbol,
+                expected,
+                received,
+            } => (
+                "FFI signature mismatch".to_string(),
+                format!(
+                    "'{}' expects {} arguments, {} provided",
+                    symbol, expected, received
+                ),
+            ),
+            FfiError::ArgType {
+                symbol,
+                index,
+                expected,
+                received,
+            } => (
+                "FFI signature mismatch".to_string(),
+                format!(
+                    "argument {} of '{}' should be {}, found {}",
+                    index, symbol, expected, received
+                ),
+            ),
+            FfiError::ReturnType {
+                symbol,
+                expected,
+                received,
+            } => (
+                "FFI signature mismatch".to_string(),
+                format!(
+                    "'{}' returned {}, which is not a valid {}",
+                    symbol, received, expected
+                ),
+            ),
+            FfiError::InvalidCString(s) => ("FFI invalid string".to_string(), format!("{}", s)),
+            FfiError::UnsupportedSignature(s) => {
+                ("FFI unsupported signature".to_string(), format!("{}", s))
+            }
+            FfiError::PluginError(s) => ("Plugin error".to_string(), format!("{}", s)),
+        },
+        VMErrorType::Foreign(foreign) => match foreign {
+            ForeignError::UnknownHandler(s) => (
+                "Foreign handler not found".to_string(),
+                format!("'{}' is not declared in foreign.toml", s),
+            ),
+            ForeignError::InvalidConfig(s) => {
+                ("Invalid foreign handlers".to_string(), format!("\n{}", s))
+            }
+            ForeignError::ArgsCount {
+                handler,
+                expected,
+                received,
+            } => (
+                "Foreign signature mismatch".to_string(),
+                format!(
+                    "'{}' expects {} arguments, {} provided",
+                    handler, expected, received
+                ),
+            ),
+            ForeignError::ArgType {
+                handler,
+                arg,
+                expected,
+                received,
+            } => (
+                "Foreign signature mismatch".to_string(),
+                format!(
+                    "argument '{}' of '{}' should be {}, found {}",
+                    arg, handler, expected, received
+                ),
+            ),
+            ForeignError::ReturnType {
+                handler,
+                expected,
+                received,
+            } => (
+                "Foreign signature mismatch".to_string(),
+                format!(
+                    "'{}' should return {}, returned {}",
+                    handler, expected, received
+                ),
+            ),
+            ForeignError::SpawnError { handler, message } => (
+                "Foreign handler spawn error".to_string(),
+                format!("'{}': {}", handler, message),
+            ),
+            ForeignError::Timeout { handler, millis } => (
+                "Foreign handler timeout".to_string(),
+                format!("'{}' didn't answer in {}ms", handler, millis),
+            ),
+            ForeignError::ProtocolError { handler, message } => (
+                "Foreign protocol error".to_string(),
+                format!("'{}': {}", handler, message),
+            ),
+            ForeignError::HandlerError { handler, message } => (
+                "Foreign handler error".to_string(),
+                format!("'{}' failed: {}", handler, message),
+            ),
+        },
+        VMErrorType::Process(process) => match process {
+            ProcessError::SpawnError { command, message } => (
+                "Process spawn error".to_string(),
+                format!("'{}': {}", command, message),
+            ),
+            ProcessError::Timeout { command, millis } => (
+                "Process timeout".to_string(),
+                format!("'{}' didn't finish in {}ms", command, millis),
+            ),
+            ProcessError::IoError { command, message } => (
+                "Process io error".to_string(),
+                format!("'{}': {}", command, message),
+            ),
         },
         VMErrorType::Struct(strc) => match strc {
             StructError::FieldNotFound { field, struct_type } => (
@@ -161,6 +429,7 @@ pub fn throw(error_type: VMErrorType, vm: &Vm) -> VMError {
             ),
         },
         VMErrorType::Any(s) => ("Error".to_string(), format!("{}", s)),
+        VMErrorType::Exit(code) => ("Exit".to_string(), format!("code {}", code)),
     };
 
     VMError {
@@ -4,4 +4,11 @@ pub enum NetErrors {
     NetConnectError(String),
     WriteError(String),
     ReadError(String),
+    ListenError { host: String, message: String },
+    AcceptError(String),
+    TlsError(String),
+    Timeout { host: String, ms: u64 },
+    Closed(String),
+    // the stream is already used by a pending read or write
+    Busy(String),
 }
@@ -1,4 +1,8 @@
 #[derive(Debug)]
 pub enum OsError {
-    __placeholder(String),
+    InvalidPath(String),
+    ChdirError { path: String, message: String },
+    HostnameError(String),
+    UnknownSignal(String),
+    SignalError { signal: String, message: String },
 }
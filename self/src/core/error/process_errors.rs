@@ -0,0 +1,6 @@
+#[derive(Debug)]
+pub enum ProcessError {
+    SpawnError { command: String, message: String },
+    Timeout { command: String, millis: u64 },
+    IoError { command: String, message: String },
+}
@@ -0,0 +1,8 @@
+#[derive(Debug)]
+pub enum TimeError {
+    ParseError { text: String, message: String },
+    InvalidPattern(String),
+    InvalidZone(String),
+    InvalidDuration(String),
+    OutOfRange(String),
+}
@@ -0,0 +1,11 @@
+#[derive(Debug)]
+pub enum WsErrors {
+    InvalidUrl(String),
+    InvalidHeader(String),
+    ConnectError { url: String, message: String },
+    ServeError { host: String, message: String },
+    SendError { url: String, message: String },
+    Closed(String),
+    // the socket is already used by a pending send
+    Busy(String),
+}
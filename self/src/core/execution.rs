@@ -20,6 +20,17 @@ impl VMExecutionResult {
         }
     }
 
+    // os.exit code, the execution unwinds as an Exit error
+    pub fn exit_code(&self) -> Option<i32> {
+        match &self.error {
+            Some(VMError {
+                error_type: VMErrorType::Exit(code),
+                ..
+            }) => Some(*code),
+            _ => None,
+        }
+    }
+
     pub fn terminate_with_errors(error_type: VMErrorType, vm: &Vm) -> VMExecutionResult {
         VMExecutionResult {
             error: Some(throw(error_type, vm)),
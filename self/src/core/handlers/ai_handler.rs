@@ -12,6 +12,8 @@ use reqwest::blocking::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use crate::vm::Vm;
+
 #[derive(Serialize)]
 struct Message {
     role: String,
@@ -65,11 +67,11 @@ fn ai_response_parser(response: &String) -> Option<(String, String)> {
     }
 }
 
-pub fn ai_handler(args: Vec<String>, debug: bool) -> Option<(String, String)> {
+pub fn ai_handler(vm: &Vm, args: Vec<String>, debug: bool) -> Option<(String, String)> {
     let request = args[0].clone();
     let context = args[1].clone();
     if debug {
-        println!("AI <- {}({})", request, context);
+        vm.trace(format!("AI <- {}({})", request, context));
     }
     // we should try to avoid prompt injection
     // maybe using multiple prompts?
@@ -125,7 +127,7 @@ context: {{ 'arg': {context} }}
         .expect("AI: Failed to send request");
 
     if !res.status().is_success() {
-        println!("AI (FAILED) -> {}", res.status());
+        vm.trace(format!("AI (FAILED) -> {}", res.status()));
         return None;
     }
 
@@ -133,7 +135,7 @@ context: {{ 'arg': {context} }}
     let answer = &response.choices[0].message.content;
 
     if debug {
-        println!("AI -> {}", answer);
+        vm.trace(format!("AI -> {}", answer));
     }
 
     let parsed_answer = ai_response_parser(answer);
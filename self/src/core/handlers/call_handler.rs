@@ -1,33 +1,300 @@
-use std::process::Command;
+use std::{process::Stdio, sync::Mutex, time::Duration};
 
-use super::foreign_handlers::ForeignHandlers;
+use serde_json::{json, Value as JsonValue};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines},
+    process::{ChildStdout, Command},
+};
 
-pub fn call_handler(foreign_handlers: &ForeignHandlers, args: Vec<String>) {
-    if args.len() < 1 {
-        panic!("Call handler requires at least 1 arg");
+use crate::{
+    core::error::foreign_errors::ForeignError,
+    output::{OutputStream, VmOutput},
+};
+
+use super::foreign_handlers::{
+    json_type_name, matches_type, ForeignHandler, ForeignHandlers, ForeignProtocol, ForeignWorker,
+};
+
+// `output` is the vm sink, where the handlers logs go
+pub async fn call_handler(
+    foreign_handlers: &mut ForeignHandlers,
+    output: &Mutex<Box<dyn VmOutput>>,
+    name: &str,
+    args: Vec<JsonValue>,
+) -> Result<JsonValue, ForeignError> {
+    if !foreign_handlers.errors.is_empty() {
+        let errors: Vec<String> = foreign_handlers
+            .errors
+            .iter()
+            .map(|e| e.to_string())
+            .collect();
+        return Err(ForeignError::InvalidConfig(errors.join("\n")));
     }
 
-    let handler = foreign_handlers.handlers.get(&args[0]);
-    let handler = match handler {
-        Some(val) => val,
-        None => panic!("Calling an unset handler"),
+    let handler = match foreign_handlers.handlers.get(name) {
+        Some(val) => val.clone(),
+        None => return Err(ForeignError::UnknownHandler(name.to_string())),
     };
+    check_args(&handler, &args)?;
 
-    spawn_process(&handler.runtime, &handler.script, args[1..].to_vec());
+    let call = async {
+        match handler.protocol {
+            ForeignProtocol::Args => call_with_args(&handler, &args).await,
+            ForeignProtocol::Json if handler.worker => {
+                call_worker(foreign_handlers, output, &handler, &args).await
+            }
+            ForeignProtocol::Json => call_once(output, &handler, &args).await,
+        }
+    };
+    let result = match handler.timeout {
+        Some(millis) => match tokio::time::timeout(Duration::from_millis(millis), call).await {
+            Ok(result) => result,
+            Err(_) => {
+                // the worker could answer this request later on, so
+                // it's not reusable anymore. dropping it kills it
+                foreign_handlers.workers.remove(name);
+                Err(ForeignError::Timeout {
+                    handler: name.to_string(),
+                    millis,
+                })
+            }
+        },
+        None => call.await,
+    }?;
+
+    if let Some(returns) = &handler.returns {
+        if !matches_type(returns, &result) {
+            return Err(ForeignError::ReturnType {
+                handler: handler.name.clone(),
+                expected: returns.clone(),
+                received: json_type_name(&result),
+            });
+        }
+    }
+    Ok(result)
 }
 
-fn spawn_process(binary: &String, script: &String, args: Vec<String>) {
-    let output = Command::new(binary).arg(script).args(args).output();
-    let output = match output {
-        Ok(val) => val,
-        Err(_) => panic!("Cannot spawn a foreign handler"),
+fn check_args(handler: &ForeignHandler, args: &[JsonValue]) -> Result<(), ForeignError> {
+    let Some(declared) = &handler.args else {
+        return Ok(());
     };
+    if declared.len() != args.len() {
+        return Err(ForeignError::ArgsCount {
+            handler: handler.name.clone(),
+            expected: declared.len() as u32,
+            received: args.len() as u32,
+        });
+    }
+    for (arg, value) in declared.iter().zip(args) {
+        if !matches_type(&arg.arg_type, value) {
+            return Err(ForeignError::ArgType {
+                handler: handler.name.clone(),
+                arg: arg.name.clone(),
+                expected: arg.arg_type.clone(),
+                received: json_type_name(value),
+            });
+        }
+    }
+    Ok(())
+}
 
-    if output.status.success() {
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        println!("{}", stdout);
-    } else {
+// legacy protocol, every value goes as a cli argument
+async fn call_with_args(
+    handler: &ForeignHandler,
+    args: &[JsonValue],
+) -> Result<JsonValue, ForeignError> {
+    let args = args.iter().map(|a| match a {
+        JsonValue::String(s) => s.clone(),
+        other => other.to_string(),
+    });
+    let output = Command::new(&handler.runtime)
+        .arg(&handler.script)
+        .args(args)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| spawn_error(handler, e))?;
+
+    if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        eprintln!("Error executing foreign handler:\n{}", stderr);
+        return Err(ForeignError::HandlerError {
+            handler: handler.name.clone(),
+            message: format!("{}\n{}", output.status, stderr.trim_end()),
+        });
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stdout = stdout.trim_end_matches(['\n', '\r']).to_string();
+    // stdout is the returned string, unless another type is declared
+    match handler.returns.as_deref() {
+        None | Some("string") | Some("any") => Ok(JsonValue::String(stdout)),
+        Some("nothing") => Ok(JsonValue::Null),
+        Some(_) => serde_json::from_str(&stdout).map_err(|_| ForeignError::ProtocolError {
+            handler: handler.name.clone(),
+            message: format!("cannot parse output '{}'", stdout),
+        }),
+    }
+}
+
+// one process per call: the request is written, stdin closed and
+// the first response read. stdin is closed before reading, scripts
+// can read it until eof
+async fn call_once(
+    output: &Mutex<Box<dyn VmOutput>>,
+    handler: &ForeignHandler,
+    args: &[JsonValue],
+) -> Result<JsonValue, ForeignError> {
+    let mut worker = spawn_worker(handler)?;
+    let id = send_request(handler, &mut worker, args).await?;
+    drop(worker.stdin);
+    let answer = read_response(handler, &mut worker.stdout, output, id).await;
+    let _ = worker.child.wait().await;
+    answer
+}
+
+async fn call_worker(
+    foreign_handlers: &mut ForeignHandlers,
+    output: &Mutex<Box<dyn VmOutput>>,
+    handler: &ForeignHandler,
+    args: &[JsonValue],
+) -> Result<JsonValue, ForeignError> {
+    if !foreign_handlers.workers.contains_key(&handler.name) {
+        let worker = spawn_worker(handler)?;
+        foreign_handlers
+            .workers
+            .insert(handler.name.clone(), worker);
+    }
+    let worker = foreign_handlers.workers.get_mut(&handler.name).unwrap();
+
+    let answer = match send_request(handler, worker, args).await {
+        Ok(id) => read_response(handler, &mut worker.stdout, output, id).await,
+        Err(e) => Err(e),
+    };
+    // a broken worker is respawned on the next call
+    if let Err(ForeignError::ProtocolError { .. }) = answer {
+        foreign_handlers.workers.remove(&handler.name);
+    }
+    answer
+}
+
+fn spawn_worker(handler: &ForeignHandler) -> Result<ForeignWorker, ForeignError> {
+    let mut child = Command::new(&handler.runtime)
+        .arg(&handler.script)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| spawn_error(handler, e))?;
+
+    let stdin = child.stdin.take().unwrap();
+    let stdout = BufReader::new(child.stdout.take().unwrap()).lines();
+    Ok(ForeignWorker {
+        child,
+        stdin,
+        stdout,
+        next_id: 1,
+    })
+}
+
+fn protocol_error(handler: &ForeignHandler, message: String) -> ForeignError {
+    ForeignError::ProtocolError {
+        handler: handler.name.clone(),
+        message,
+    }
+}
+
+// json-rpc 2.0 request, returns its id. params are named when the
+// args are declared
+async fn send_request(
+    handler: &ForeignHandler,
+    worker: &mut ForeignWorker,
+    args: &[JsonValue],
+) -> Result<u64, ForeignError> {
+    let id = worker.next_id;
+    worker.next_id += 1;
+    let params = match &handler.args {
+        Some(declared) => JsonValue::Object(
+            declared
+                .iter()
+                .zip(args)
+                .map(|(arg, value)| (arg.name.clone(), value.clone()))
+                .collect(),
+        ),
+        None => JsonValue::Array(args.to_vec()),
+    };
+    let message = json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": handler.name,
+        "params": params,
+    });
+
+    let line = format!("{}\n", message);
+    worker
+        .stdin
+        .write_all(line.as_bytes())
+        .await
+        .map_err(|e| protocol_error(handler, format!("cannot write request: {}", e)))?;
+    worker
+        .stdin
+        .flush()
+        .await
+        .map_err(|e| protocol_error(handler, format!("cannot write request: {}", e)))?;
+    Ok(id)
+}
+
+// stdout lines that are not the response are written to the vm
+// output, so the handlers can still log
+async fn read_response(
+    handler: &ForeignHandler,
+    stdout: &mut Lines<BufReader<ChildStdout>>,
+    output: &Mutex<Box<dyn VmOutput>>,
+    id: u64,
+) -> Result<JsonValue, ForeignError> {
+    loop {
+        let line = stdout
+            .next_line()
+            .await
+            .map_err(|e| protocol_error(handler, format!("cannot read response: {}", e)))?;
+        let Some(line) = line else {
+            return Err(protocol_error(
+                handler,
+                "process exited without answering".to_string(),
+            ));
+        };
+
+        let response = match serde_json::from_str::<JsonValue>(&line) {
+            Ok(response) if response.get("jsonrpc").is_some() => response,
+            _ => {
+                output
+                    .lock()
+                    .unwrap()
+                    .write(OutputStream::Stdout, &format!("{}\n", line));
+                continue;
+            }
+        };
+        if response.get("id") != Some(&json!(id)) {
+            continue;
+        }
+
+        if let Some(error) = response.get("error") {
+            let message = error
+                .get("message")
+                .and_then(|m| m.as_str())
+                .map(String::from)
+                .unwrap_or(error.to_string());
+            return Err(ForeignError::HandlerError {
+                handler: handler.name.clone(),
+                message,
+            });
+        }
+        return Ok(response.get("result").cloned().unwrap_or(JsonValue::Null));
+    }
+}
+
+fn spawn_error(handler: &ForeignHandler, err: std::io::Error) -> ForeignError {
+    ForeignError::SpawnError {
+        handler: handler.name.clone(),
+        message: format!("{} {}: {}", handler.runtime, handler.script, err),
     }
 }
@@ -1,38 +1,140 @@
 use serde::Deserialize;
-use std::collections::HashMap;
+use serde_json::Value as JsonValue;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+use tokio::{
+    io::{BufReader, Lines},
+    process::{Child, ChildStdin, ChildStdout},
+};
 
-#[derive(Debug, Deserialize)]
+use crate::utils::foreign_handlers_utils::{get_foreign_handlers, ForeignConfigError};
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
 pub struct Argument {
     pub name: String,
+    #[serde(rename = "type", alias = "arg_type", default = "any_type")]
     pub arg_type: String,
 }
 
-#[derive(Debug, Deserialize)]
+fn any_type() -> String {
+    "any".to_string()
+}
+
+// how the values are sent to the foreign runtime:
+// - args: stringified as command line arguments, stdout is the result
+// - json: json-rpc 2.0 messages, one per line, over stdin/stdout
+#[derive(Debug, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ForeignProtocol {
+    #[default]
+    Args,
+    Json,
+}
+
+//   [[functions]]
+//   name = "sum"
+//   runtime = "node"
+//   script = "foreigns/sum.js"
+//   protocol = "json"
+//   args = [{ name = "a", type = "number" }, { name = "b", type = "number" }]
+//   returns = "number"
+//   timeout = 5000 # ms
+//   worker = true  # keep the process alive between calls (json only)
+#[derive(Debug, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
 pub struct ForeignHandler {
     pub name: String,
     pub runtime: String,
     pub script: String,
-    // pub args: Vec<Argument>,
+    pub args: Option<Vec<Argument>>,
+    pub returns: Option<String>,
+    #[serde(default)]
+    pub protocol: ForeignProtocol,
+    pub timeout: Option<u64>,
+    #[serde(default)]
+    pub worker: bool,
+    // file where the handler was declared
+    #[serde(skip)]
+    pub source: PathBuf,
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct ForeignHandlersToml {
+    #[serde(default)]
     pub functions: Vec<ForeignHandler>,
 }
 
+// a long-lived foreign process, reused by every call to its handler
+#[derive(Debug)]
+pub struct ForeignWorker {
+    pub child: Child,
+    pub stdin: ChildStdin,
+    pub stdout: Lines<BufReader<ChildStdout>>,
+    pub next_id: u64,
+}
+
 #[derive(Debug)]
 pub struct ForeignHandlers {
     pub handlers: HashMap<String, ForeignHandler>,
+    pub workers: HashMap<String, ForeignWorker>,
+    // invalid handler files, reported when a handler is called
+    pub errors: Vec<ForeignConfigError>,
 }
 
 impl ForeignHandlers {
     pub fn new() -> ForeignHandlers {
         ForeignHandlers {
             handlers: HashMap::new(),
+            workers: HashMap::new(),
+            errors: vec![],
         }
     }
 
+    pub fn load(entry: Option<&Path>) -> ForeignHandlers {
+        let config = get_foreign_handlers(entry);
+        let mut handlers = ForeignHandlers::new();
+        for handler in config.handlers {
+            handlers.add(handler);
+        }
+        handlers.errors = config.errors;
+        handlers
+    }
+
     pub fn add(&mut self, handler: ForeignHandler) {
         self.handlers.insert(handler.name.clone(), handler);
     }
 }
+
+pub const FOREIGN_TYPES: [&str; 9] = [
+    "string", "number", "int", "bool", "object", "vector", "array", "nothing", "any",
+];
+
+// declared types of args and returns
+pub fn matches_type(data_type: &str, value: &JsonValue) -> bool {
+    match data_type {
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "int" => value.is_i64() || value.is_u64(),
+        "bool" => value.is_boolean(),
+        "object" => value.is_object(),
+        "vector" | "array" => value.is_array(),
+        "nothing" => value.is_null(),
+        _ => true,
+    }
+}
+
+pub fn json_type_name(value: &JsonValue) -> String {
+    match value {
+        JsonValue::Null => "nothing",
+        JsonValue::Bool(_) => "bool",
+        JsonValue::Number(_) => "number",
+        JsonValue::String(_) => "string",
+        JsonValue::Array(_) => "vector",
+        JsonValue::Object(_) => "object",
+    }
+    .to_string()
+}
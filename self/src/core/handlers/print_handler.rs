@@ -1,21 +1,16 @@
-pub fn print_handler(args: Vec<String>, debug: bool, newline_end: bool) {
+use crate::{output::OutputStream, vm::Vm};
+
+pub fn print_handler(vm: &Vm, args: Vec<String>, debug: bool, newline_end: bool) {
     for arg in args {
         if debug {
-            println!("PRINTLN -> {arg}");
+            vm.trace(format!("PRINTLN -> {arg}"));
         } else {
-            let mut iter = arg.split("\\n").enumerate().peekable();
-
-            while let Some((_index, string)) = iter.next() {
-                if iter.peek().is_none() {
-                    print!("{}", string);
-                } else {
-                    println!("{}", string);
-                }
-            }
+            // `\n` is kept literally on strings, here it becomes a line break
+            vm.write_output(OutputStream::Stdout, &arg.replace("\\n", "\n"));
         }
     }
 
     if newline_end {
-        print!("\n")
-    };
+        vm.write_output(OutputStream::Stdout, "\n");
+    }
 }
@@ -1,5 +1,56 @@
-use crate::types::object::func::Function;
+use std::sync::Arc;
+
+use serde_json::Value as JsonValue;
+use tokio::sync::{oneshot, watch};
+
+use crate::{
+    types::{object::func::Function, Value},
+    vm::Vm,
+};
+
+// builds the arguments of a CallWith event on the vm side
+pub type ArgsBuilder = Box<dyn FnOnce(&mut Vm) -> Vec<Value> + Send>;
+
+// work that needs the vm but calls no function, see Event::Run
+pub type VmTask = Box<dyn FnOnce(&mut Vm) + Send>;
 
 pub enum Event {
-    Call(Function),
+    // callback invocation with its arguments. arguments travel
+    // as json since they're produced outside of the vm and
+    // cannot be allocated until the event is drained
+    Call(Function, Vec<JsonValue>),
+    // same as Call, but the producer waits for the returned
+    // value (or the error message) through the reply channel
+    Request(
+        Function,
+        Vec<JsonValue>,
+        oneshot::Sender<Result<JsonValue, String>>,
+    ),
+    // same as Call, for arguments that cannot travel as json
+    // (like native structs or bytes). they're built once the
+    // event is drained
+    CallWith(Function, ArgsBuilder),
+    // runs on the vm side without calling anything, like releasing
+    // a handle that a producer retained while it was running
+    Run(VmTask),
+}
+
+// held by producers that keep sending events once the bytecode
+// ends (like fs.watch). the vm keeps handling events until every
+// guard is dropped, see Vm::keep_alive
+pub struct KeepAlive {
+    active: Arc<watch::Sender<usize>>,
+}
+
+impl KeepAlive {
+    pub fn new(active: Arc<watch::Sender<usize>>) -> KeepAlive {
+        active.send_modify(|count| *count += 1);
+        KeepAlive { active }
+    }
+}
+
+impl Drop for KeepAlive {
+    fn drop(&mut self) {
+        self.active.send_modify(|count| *count -= 1);
+    }
 }
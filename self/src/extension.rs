@@ -0,0 +1,36 @@
+// embedding api. everything a host application needs to extend the
+// vm with its own modules and types:
+//
+//   fn hello(vm: &mut Vm, _self: Option<Handle>, params: Vec<Value>, debug: bool)
+//       -> Result<Value, VMError> { ... }
+//
+//   vm.register_module("greeter", vec![
+//       Function::new("hello".to_string(), vec!["name".to_string()], Engine::Native(hello)),
+//   ]);
+//
+// host types implement HostObject and are allocated as
+// MemObject::NativeStruct(NativeStruct::Custom(Box::new(obj)))
+
+pub use crate::{
+    core::{
+        error::{throw, VMError, VMErrorType},
+        execution::VMExecutionResult,
+    },
+    memory::{Handle, MemObject},
+    std::{
+        heap_utils::{put_string, put_vector},
+        native::plugin::{EgoPluginMember, EgoPluginModule, EGO_PLUGIN_ABI_VERSION},
+        utils::{json_to_value, value_to_json},
+    },
+    types::{
+        object::{
+            func::{Engine, Function},
+            host::HostObject,
+            native_struct::NativeStruct,
+            structs::StructLiteral,
+        },
+        raw::{bool::Bool, f64::F64, i32::I32, i64::I64, u32::U32, u64::U64, utf8::Utf8, RawValue},
+        Value,
+    },
+    vm::Vm,
+};
@@ -9,6 +9,8 @@ mod std;
 mod translator;
 mod types;
 
+pub mod extension;
+pub mod output;
 pub mod utils;
 pub mod vm;
 pub use opcodes::get_codes_map;
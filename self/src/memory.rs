@@ -4,6 +4,7 @@ use crate::{
     core::error::{self, memory_errors::MemoryError, VMError, VMErrorType},
     heap::{Heap, HeapRef},
     types::object::{
+        bytes::Bytes,
         func::Function,
         native_struct::NativeStruct,
         string::SelfString,
@@ -36,7 +37,8 @@ impl MemoryManager {
             | MemObject::NativeStruct(_)
             | MemObject::StructDeclaration(_)
             | MemObject::StructLiteral(_)
-            | MemObject::Vector(_) => {
+            | MemObject::Vector(_)
+            | MemObject::Bytes(_) => {
                 let heap_ref = self.heap.allocate(obj);
                 self.gen_handle(PointerType::HeapPointer(heap_ref))
             }
@@ -52,7 +54,8 @@ impl MemoryManager {
             | MemObject::NativeStruct(_)
             | MemObject::StructDeclaration(_)
             | MemObject::StructLiteral(_)
-            | MemObject::Vector(_) => {
+            | MemObject::Vector(_)
+            | MemObject::Bytes(_) => {
                 // free handle from table
                 let heap_ref = self.free_handle(&handle).1.as_heap_pointer();
                 // free heap
@@ -198,6 +201,7 @@ pub enum MemObject {
     StructLiteral(StructLiteral),
     NativeStruct(NativeStruct),
     Vector(Vector),
+    Bytes(Bytes),
 }
 
 impl MemObject {
@@ -209,6 +213,7 @@ impl MemObject {
             MemObject::StructLiteral(x) => x.struct_type.to_string(),
             MemObject::NativeStruct(x) => x.to_string(vm),
             MemObject::Vector(x) => x.to_string(vm),
+            MemObject::Bytes(x) => x.to_string(),
         }
     }
 
@@ -220,6 +225,7 @@ impl MemObject {
             MemObject::StructLiteral(_) => "struct_literal".to_string(),
             MemObject::NativeStruct(_) => "native_struct".to_string(),
             MemObject::Vector(_) => "vector".to_string(),
+            MemObject::Bytes(_) => "bytes".to_string(),
         }
     }
 
@@ -0,0 +1,150 @@
+use std::{
+    io::Write,
+    sync::{Arc, Mutex},
+};
+
+// where a piece of output comes from. print/println write to stdout,
+// error reports to stderr and the -d traces to debug
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+    Debug,
+}
+
+// sink for everything the vm writes. the default one is the process
+// stdout/stderr, embedders can capture the output replacing it with
+// Vm::set_output
+pub trait VmOutput: Send {
+    fn write(&mut self, stream: OutputStream, text: &str);
+    fn flush(&mut self) {}
+}
+
+// process stdout and stderr, debug traces go to stdout
+pub struct StdOutput;
+
+impl VmOutput for StdOutput {
+    fn write(&mut self, stream: OutputStream, text: &str) {
+        // a closed pipe shouldn't bring the vm down
+        let _ = match stream {
+            OutputStream::Stdout | OutputStream::Debug => {
+                std::io::stdout().write_all(text.as_bytes())
+            }
+            OutputStream::Stderr => std::io::stderr().write_all(text.as_bytes()),
+        };
+    }
+
+    fn flush(&mut self) {
+        let _ = std::io::stdout().flush();
+        let _ = std::io::stderr().flush();
+    }
+}
+
+// everything to the process stderr, for when stdout carries
+// something else (like the frames of mcp.serve over stdio)
+pub struct StderrOutput;
+
+impl VmOutput for StderrOutput {
+    fn write(&mut self, _stream: OutputStream, text: &str) {
+        let _ = std::io::stderr().write_all(text.as_bytes());
+    }
+
+    fn flush(&mut self) {
+        let _ = std::io::stderr().flush();
+    }
+}
+
+// keeps the output in memory. it's cloned before handing it to the
+// vm, all the clones share the same buffer
+#[derive(Clone, Default)]
+pub struct BufferOutput {
+    entries: Arc<Mutex<Vec<(OutputStream, String)>>>,
+}
+
+impl BufferOutput {
+    pub fn new() -> BufferOutput {
+        BufferOutput::default()
+    }
+
+    // every write in order, with the stream it was written to
+    pub fn entries(&self) -> Vec<(OutputStream, String)> {
+        self.entries.lock().unwrap().clone()
+    }
+
+    // text written to the given stream
+    pub fn contents(&self, stream: OutputStream) -> String {
+        self.entries
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(s, _)| *s == stream)
+            .map(|(_, text)| text.as_str())
+            .collect()
+    }
+
+    // all the streams merged in the order they were written
+    pub fn lines(&self) -> Vec<String> {
+        let text: String = self
+            .entries
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(_, text)| text.as_str())
+            .collect();
+        text.lines().map(|line| line.to_string()).collect()
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
+
+impl VmOutput for BufferOutput {
+    fn write(&mut self, stream: OutputStream, text: &str) {
+        self.entries
+            .lock()
+            .unwrap()
+            .push((stream, text.to_string()));
+    }
+}
+
+// hands every write to a closure
+pub struct CallbackOutput<F>(pub F)
+where
+    F: FnMut(OutputStream, &str) + Send;
+
+impl<F> VmOutput for CallbackOutput<F>
+where
+    F: FnMut(OutputStream, &str) + Send,
+{
+    fn write(&mut self, stream: OutputStream, text: &str) {
+        (self.0)(stream, text)
+    }
+}
+
+// writes to both sinks, e.g. the terminal and a buffer
+pub struct TeeOutput {
+    first: Box<dyn VmOutput>,
+    second: Box<dyn VmOutput>,
+}
+
+impl TeeOutput {
+    pub fn new(first: impl VmOutput + 'static, second: impl VmOutput + 'static) -> TeeOutput {
+        TeeOutput {
+            first: Box::new(first),
+            second: Box::new(second),
+        }
+    }
+}
+
+impl VmOutput for TeeOutput {
+    fn write(&mut self, stream: OutputStream, text: &str) {
+        self.first.write(stream, text);
+        self.second.write(stream, text);
+    }
+
+    fn flush(&mut self) {
+        self.first.flush();
+        self.second.flush();
+    }
+}
@@ -0,0 +1,159 @@
+// on-disk cache of ai answers. opt-in with SELF_AI_CACHE=1, entries
+// are addressed by the hash of provider, model and prompt so the
+// same prompt is only paid once
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    std::ai::providers::{engine_name, model_name},
+    utils::foreign_handlers_utils::project_dir,
+};
+
+const DEFAULT_TTL_SECS: u64 = 7 * 24 * 60 * 60;
+const DEFAULT_MAX_MB: u64 = 50;
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    created_at: u64,
+    provider: String,
+    model: String,
+    answer: String,
+}
+
+pub struct AICache {
+    pub enabled: bool,
+    dir: PathBuf,
+    ttl: Duration,
+    max_bytes: u64,
+}
+
+impl AICache {
+    pub fn new() -> AICache {
+        let env_u64 = |key: &str, default: u64| {
+            env::var(key)
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(default)
+        };
+        let enabled = matches!(env::var("SELF_AI_CACHE").as_deref(), Ok("1") | Ok("true"));
+        // resolved now, so a later os.chdir doesn't move the cache
+        let dir = match env::var("SELF_AI_CACHE_DIR") {
+            Ok(dir) => env::current_dir().unwrap_or_default().join(dir),
+            Err(_) => default_dir(&project_dir(None)),
+        };
+
+        AICache {
+            enabled,
+            dir,
+            ttl: Duration::from_secs(env_u64("SELF_AI_CACHE_TTL", DEFAULT_TTL_SECS)),
+            max_bytes: env_u64("SELF_AI_CACHE_MAX_MB", DEFAULT_MAX_MB) * 1024 * 1024,
+        }
+    }
+
+    // the cache is kept under the project of the module being run,
+    // unless SELF_AI_CACHE_DIR is set
+    pub fn set_project(&mut self, root: &Path) {
+        if env::var("SELF_AI_CACHE_DIR").is_err() {
+            self.dir = default_dir(root);
+        }
+    }
+
+    pub fn key(&self, prompt: &str) -> String {
+        let mut hasher = Sha256::new();
+        for part in [engine_name().as_str(), model_name().as_str(), prompt] {
+            hasher.update(part.as_bytes());
+            // separator, so ("ab", "c") and ("a", "bc") don't collide
+            hasher.update([0u8]);
+        }
+        hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    pub fn get(&self, key: &str) -> Option<String> {
+        if !self.enabled {
+            return None;
+        }
+
+        let path = self.dir.join(format!("{}.json", key));
+        let content = fs::read_to_string(&path).ok()?;
+        let entry: CacheEntry = serde_json::from_str(&content).ok()?;
+
+        if now_secs().saturating_sub(entry.created_at) > self.ttl.as_secs() {
+            let _ = fs::remove_file(&path);
+            return None;
+        }
+        Some(entry.answer)
+    }
+
+    // cache failures are not errors, the answer is still valid
+    pub fn put(&self, key: &str, answer: &str) {
+        if !self.enabled || fs::create_dir_all(&self.dir).is_err() {
+            return;
+        }
+
+        let entry = CacheEntry {
+            created_at: now_secs(),
+            provider: engine_name(),
+            model: model_name(),
+            answer: answer.to_string(),
+        };
+        let Ok(content) = serde_json::to_string(&entry) else {
+            return;
+        };
+        if fs::write(self.dir.join(format!("{}.json", key)), content).is_ok() {
+            self.evict();
+        }
+    }
+
+    // removes the oldest entries until the cache fits in max_bytes
+    fn evict(&self) {
+        let Ok(read_dir) = fs::read_dir(&self.dir) else {
+            return;
+        };
+
+        let mut entries: Vec<(PathBuf, u64, SystemTime)> = read_dir
+            .filter_map(|e| e.ok())
+            .filter_map(|e| {
+                let metadata = e.metadata().ok()?;
+                let modified = metadata.modified().unwrap_or(UNIX_EPOCH);
+                Some((e.path(), metadata.len(), modified))
+            })
+            .collect();
+
+        let mut total: u64 = entries.iter().map(|(_, size, _)| size).sum();
+        if total <= self.max_bytes {
+            return;
+        }
+
+        entries.sort_by_key(|(_, _, modified)| *modified);
+        for (path, size, _) in entries {
+            if total <= self.max_bytes {
+                break;
+            }
+            if fs::remove_file(&path).is_ok() {
+                total -= size;
+            }
+        }
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn default_dir(root: &Path) -> PathBuf {
+    root.join(".ego").join("ai_cache")
+}
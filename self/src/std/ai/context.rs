@@ -0,0 +1,154 @@
+// ai state that lives as long as the vm: token usage of every
+// request, the configured budgets, the response cache and the
+// mcp servers registered as actions
+
+use std::env;
+
+use crate::{
+    core::error::ai_errors::AIError,
+    std::ai::{cache::AICache, mcp::AIMcpModule, providers::Usage},
+};
+
+#[derive(Debug, Clone)]
+pub struct AICallUsage {
+    pub model: String,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+}
+
+impl AICallUsage {
+    pub fn total_tokens(&self) -> u64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+
+    // estimated cost in usd
+    pub fn cost(&self) -> f64 {
+        let (input, output) = model_prices(&self.model);
+        (self.prompt_tokens as f64 * input + self.completion_tokens as f64 * output) / 1_000_000.0
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AIBudget {
+    pub max_tokens: Option<u64>,
+    pub max_requests: Option<u64>,
+}
+
+pub struct AIContext {
+    pub calls: Vec<AICallUsage>,
+    pub budget: AIBudget,
+    pub cache: AICache,
+    pub mcp_modules: Vec<AIMcpModule>,
+}
+
+impl AIContext {
+    pub fn new() -> AIContext {
+        let from_env = |key: &str| env::var(key).ok().and_then(|v| v.parse::<u64>().ok());
+
+        AIContext {
+            calls: vec![],
+            budget: AIBudget {
+                max_tokens: from_env("SELF_AI_MAX_TOKENS"),
+                max_requests: from_env("SELF_AI_MAX_REQUESTS"),
+            },
+            cache: AICache::new(),
+            mcp_modules: vec![],
+        }
+    }
+
+    pub fn record(&mut self, model: String, usage: Option<Usage>) {
+        let usage = usage.unwrap_or_default();
+        self.calls.push(AICallUsage {
+            model,
+            prompt_tokens: usage.prompt_tokens,
+            completion_tokens: usage.completion_tokens,
+        });
+    }
+
+    // registering the same module name again replaces it
+    pub fn register_mcp(&mut self, module: AIMcpModule) {
+        self.mcp_modules.retain(|m| m.module != module.module);
+        self.mcp_modules.push(module);
+    }
+
+    pub fn mcp_module(&self, module: &str) -> Option<&AIMcpModule> {
+        self.mcp_modules.iter().find(|m| m.module == module)
+    }
+
+    pub fn requests(&self) -> u64 {
+        self.calls.len() as u64
+    }
+
+    pub fn prompt_tokens(&self) -> u64 {
+        self.calls.iter().map(|c| c.prompt_tokens).sum()
+    }
+
+    pub fn completion_tokens(&self) -> u64 {
+        self.calls.iter().map(|c| c.completion_tokens).sum()
+    }
+
+    pub fn total_tokens(&self) -> u64 {
+        self.calls.iter().map(|c| c.total_tokens()).sum()
+    }
+
+    pub fn cost(&self) -> f64 {
+        self.calls.iter().map(|c| c.cost()).sum()
+    }
+
+    // checked before sending a request and after recording its usage
+    pub fn check_budget(&self) -> Result<(), AIError> {
+        if let Some(max) = self.budget.max_requests {
+            if self.requests() >= max {
+                return Err(AIError::AIBudgetExceeded(format!(
+                    "max requests reached ({}/{})",
+                    self.requests(),
+                    max
+                )));
+            }
+        }
+        if let Some(max) = self.budget.max_tokens {
+            if self.total_tokens() >= max {
+                return Err(AIError::AIBudgetExceeded(format!(
+                    "max tokens reached ({}/{})",
+                    self.total_tokens(),
+                    max
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn report(&self) -> Option<String> {
+        if self.calls.is_empty() {
+            return None;
+        }
+        Some(format!(
+            "AI usage: {} requests, {} tokens ({} prompt, {} completion), ~${:.4}",
+            self.requests(),
+            self.total_tokens(),
+            self.prompt_tokens(),
+            self.completion_tokens(),
+            self.cost()
+        ))
+    }
+}
+
+// usd per 1M tokens (input, output). unknown models are not priced
+fn model_prices(model: &str) -> (f64, f64) {
+    let prices = [
+        ("gpt-4o-mini", (0.15, 0.6)),
+        ("gpt-4o", (2.5, 10.0)),
+        ("gpt-4.1-mini", (0.4, 1.6)),
+        ("gpt-4.1", (2.0, 8.0)),
+        ("mistral-small", (0.2, 0.6)),
+        ("mistral-medium", (0.4, 2.0)),
+        ("mistral-large", (2.0, 6.0)),
+    ];
+
+    // providers answer with versioned names (gpt-4o-2024-08-06)
+    prices
+        .iter()
+        .find(|(name, _)| model.starts_with(name))
+        .map(|(_, price)| *price)
+        .unwrap_or((0.0, 0.0))
+}
@@ -0,0 +1,96 @@
+// mcp servers registered with `ai.register_mcp`. their tools are
+// offered to the model as actions, next to the stdlib modules, and
+// executed as remote tool calls
+
+use serde_json::Value as JsonValue;
+
+use crate::std::{mcp::types::McpClientRef, NativeMember, NativeModuleDef};
+
+#[derive(Debug, Clone)]
+pub struct AIMcpTool {
+    pub name: String,
+    pub description: String,
+    // (name, json schema type) in the order the model must send them
+    pub params: Vec<(String, Option<String>)>,
+}
+
+impl AIMcpTool {
+    pub fn new(name: String, description: String, input_schema: &JsonValue) -> AIMcpTool {
+        let properties = input_schema.get("properties").and_then(|p| p.as_object());
+        let required: Vec<String> = input_schema
+            .get("required")
+            .and_then(|r| r.as_array())
+            .map(|r| {
+                r.iter()
+                    .filter_map(|f| f.as_str())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        // required params go first, so trailing optional ones can be left out
+        let mut names = required;
+        if let Some(properties) = properties {
+            for name in properties.keys() {
+                if !names.contains(name) {
+                    names.push(name.clone());
+                }
+            }
+        }
+
+        let params = names
+            .into_iter()
+            .map(|name| {
+                let data_type = properties
+                    .and_then(|p| p.get(&name))
+                    .and_then(|p| p.get("type"))
+                    .and_then(|t| t.as_str())
+                    .map(String::from);
+                (name, data_type)
+            })
+            .collect();
+
+        AIMcpTool {
+            name,
+            description,
+            params,
+        }
+    }
+
+    fn to_member(&self) -> NativeMember {
+        NativeMember {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            params: Some(
+                self.params
+                    .iter()
+                    .map(|(name, data_type)| match data_type {
+                        Some(t) => format!("{}({})", name, t),
+                        None => name.clone(),
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AIMcpModule {
+    pub module: String,
+    pub url: String,
+    pub client: McpClientRef,
+    pub tools: Vec<AIMcpTool>,
+}
+
+impl AIMcpModule {
+    pub fn def(&self) -> NativeModuleDef {
+        NativeModuleDef {
+            module: self.module.clone(),
+            members: self.tools.iter().map(|t| t.to_member()).collect(),
+        }
+    }
+
+    pub fn tool(&self, name: &str) -> Option<&AIMcpTool> {
+        self.tools.iter().find(|t| t.name == name)
+    }
+}
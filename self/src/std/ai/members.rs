@@ -6,29 +6,43 @@ PROVIDER OR ENABLE USER IMPLEMENTATION OF
 PROVIDER.
 */
 
-use std::vec;
+use std::{env, vec};
 
 use futures::future::BoxFuture;
-use serde_json::Value as SValue;
+use rmcp::model::JsonObject;
+use serde_json::{json, Value as SValue};
+use tokio::sync::oneshot;
 
 use crate::{
     core::{
         error::{
-            self, action_errors::ActionError, ai_errors::AIError, type_errors::TypeError, VMError,
-            VMErrorType,
+            self, action_errors::ActionError, ai_errors::AIError, mcp_errors::McpError,
+            type_errors::TypeError, VMError, VMErrorType,
         },
         logs::write_log,
     },
+    events::{ArgsBuilder, Event},
     memory::{Handle, MemObject},
     std::{
         ai::{
-            prompts::{act_chain_prompt, do_prompt, infer_prompt, resolve_prompt},
-            providers::{fetch_ai, ChatResponse},
+            context::AIBudget,
+            mcp::{AIMcpModule, AIMcpTool},
+            prompts::{
+                act_chain_prompt, do_prompt, infer_prompt, repair_prompt, resolve_prompt,
+                typed_prompt,
+            },
+            providers::{
+                complete, fetch_ai_stream, model_name,
+                sse::{SseParser, SSE_DONE},
+                ChatStreamChunk,
+            },
+            schema::{repair_json, TypeHint},
             types::{AIAction, Action, Chain, ChainLinkJson, Link, UnfoldStore},
         },
         gen_native_modules_defs, generate_native_module, get_native_module_type,
         heap_utils::put_string,
-        utils::cast_json_value,
+        mcp::members::run_tool,
+        utils::{cast_json_value, json_to_value, value_to_json},
         vector, NativeMember,
     },
     types::{
@@ -79,6 +93,101 @@ fn ai_response_parser(response: &String, vm: &mut Vm) -> Option<Value> {
     }
 }
 
+// asks the ai until the answer validates against the type hint. on
+// invalid answers the validation error is sent back so the model
+// can repair its reply
+async fn typed_answer(
+    vm: &mut Vm,
+    prompt: String,
+    hint: &TypeHint,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let max_repairs = env::var("SELF_AI_MAX_REPAIRS")
+        .ok()
+        .and_then(|v| v.parse::<u32>().ok())
+        .unwrap_or(2);
+
+    let schema = serde_json::to_string_pretty(&hint.to_schema()).unwrap_or_default();
+    let typed = typed_prompt(&prompt, &schema);
+
+    // a cached answer is validated again, the type hint could have
+    // changed since it was stored
+    let cache_key = vm.ai_context.cache.key(&typed);
+    if let Some(answer) = cache_lookup(vm, &cache_key, debug) {
+        if let Ok(value) = validate_typed_answer(&answer, hint) {
+            return Ok(hint.to_value(&value, vm));
+        }
+    }
+
+    let mut request = typed.clone();
+    let mut attempt = 0;
+
+    loop {
+        let answer = complete(vm, request).await?;
+
+        if debug {
+            vm.trace(format!("AI -> {}", answer));
+        }
+
+        match validate_typed_answer(&answer, hint) {
+            Ok(value) => {
+                vm.ai_context.cache.put(&cache_key, &answer);
+                return Ok(hint.to_value(&value, vm));
+            }
+            Err(err) => {
+                if attempt >= max_repairs {
+                    return Err(error::throw(
+                        VMErrorType::AI(AIError::AIInvalidResponse(err)),
+                        vm,
+                    ));
+                }
+                if debug {
+                    vm.trace(format!("AI.repair({}) <- {}", attempt + 1, err));
+                }
+                attempt += 1;
+                request = repair_prompt(&typed, &answer, &err);
+            }
+        }
+    }
+}
+
+fn validate_typed_answer(answer: &String, hint: &TypeHint) -> Result<SValue, String> {
+    match repair_json(answer) {
+        Some(json) => match json.get("value") {
+            Some(value) => hint.validate(value).map(|_| value.clone()),
+            None => Err("missing the value field".to_string()),
+        },
+        None => Err("the response is not valid JSON".to_string()),
+    }
+}
+
+fn cache_lookup(vm: &Vm, key: &String, debug: bool) -> Option<String> {
+    if !vm.ai_context.cache.enabled {
+        return None;
+    }
+    let answer = vm.ai_context.cache.get(key);
+    if debug {
+        match answer {
+            Some(_) => vm.trace(format!("AI.CACHE (HIT) -> {}", key)),
+            None => vm.trace(format!("AI.CACHE (MISS) -> {}", key)),
+        }
+    }
+    answer
+}
+
+// complete for deterministic prompts (infer, resolve), answers are
+// read from and stored in the response cache when enabled
+async fn cached_complete(vm: &mut Vm, prompt: String, debug: bool) -> Result<String, VMError> {
+    let key = vm.ai_context.cache.key(&prompt);
+    if let Some(answer) = cache_lookup(vm, &key, debug) {
+        return Ok(answer);
+    }
+
+    let answer = complete(vm, prompt).await?;
+    vm.ai_context.cache.put(&key, &answer);
+    Ok(answer)
+}
+
 // infer
 pub fn infer_def() -> NativeMember {
     NativeMember {
@@ -87,6 +196,7 @@ pub fn infer_def() -> NativeMember {
         params: Some(vec![
             "prompt(string)".to_string(),
             "context(string)".to_string(),
+            "type(optional struct, [struct] or type name)".to_string(),
         ]),
     }
 }
@@ -104,35 +214,24 @@ pub fn infer(
         let context = context_ref.as_string_obj(vm)?;
 
         if debug {
-            println!("AI <- {}({})", request, context.to_string());
+            vm.trace(format!("AI <- {}({})", request, context.to_string()));
         }
 
         // we should try to avoid prompt injection
         // maybe using multiple prompts?
         let prompt = infer_prompt(&request, &context);
-        let res = fetch_ai(prompt).await;
-        let res = match res {
-            Ok(r) => r,
-            Err(vm_err) => {
-                return Err(error::throw(vm_err, vm));
-            }
-        };
-
-        if !res.status().is_success() {
-            return Err(error::throw(
-                VMErrorType::AI(AIError::AIFetchError(res.status().to_string())),
-                vm,
-            ));
+        if let Some(hint) = params.get(2) {
+            let hint = TypeHint::from_value(hint, vm)?;
+            return typed_answer(vm, prompt, &hint, debug).await;
         }
 
-        let response: ChatResponse = res.json().await.expect("AI: Failed to parse response");
-        let answer = &response.choices[0].message.content;
+        let answer = cached_complete(vm, prompt, debug).await?;
 
         if debug {
-            println!("AI -> {}", answer);
+            vm.trace(format!("AI -> {}", answer));
         }
 
-        let parsed_answer = ai_response_parser(answer, vm);
+        let parsed_answer = ai_response_parser(&answer, vm);
         if let Some(v) = parsed_answer {
             return Ok(v);
         } else {
@@ -155,7 +254,10 @@ pub fn resolve_def() -> NativeMember {
         name: "resolve".to_string(),
         description: "resolve a query into a single output value using AI. eg: url of nike"
             .to_string(),
-        params: Some(vec!["query(string)".to_string()]),
+        params: Some(vec![
+            "query(string)".to_string(),
+            "type(optional struct, [struct] or type name)".to_string(),
+        ]),
     }
 }
 
@@ -170,35 +272,24 @@ pub fn resolve(
         let query = query_ref.as_string_obj(vm)?;
 
         if debug {
-            println!("AI.resolve <- {}", query);
+            vm.trace(format!("AI.resolve <- {}", query));
         }
 
         // we should try to avoid prompt injection
         // maybe using multiple prompts?
         let prompt = resolve_prompt(&query);
-        let res = fetch_ai(prompt).await;
-        let res = match res {
-            Ok(r) => r,
-            Err(vm_err) => {
-                return Err(error::throw(vm_err, vm));
-            }
-        };
-
-        if !res.status().is_success() {
-            return Err(error::throw(
-                VMErrorType::AI(AIError::AIFetchError(res.status().to_string())),
-                vm,
-            ));
+        if let Some(hint) = params.get(1) {
+            let hint = TypeHint::from_value(hint, vm)?;
+            return typed_answer(vm, prompt, &hint, debug).await;
         }
 
-        let response: ChatResponse = res.json().await.expect("AI: Failed to parse response");
-        let answer = &response.choices[0].message.content;
+        let answer = cached_complete(vm, prompt, debug).await?;
 
         if debug {
-            println!("AI -> {}", answer);
+            vm.trace(format!("AI -> {}", answer));
         }
 
-        let parsed_answer = ai_response_parser(answer, vm);
+        let parsed_answer = ai_response_parser(&answer, vm);
         if let Some(v) = parsed_answer {
             return Ok(v);
         } else {
@@ -207,53 +298,166 @@ pub fn resolve(
     })
 }
 
-// do
-pub fn do_fn(
+// stream: deliver the answer chunks to a callback as they arrive
+pub fn stream_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "stream".to_string(),
+        vec!["prompt".to_string(), "on_chunk".to_string()], // TODO: load params to native functions
+        Engine::NativeAsync(stream),
+    ))
+}
+
+pub fn stream_def() -> NativeMember {
+    NativeMember {
+        name: "stream".to_string(),
+        description: "send a prompt to the AI and call on_chunk with each piece of the answer as it is generated. returns the whole answer".to_string(),
+        params: Some(vec![
+            "prompt(string)".to_string(),
+            "on_chunk(function)".to_string(),
+        ]),
+    }
+}
+
+pub fn stream(
     vm: &mut Vm,
     _self: Option<Handle>,
     params: Vec<Value>,
     debug: bool,
 ) -> BoxFuture<Result<Value, VMError>> {
     Box::pin(async move {
-        let request_ref = params[0].clone();
-        let request = request_ref.as_string_obj(vm)?;
+        if params.len() != 2 {
+            return Err(error::throw(
+                VMErrorType::TypeError(TypeError::InvalidArgsCount {
+                    expected: 2,
+                    received: params.len() as u32,
+                }),
+                vm,
+            ));
+        }
+        let prompt = params[0].as_string_obj(vm)?;
+        let on_chunk = params[1].as_function_obj(vm)?;
 
         if debug {
-            println!("AI.DO <- {}", request);
+            vm.trace(format!("AI.stream <- {}", prompt));
         }
 
-        let stdlib_defs: Vec<String> = gen_native_modules_defs()
-            .iter()
-            .map(|nm| nm.to_string())
-            .collect();
+        if let Err(err) = vm.ai_context.check_budget() {
+            return Err(error::throw(VMErrorType::AI(err), vm));
+        }
 
-        // we should try to avoid prompt injection
-        // maybe using multiple prompts?
-        let prompt = do_prompt(stdlib_defs, &request);
-        let res = fetch_ai(prompt).await;
-        let res = match res {
+        let mut res = match fetch_ai_stream(prompt).await {
             Ok(r) => r,
             Err(vm_err) => {
                 return Err(error::throw(vm_err, vm));
             }
         };
 
-        if !res.status().is_success() {
-            println!("AI.DO (FAILED) -> {}", res.status());
-            return Err(error::throw(
-                VMErrorType::AI(AIError::AIFetchError(res.status().to_string())),
-                vm,
-            ));
+        // chunks are queued as events and the stream is read while
+        // the events are handled, so the callback runs the same way
+        // schedule callbacks do, also when streaming from a callback
+        let notifier = vm.get_vm_notifier();
+        let reading = async move {
+            let mut parser = SseParser::new();
+            let mut answer = String::new();
+            let mut model = model_name();
+            let mut usage = None;
+            let mut delivered = None;
+            let mut done = false;
+
+            while !done {
+                let payloads = match res.chunk().await {
+                    Ok(Some(bytes)) => parser.feed(&bytes),
+                    Ok(None) => {
+                        done = true;
+                        parser.finish().into_iter().collect()
+                    }
+                    Err(e) => return Err(AIError::AIFetchError(e.to_string())),
+                };
+
+                for payload in payloads {
+                    if payload == SSE_DONE {
+                        done = true;
+                        break;
+                    }
+                    // skip anything that is not a completion chunk
+                    let Ok(chunk) = serde_json::from_str::<ChatStreamChunk>(&payload) else {
+                        continue;
+                    };
+                    if let Some(m) = chunk.model {
+                        model = m;
+                    }
+                    if chunk.usage.is_some() {
+                        usage = chunk.usage;
+                    }
+                    for choice in chunk.choices {
+                        let Some(content) = choice.delta.content else {
+                            continue;
+                        };
+                        if content.is_empty() {
+                            continue;
+                        }
+                        answer.push_str(&content);
+                        let (ack, acked) = oneshot::channel();
+                        let build_args: ArgsBuilder = Box::new(move |vm| {
+                            let _ = ack.send(());
+                            vec![Value::Handle(put_string(vm, content))]
+                        });
+                        let _ = notifier.send(Event::CallWith(on_chunk.clone(), build_args));
+                        delivered = Some(acked);
+                    }
+                }
+            }
+
+            // events run in order, once the last chunk is taken
+            // every callback has run
+            if let Some(acked) = delivered {
+                let _ = acked.await;
+            }
+            Ok((answer, model, usage))
+        };
+
+        let (answer, model, usage) = match vm.with_events(reading).await? {
+            Ok(streamed) => streamed,
+            Err(err) => return Err(error::throw(VMErrorType::AI(err), vm)),
+        };
+
+        vm.ai_context.record(model, usage);
+
+        if debug {
+            vm.trace(format!("AI.stream -> {}", answer));
         }
 
-        let response: ChatResponse = res.json().await.expect("AI.DO: Failed to parse response");
-        let answer = &response.choices[0].message.content;
+        Ok(Value::Handle(put_string(vm, answer)))
+    })
+}
+
+// do
+pub fn do_fn(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> BoxFuture<Result<Value, VMError>> {
+    Box::pin(async move {
+        let request_ref = params[0].clone();
+        let request = request_ref.as_string_obj(vm)?;
 
         if debug {
-            println!("AI -> {}", answer);
+            vm.trace(format!("AI.DO <- {}", request));
         }
 
-        let cleaned = get_response_json(answer);
+        let stdlib_defs = get_action_defs(vm);
+
+        // we should try to avoid prompt injection
+        // maybe using multiple prompts?
+        let prompt = do_prompt(stdlib_defs, &request);
+        let answer = complete(vm, prompt).await?;
+
+        if debug {
+            vm.trace(format!("AI -> {}", answer));
+        }
+
+        let cleaned = get_response_json(&answer);
         let instructions: Vec<AIAction> = if let Ok(val) = serde_json::from_str(cleaned.as_str()) {
             val
         } else {
@@ -293,7 +497,7 @@ pub fn do_fn(
             .collect();
 
         if debug {
-            println!("AI.DO <- {:#?}", actions)
+            vm.trace(format!("AI.DO <- {:#?}", actions))
         }
 
         let mut actions_ref = vec![];
@@ -350,11 +554,11 @@ pub fn chain(
         let end_condition = end_condition_handle.as_string_obj(vm)?;
 
         if debug {
-            println!("AI.CHAIN <- {}", purpose);
+            vm.trace(format!("AI.CHAIN <- {}", purpose));
         }
 
         // get base libs
-        let stdlib_defs = get_stdlib_defs();
+        let stdlib_defs = get_action_defs(vm);
         let master_link = generate_link(
             &purpose,
             &end_condition,
@@ -392,35 +596,15 @@ pub async fn generate_link(
     if debug {
         write_log("PROMPT", &prompt);
     }
-    let res = fetch_ai(prompt).await;
-    let res = match res {
-        Ok(r) => r,
-        Err(vm_err) => {
-            return Err(error::throw(vm_err, vm));
-        }
-    };
-
-    if !res.status().is_success() {
-        println!("AI.CHAIN (FAILED) -> {}", res.status());
-        return Err(error::throw(
-            VMErrorType::AI(AIError::AIFetchError(res.status().to_string())),
-            vm,
-        ));
-    }
-
-    let response: ChatResponse = res
-        .json()
-        .await
-        .expect("AI.CHAIN: Failed to parse response");
-    let answer = &response.choices[0].message.content;
+    let answer = complete(vm, prompt).await?;
 
     if debug {
-        println!("AI.CHAIN -> {}", answer);
+        vm.trace(format!("AI.CHAIN -> {}", answer));
     }
 
-    let cleaned = get_response_json(answer);
+    let cleaned = get_response_json(&answer);
     if debug {
-        println!("AI.CHAIN [RESPONSE] -> {}", cleaned);
+        vm.trace(format!("AI.CHAIN [RESPONSE] -> {}", cleaned));
     }
     let chain_link: ChainLinkJson = if let Ok(val) = serde_json::from_str(cleaned.as_str()) {
         val
@@ -433,7 +617,7 @@ pub async fn generate_link(
     };
 
     if debug {
-        println!("AI.CHAIN <- {:#?}", chain_link)
+        vm.trace(format!("AI.CHAIN <- {:#?}", chain_link))
     }
 
     // todo:
@@ -522,11 +706,11 @@ pub fn unfold(
             .collect::<Result<Vec<Link>, VMError>>()?;
 
         // get base libs
-        let stdlib_defs = get_stdlib_defs();
+        let stdlib_defs = get_action_defs(vm);
 
         // start chain traversing, here occurs the magic
         if debug {
-            println!("CHAIN.TRAVERSE <- {}", _self.to_string(vm));
+            vm.trace(format!("CHAIN.TRAVERSE <- {}", _self.to_string(vm)));
         }
 
         // let traversing context
@@ -663,10 +847,10 @@ pub fn unfold(
             };
 
             if debug {
-                println!(
+                vm.trace(format!(
                     "CHAIN MODE: {:#?}",
                     if session_mode { "SESSION" } else { "NORMAL" }
-                );
+                ));
             }
 
             // process the current link
@@ -769,7 +953,7 @@ pub fn exec(
         };
 
         if debug {
-            println!("ACTION <- {}.{}", _self.module, _self.member);
+            vm.trace(format!("ACTION <- {}.{}", _self.module, _self.member));
         }
         let native_module_type = get_native_module_type(&_self.module);
 
@@ -793,33 +977,7 @@ pub fn exec(
 
             match &member.1 {
                 MemObject::Function(f) => {
-                    let mut consumer_param_counter = 0;
-                    let resolved_action_params: Vec<Value> = _self
-                    .args
-                    .iter()
-                    .enumerate()
-                    .map(|(index, arg)| match arg.as_string_obj(vm).ok().as_deref() {
-                        Some(v) => {
-                            if v.contains("{self_runtime}") {
-                                let param = params
-                                    .get(consumer_param_counter)
-                                    .cloned()
-                                    .unwrap_or_else(|| {
-                                        eprintln!(
-                                            "action runtime defined param cannot be populated (index: {})",
-                                            index
-                                        );
-                                        Value::RawValue(RawValue::Nothing)
-                                    });
-                                consumer_param_counter += 1;
-                                param
-                            } else {
-                                arg.clone()
-                            }
-                        }
-                        _ => arg.clone(),
-                    })
-                    .collect();
+                    let resolved_action_params = resolve_action_params(vm, &_self.args, &params);
 
                     let execution = vm
                         .run_function(&f.clone(), Some(_self_ref), resolved_action_params, debug)
@@ -840,6 +998,39 @@ pub fn exec(
                     panic!("error, member is not callable");
                 }
             }
+        } else if let Some(mcp_module) = vm.ai_context.mcp_module(&_self.module).cloned() {
+            let Some(tool) = mcp_module.tool(&_self.member) else {
+                return Err(error::throw(
+                    VMErrorType::Action(ActionError::InvalidMember {
+                        module: _self.module.clone(),
+                        member: _self.member.clone(),
+                    }),
+                    vm,
+                ));
+            };
+
+            // positional action params to the named tool arguments,
+            // nothing is left out so optional arguments can be skipped
+            let resolved_action_params = resolve_action_params(vm, &_self.args, &params);
+            let mut arguments = JsonObject::new();
+            for ((name, _), arg) in tool.params.iter().zip(resolved_action_params.iter()) {
+                match value_to_json(vm, arg) {
+                    SValue::Null => {}
+                    value => {
+                        arguments.insert(name.clone(), value);
+                    }
+                }
+            }
+
+            run_tool(
+                vm,
+                mcp_module.url.clone(),
+                mcp_module.client.clone(),
+                tool.name.clone(),
+                Some(arguments),
+                debug,
+            )
+            .await
         } else {
             if let Some(struct_handle) = vm.call_stack.resolve(&_self.module) {
                 if let Value::Handle(h) = struct_handle {
@@ -865,6 +1056,272 @@ pub fn exec(
     })
 }
 
+// the action args with each {self_runtime} placeholder replaced, in
+// order, by the params the action is executed with
+fn resolve_action_params(vm: &Vm, args: &[Value], params: &[Value]) -> Vec<Value> {
+    let mut consumer_param_counter = 0;
+    args.iter()
+        .enumerate()
+        .map(|(index, arg)| match arg.as_string_obj(vm).ok().as_deref() {
+            Some(v) if v.contains("{self_runtime}") => {
+                let param = params
+                    .get(consumer_param_counter)
+                    .cloned()
+                    .unwrap_or_else(|| {
+                        eprintln!(
+                            "action runtime defined param cannot be populated (index: {})",
+                            index
+                        );
+                        Value::RawValue(RawValue::Nothing)
+                    });
+                consumer_param_counter += 1;
+                param
+            }
+            _ => arg.clone(),
+        })
+        .collect()
+}
+
+// register_mcp: offer the tools of an mcp client as actions
+pub fn register_mcp_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "register_mcp".to_string(),
+        // (client, name). the name is optional
+        vec!["client".to_string()],
+        Engine::NativeAsync(register_mcp),
+    ))
+}
+
+pub fn register_mcp_def() -> NativeMember {
+    NativeMember {
+        name: "register_mcp".to_string(),
+        description: "register the tools of a connected mcp client as actions for ai.do and ai.chain. the module name defaults to the server name and can't be the name of a stdlib or host module. returns the module name".to_string(),
+        params: Some(vec![
+            "client(McpClient)".to_string(),
+            "name(string or nothing)".to_string(),
+        ]),
+    }
+}
+
+pub fn register_mcp(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> BoxFuture<'_, Result<Value, VMError>> {
+    Box::pin(async move {
+        if params.len() < 1 {
+            return Err(error::throw(
+                VMErrorType::TypeError(TypeError::InvalidArgsCount {
+                    expected: 1,
+                    received: params.len() as u32,
+                }),
+                vm,
+            ));
+        }
+
+        let (url, client) = match params[0].as_mem_obj(vm)? {
+            MemObject::NativeStruct(NativeStruct::McpClient(mc)) => {
+                (mc.url.clone(), mc.client.clone())
+            }
+            _ => {
+                return Err(error::throw(
+                    VMErrorType::TypeError(TypeError::InvalidTypeUnwrap {
+                        expected: "McpClient".to_string(),
+                        received: params[0].get_resolved_type(vm),
+                    }),
+                    vm,
+                ))
+            }
+        };
+        let name = match params.get(1) {
+            Some(Value::RawValue(RawValue::Nothing)) | None => None,
+            Some(name) => Some(name.as_string_obj(vm)?),
+        };
+
+        let (server_name, tools) = {
+            let guard = client.lock().await;
+            let Some(service) = guard.as_ref() else {
+                return Err(error::throw(
+                    VMErrorType::Mcp(McpError::NotConnected(url)),
+                    vm,
+                ));
+            };
+            let server_name = service.peer_info().map(|i| i.server_info.name.clone());
+            (server_name, service.list_all_tools().await)
+        };
+        let tools = tools.map_err(|e| {
+            error::throw(VMErrorType::Mcp(McpError::RequestError(e.to_string())), vm)
+        })?;
+
+        // module names end up in the prompt, keep them identifier-like
+        let module = name
+            .or(server_name)
+            .unwrap_or("mcp".to_string())
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '_' })
+            .collect::<String>();
+        // stdlib and host modules are resolved first by exec, so
+        // their members would shadow the tools
+        if vm.is_module(&module) {
+            return Err(error::throw(
+                VMErrorType::Mcp(McpError::ModuleTaken(module)),
+                vm,
+            ));
+        }
+
+        let tools = tools
+            .iter()
+            .map(|t| {
+                AIMcpTool::new(
+                    t.name.to_string(),
+                    t.description.as_deref().unwrap_or_default().to_string(),
+                    &SValue::Object(t.input_schema.as_ref().clone()),
+                )
+            })
+            .collect();
+        let mcp_module = AIMcpModule {
+            module: module.clone(),
+            url,
+            client,
+            tools,
+        };
+
+        if debug {
+            vm.trace(format!(
+                "AI.register_mcp -> {}",
+                mcp_module.def().to_string()
+            ));
+        }
+
+        vm.ai_context.register_mcp(mcp_module);
+        Ok(Value::Handle(put_string(vm, module)))
+    })
+}
+
+// usage: token usage of the ai requests made by the vm
+pub fn usage_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "usage".to_string(),
+        vec![],
+        Engine::Native(usage),
+    ))
+}
+
+pub fn usage_def() -> NativeMember {
+    NativeMember {
+        name: "usage".to_string(),
+        description: "get the requests, tokens and estimated cost (usd) of the AI calls made so far, with the detail of each call".to_string(),
+        params: None,
+    }
+}
+
+pub fn usage(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    _params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let calls: Vec<SValue> = vm
+        .ai_context
+        .calls
+        .iter()
+        .map(|c| {
+            json!({
+                "model": c.model,
+                "prompt_tokens": c.prompt_tokens,
+                "completion_tokens": c.completion_tokens,
+                "total_tokens": c.total_tokens(),
+                "cost": c.cost(),
+            })
+        })
+        .collect();
+    let usage = json!({
+        "requests": vm.ai_context.requests(),
+        "prompt_tokens": vm.ai_context.prompt_tokens(),
+        "completion_tokens": vm.ai_context.completion_tokens(),
+        "total_tokens": vm.ai_context.total_tokens(),
+        "cost": vm.ai_context.cost(),
+        "calls": calls,
+    });
+
+    if debug {
+        vm.trace(format!("AI.usage -> {}", usage));
+    }
+
+    Ok(json_to_value(vm, &usage))
+}
+
+// budget: limit the tokens and requests of the run
+pub fn budget_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "budget".to_string(),
+        vec!["max_tokens".to_string(), "max_requests".to_string()],
+        Engine::Native(budget),
+    ))
+}
+
+pub fn budget_def() -> NativeMember {
+    NativeMember {
+        name: "budget".to_string(),
+        description: "limit the total tokens and requests of AI calls. nothing removes a limit. calls beyond the budget fail".to_string(),
+        params: Some(vec![
+            "max_tokens(number or nothing)".to_string(),
+            "max_requests(number or nothing)".to_string(),
+        ]),
+    }
+}
+
+pub fn budget(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    if params.len() != 2 {
+        return Err(error::throw(
+            VMErrorType::TypeError(TypeError::InvalidArgsCount {
+                expected: 2,
+                received: params.len() as u32,
+            }),
+            vm,
+        ));
+    }
+
+    let max_tokens = budget_limit(&params[0], vm)?;
+    let max_requests = budget_limit(&params[1], vm)?;
+    if debug {
+        vm.trace(format!(
+            "AI.budget <- {:?} tokens, {:?} requests",
+            max_tokens, max_requests
+        ));
+    }
+
+    vm.ai_context.budget = AIBudget {
+        max_tokens,
+        max_requests,
+    };
+    Ok(Value::RawValue(RawValue::Nothing))
+}
+
+fn budget_limit(value: &Value, vm: &Vm) -> Result<Option<u64>, VMError> {
+    match value {
+        Value::RawValue(RawValue::Nothing) => Ok(None),
+        Value::RawValue(RawValue::F64(v)) if v.value >= 0.0 => Ok(Some(v.value as u64)),
+        Value::RawValue(r) if r.as_usize().is_some() => Ok(r.as_usize().map(|v| v as u64)),
+        Value::RawValue(r) if r.as_isize().is_some_and(|v| v >= 0) => {
+            Ok(r.as_isize().map(|v| v as u64))
+        }
+        _ => Err(error::throw(
+            VMErrorType::TypeError(TypeError::InvalidTypeUnwrap {
+                expected: "number or nothing".to_string(),
+                received: value.get_resolved_type(vm),
+            }),
+            vm,
+        )),
+    }
+}
+
 // utils functions
 fn enter_session_mode(vm: &mut Vm, conclusion: &Value) -> Option<(String, String)> {
     let handle = match conclusion {
@@ -902,9 +1359,17 @@ fn enter_session_mode(vm: &mut Vm, conclusion: &Value) -> Option<(String, String
     return Some((instance_name, defs));
 }
 
-fn get_stdlib_defs() -> Vec<String> {
-    gen_native_modules_defs()
+// stdlib modules plus the mcp servers registered with `ai.register_mcp`
+fn get_action_defs(vm: &Vm) -> Vec<String> {
+    let mut defs: Vec<String> = gen_native_modules_defs()
         .iter()
         .map(|nm| nm.to_string())
-        .collect()
+        .collect();
+    defs.extend(
+        vm.ai_context
+            .mcp_modules
+            .iter()
+            .map(|m| m.def().to_string()),
+    );
+    defs
 }
@@ -1,13 +1,21 @@
+mod cache;
+pub mod context;
+mod mcp;
 mod members;
 mod prompts;
 mod providers;
+mod schema;
 pub mod types;
 
 use crate::{
     memory::MemObject,
     opcodes::DataType,
     std::{
-        ai::members::{chain_obj, do_fn, infer, infer_def, resolve_def, resolve_obj},
+        ai::members::{
+            budget_def, budget_obj, chain_obj, do_fn, infer, infer_def, register_mcp_def,
+            register_mcp_obj, resolve_def, resolve_obj, stream_def, stream_obj, usage_def,
+            usage_obj,
+        },
         NativeModuleDef,
     },
     types::object::{
@@ -37,13 +45,24 @@ pub fn generate_struct() -> (String, Vec<(String, MemObject)>) {
     fields.push(("resolve".to_string(), resolve_obj()));
     fields.push(("do".to_string(), do_ref));
     fields.push(("chain".to_string(), chain_obj()));
+    fields.push(("stream".to_string(), stream_obj()));
+    fields.push(("usage".to_string(), usage_obj()));
+    fields.push(("budget".to_string(), budget_obj()));
+    fields.push(("register_mcp".to_string(), register_mcp_obj()));
     fields.push(("Engine".to_string(), engine_ref));
 
     ("ai".to_string(), fields)
 }
 
 pub fn generate_mod_def() -> NativeModuleDef {
-    let members = vec![infer_def(), resolve_def()];
+    let members = vec![
+        infer_def(),
+        resolve_def(),
+        stream_def(),
+        usage_def(),
+        budget_def(),
+        register_mcp_def(),
+    ];
 
     NativeModuleDef {
         module: "ai".to_string(),
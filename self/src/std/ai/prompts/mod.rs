@@ -142,3 +142,31 @@ ANSWER ONLY IN JSON, ONLY ISO 8259 VALID FORMATTED JSON.
         context.iter().map(|c| format!("\t<VARIABLE>\n{}\n\t</VARIABLE>", c)).collect::<Vec<String>>().join("\n\n"),
     );
 }
+
+pub fn typed_prompt(prompt: &String, schema: &String) -> String {
+    return format!(
+        "{}
+The response value must validate against the following JSON schema:
+
+{}
+
+If the value is an object or an array, respond with it as is inside the value field.
+",
+        prompt, schema
+    );
+}
+
+pub fn repair_prompt(prompt: &String, answer: &String, error: &String) -> String {
+    return format!(
+        "{}
+Your previous response was:
+
+{}
+
+It is not valid: {}
+
+Respond again fixing the error. Only respond with the JSON.
+",
+        prompt, answer, error
+    );
+}
@@ -1,28 +1,41 @@
 use std::{env, vec};
 
-use reqwest::{Client, Response};
+use reqwest::{Client, RequestBuilder};
 
-use crate::std::ai::providers::{ChatRequest, Message};
+use crate::{
+    core::error::{ai_errors::AIError, VMErrorType},
+    std::ai::providers::{ChatRequest, Message},
+};
 
-pub async fn fetch(prompt: String) -> Response {
-    let api_key = env::var("MISTRAL_API_KEY").expect("MISTRAL_API_KEY not set");
+// or "mistral-small", "mistral-large", etc.
+pub const MODEL: &str = "mistral-medium";
+
+pub fn request(
+    client: &Client,
+    prompt: String,
+    stream: bool,
+) -> Result<RequestBuilder, VMErrorType> {
+    let Ok(api_key) = env::var("MISTRAL_API_KEY") else {
+        return Err(VMErrorType::AI(AIError::AIFetchError(
+            "MISTRAL_API_KEY not set".to_string(),
+        )));
+    };
 
-    let client = Client::new();
     let request_body = ChatRequest {
-        model: "mistral-medium".to_string(), // O "mistral-small", "mistral-large", etc.
+        model: MODEL.to_string(),
         messages: vec![Message {
             role: "system".to_string(),
             content: prompt,
         }],
+        stream: if stream { Some(true) } else { None },
+        stream_options: None,
     };
+    let base_url = env::var("MISTRAL_BASE_URL").unwrap_or("https://api.mistral.ai/v1".to_string());
 
-    let res = client
-        .post("https://api.mistral.ai/v1/chat/completions")
+    let request = client
+        .post(format!("{}/chat/completions", base_url))
         .bearer_auth(api_key)
-        .json(&request_body)
-        .send()
-        .await
-        .expect("AI: Failed to send request");
+        .json(&request_body);
 
-    res
+    Ok(request)
 }
@@ -1,12 +1,22 @@
-use std::env;
+use std::{
+    env,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
-use reqwest::Response;
+use reqwest::{header::RETRY_AFTER, Client, Response, StatusCode};
 use serde::{Deserialize, Serialize};
+use tokio::time::sleep;
 
-use crate::core::error::{ai_errors::AIError, VMErrorType};
+use crate::{
+    core::error::{self, ai_errors::AIError, VMError, VMErrorType},
+    vm::Vm,
+};
 
 mod mistral;
 mod openai;
+pub mod sse;
+
+const MAX_RETRY_DELAY_MS: u64 = 60_000;
 
 #[derive(Serialize)]
 pub struct Message {
@@ -18,11 +28,32 @@ pub struct Message {
 pub struct ChatRequest {
     pub model: String,
     pub messages: Vec<Message>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream_options: Option<StreamOptions>,
+}
+
+#[derive(Serialize)]
+pub struct StreamOptions {
+    pub include_usage: bool,
 }
 
 #[derive(Deserialize)]
 pub struct ChatResponse {
     pub choices: Vec<Choice>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub usage: Option<Usage>,
+}
+
+#[derive(Deserialize, Default, Clone)]
+pub struct Usage {
+    #[serde(default)]
+    pub prompt_tokens: u64,
+    #[serde(default)]
+    pub completion_tokens: u64,
 }
 
 #[derive(Deserialize)]
@@ -35,7 +66,37 @@ pub struct MessageContent {
     pub content: String,
 }
 
+// stream chunks (openai and mistral share the format)
+#[derive(Deserialize)]
+pub struct ChatStreamChunk {
+    pub choices: Vec<StreamChoice>,
+    #[serde(default)]
+    pub model: Option<String>,
+    // only present on the last chunk
+    #[serde(default)]
+    pub usage: Option<Usage>,
+}
+
+#[derive(Deserialize)]
+pub struct StreamChoice {
+    pub delta: Delta,
+}
+
+#[derive(Deserialize)]
+pub struct Delta {
+    pub content: Option<String>,
+}
+
 pub async fn fetch_ai(prompt: String) -> Result<Response, VMErrorType> {
+    fetch_provider(prompt, false).await
+}
+
+// same as fetch_ai, but the provider answers with server-sent events
+pub async fn fetch_ai_stream(prompt: String) -> Result<Response, VMErrorType> {
+    fetch_provider(prompt, true).await
+}
+
+async fn fetch_provider(prompt: String, stream: bool) -> Result<Response, VMErrorType> {
     let ai_engine = env::var("SELF_AI_ENGINE");
     let ai_engine = if let Ok(engine) = ai_engine {
         engine
@@ -43,9 +104,310 @@ pub async fn fetch_ai(prompt: String) -> Result<Response, VMErrorType> {
         return Err(VMErrorType::AI(AIError::AIEngineNotSet()));
     };
 
-    match ai_engine.as_str() {
-        "openai" => Ok(openai::fetch(prompt).await),
-        "mistral" => Ok(mistral::fetch(prompt).await),
-        _ => Err(VMErrorType::AI(AIError::AIEngineNotImplemented(ai_engine))),
+    let timeout = Duration::from_millis(env_u64("SELF_AI_TIMEOUT_MS", 60_000));
+    let max_retries = env_u64("SELF_AI_MAX_RETRIES", 3);
+
+    // streams can last longer than any sensible request timeout, so
+    // for them the timeout applies between reads
+    let client = if stream {
+        Client::builder().read_timeout(timeout)
+    } else {
+        Client::builder().timeout(timeout)
+    };
+    let client = match client.build() {
+        Ok(c) => c,
+        Err(e) => return Err(VMErrorType::AI(AIError::AIFetchError(e.to_string()))),
+    };
+
+    let mut attempt = 0;
+    loop {
+        let request = match ai_engine.as_str() {
+            "openai" => openai::request(&client, prompt.clone(), stream)?,
+            "mistral" => mistral::request(&client, prompt.clone(), stream)?,
+            _ => return Err(VMErrorType::AI(AIError::AIEngineNotImplemented(ai_engine))),
+        };
+
+        let retry_in = match request.send().await {
+            Ok(res) if res.status().is_success() => return Ok(res),
+            Ok(res) => {
+                let status = res.status();
+                let retryable = status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error();
+                if !retryable || attempt >= max_retries {
+                    let body = res.text().await.unwrap_or_default();
+                    return Err(VMErrorType::AI(AIError::AIFetchError(format!(
+                        "{}: {}",
+                        status,
+                        body.trim()
+                    ))));
+                }
+                retry_after(&res).unwrap_or(backoff(attempt))
+            }
+            Err(e) => {
+                // timeouts and connection errors are worth another try,
+                // anything else (invalid url, body...) will fail again
+                let retryable = e.is_timeout() || e.is_connect();
+                if !retryable || attempt >= max_retries {
+                    // the cause of a timeout is lost in the reqwest message
+                    let message = if e.is_timeout() {
+                        format!("timed out after {}ms", timeout.as_millis())
+                    } else {
+                        e.to_string()
+                    };
+                    return Err(VMErrorType::AI(AIError::AIFetchError(message)));
+                }
+                backoff(attempt)
+            }
+        };
+
+        attempt += 1;
+        sleep(retry_in).await;
+    }
+}
+
+fn env_u64(key: &str, default: u64) -> u64 {
+    env::var(key)
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(default)
+}
+
+// exponential backoff with up to 50% of jitter, so clients rate
+// limited at the same time don't retry at the same time
+fn backoff(attempt: u64) -> Duration {
+    let base = env_u64("SELF_AI_RETRY_BASE_MS", 500);
+    let delay = base
+        .saturating_mul(1 << attempt.min(10))
+        .min(MAX_RETRY_DELAY_MS);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() as u64)
+        .unwrap_or(0);
+    let jitter = if delay > 0 {
+        nanos % (delay / 2 + 1)
+    } else {
+        0
+    };
+    Duration::from_millis(delay + jitter)
+}
+
+// only the delay-seconds form of the header is supported
+fn retry_after(res: &Response) -> Option<Duration> {
+    let seconds = res
+        .headers()
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    Some(Duration::from_millis(
+        seconds.saturating_mul(1000).min(MAX_RETRY_DELAY_MS),
+    ))
+}
+
+// sends a prompt and returns the answer content. every request goes
+// through here so usage is recorded and budgets are enforced
+pub async fn complete(vm: &mut Vm, prompt: String) -> Result<String, VMError> {
+    if let Err(err) = vm.ai_context.check_budget() {
+        return Err(error::throw(VMErrorType::AI(err), vm));
+    }
+
+    let res = match fetch_ai(prompt).await {
+        Ok(r) => r,
+        Err(vm_err) => {
+            return Err(error::throw(vm_err, vm));
+        }
+    };
+
+    let response: ChatResponse = match res.json().await {
+        Ok(r) => r,
+        Err(e) => {
+            return Err(error::throw(
+                VMErrorType::AI(AIError::AIFetchError(format!(
+                    "cannot decode response: {}",
+                    e
+                ))),
+                vm,
+            ));
+        }
+    };
+
+    let model = response.model.unwrap_or(model_name());
+    vm.ai_context.record(model, response.usage);
+
+    let Some(choice) = response.choices.into_iter().next() else {
+        return Err(error::throw(
+            VMErrorType::AI(AIError::AIFetchError("empty response".to_string())),
+            vm,
+        ));
+    };
+
+    // the request that crossed the limit is still returned, the
+    // next one is rejected
+    Ok(choice.message.content)
+}
+
+pub fn engine_name() -> String {
+    env::var("SELF_AI_ENGINE").unwrap_or_default()
+}
+
+// model requested to the configured engine
+pub fn model_name() -> String {
+    match engine_name().as_str() {
+        "openai" => openai::MODEL.to_string(),
+        "mistral" => mistral::MODEL.to_string(),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Mutex,
+        },
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use super::{backoff, fetch_ai};
+    use crate::core::error::{ai_errors::AIError, VMErrorType};
+
+    // the providers are configured through the env, shared by the
+    // tests running in parallel
+    static ENV: Mutex<()> = Mutex::new(());
+
+    // a stand-in for the provider answering each request with the
+    // next response, after the given delay. returns its url and the
+    // count of requests received
+    fn serve(responses: Vec<(Duration, &'static str)>) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let received = Arc::new(AtomicUsize::new(0));
+        let counter = received.clone();
+        thread::spawn(move || {
+            for (delay, response) in responses {
+                let Ok((mut stream, _)) = listener.accept() else {
+                    return;
+                };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut length = 0;
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    if let Some(value) = line.to_lowercase().strip_prefix("content-length:") {
+                        length = value.trim().parse().unwrap();
+                    }
+                    line.clear();
+                }
+                reader.read_exact(&mut vec![0; length]).unwrap();
+                counter.fetch_add(1, Ordering::SeqCst);
+
+                thread::sleep(delay);
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        (url, received)
+    }
+
+    fn configure(url: &str, timeout_ms: u64, max_retries: u64) {
+        std::env::set_var("SELF_AI_ENGINE", "openai");
+        std::env::set_var("OPENAI_API_KEY", "test");
+        std::env::set_var("OPENAI_BASE_URL", url);
+        std::env::set_var("SELF_AI_TIMEOUT_MS", timeout_ms.to_string());
+        std::env::set_var("SELF_AI_MAX_RETRIES", max_retries.to_string());
+        std::env::set_var("SELF_AI_RETRY_BASE_MS", "1");
+    }
+
+    fn fetch_error(url: &str, timeout_ms: u64, max_retries: u64) -> String {
+        configure(url, timeout_ms, max_retries);
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        match runtime.block_on(fetch_ai("prompt".to_string())) {
+            Err(VMErrorType::AI(AIError::AIFetchError(message))) => message,
+            Err(_) => panic!("expected a fetch error"),
+            Ok(res) => panic!("expected a fetch error, got {}", res.status()),
+        }
+    }
+
+    const OK: &str =
+        "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: 2\r\nconnection: close\r\n\r\n{}";
+    const RATE_LIMITED: &str =
+        "HTTP/1.1 429 Too Many Requests\r\nretry-after: 1\r\ncontent-length: 0\r\nconnection: close\r\n\r\n";
+    const OVERLOADED: &str =
+        "HTTP/1.1 503 Service Unavailable\r\ncontent-length: 11\r\nconnection: close\r\n\r\noverloaded\n";
+    const BAD_REQUEST: &str =
+        "HTTP/1.1 400 Bad Request\r\ncontent-length: 13\r\nconnection: close\r\n\r\nunknown model";
+
+    #[test]
+    fn honours_retry_after() {
+        let _env = ENV.lock().unwrap_or_else(|e| e.into_inner());
+        let (url, received) = serve(vec![(Duration::ZERO, RATE_LIMITED), (Duration::ZERO, OK)]);
+        configure(&url, 5_000, 3);
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let started = Instant::now();
+        let res = runtime.block_on(fetch_ai("prompt".to_string())).unwrap();
+
+        assert!(res.status().is_success());
+        assert!(started.elapsed() >= Duration::from_secs(1));
+        assert_eq!(received.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn gives_up_after_the_max_retries() {
+        let _env = ENV.lock().unwrap_or_else(|e| e.into_inner());
+        let (url, received) = serve(vec![(Duration::ZERO, OVERLOADED); 4]);
+
+        let message = fetch_error(&url, 5_000, 2);
+
+        assert_eq!(message, "503 Service Unavailable: overloaded");
+        assert_eq!(received.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn reports_the_status_and_body_without_retrying_client_errors() {
+        let _env = ENV.lock().unwrap_or_else(|e| e.into_inner());
+        let (url, received) = serve(vec![(Duration::ZERO, BAD_REQUEST), (Duration::ZERO, OK)]);
+
+        let message = fetch_error(&url, 5_000, 3);
+
+        assert_eq!(message, "400 Bad Request: unknown model");
+        assert_eq!(received.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn times_out_slow_requests() {
+        let _env = ENV.lock().unwrap_or_else(|e| e.into_inner());
+        let (url, _) = serve(vec![(Duration::from_secs(3), OK)]);
+
+        let started = Instant::now();
+        let message = fetch_error(&url, 200, 0);
+
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert_eq!(message, "timed out after 200ms");
+    }
+
+    #[test]
+    fn keeps_the_backoff_within_its_jitter() {
+        let _env = ENV.lock().unwrap_or_else(|e| e.into_inner());
+        std::env::set_var("SELF_AI_RETRY_BASE_MS", "100");
+
+        for _ in 0..50 {
+            for (attempt, delay) in [(0, 100), (1, 200), (3, 800)] {
+                let backoff = backoff(attempt).as_millis() as u64;
+                assert!(backoff >= delay && backoff <= delay + delay / 2);
+            }
+            // capped at a minute, plus the jitter
+            let backoff = backoff(20).as_millis() as u64;
+            assert!((60_000..=90_000).contains(&backoff));
+        }
     }
 }
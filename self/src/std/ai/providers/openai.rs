@@ -1,28 +1,48 @@
 use std::{env, vec};
 
-use reqwest::{Client, Response};
+use reqwest::{Client, RequestBuilder};
 
-use crate::std::ai::providers::{ChatRequest, Message};
+use crate::{
+    core::error::{ai_errors::AIError, VMErrorType},
+    std::ai::providers::{ChatRequest, Message, StreamOptions},
+};
 
-pub async fn fetch(prompt: String) -> Response {
-    let api_key = env::var("OPENAI_API_KEY").expect("OPENAI_API_KEY not set");
+pub const MODEL: &str = "gpt-4o";
+
+pub fn request(
+    client: &Client,
+    prompt: String,
+    stream: bool,
+) -> Result<RequestBuilder, VMErrorType> {
+    let Ok(api_key) = env::var("OPENAI_API_KEY") else {
+        return Err(VMErrorType::AI(AIError::AIFetchError(
+            "OPENAI_API_KEY not set".to_string(),
+        )));
+    };
 
-    let client = Client::new();
     let request_body = ChatRequest {
-        model: "gpt-4o".to_string(),
+        model: MODEL.to_string(),
         messages: vec![Message {
             role: "system".to_string(),
             content: prompt,
         }],
+        stream: if stream { Some(true) } else { None },
+        // openai only sends usage on streams when asked to
+        stream_options: if stream {
+            Some(StreamOptions {
+                include_usage: true,
+            })
+        } else {
+            None
+        },
     };
+    // allows pointing the provider to a compatible server (proxies, local stand-ins)
+    let base_url = env::var("OPENAI_BASE_URL").unwrap_or("https://api.openai.com/v1".to_string());
 
-    let res = client
-        .post("https://api.openai.com/v1/chat/completions")
+    let request = client
+        .post(format!("{}/chat/completions", base_url))
         .bearer_auth(api_key)
-        .json(&request_body)
-        .send()
-        .await
-        .expect("AI: Failed to send request"); // handle no network timeout here
+        .json(&request_body);
 
-    res
+    Ok(request)
 }
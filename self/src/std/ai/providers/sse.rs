@@ -0,0 +1,144 @@
+// minimal server-sent events parser. bytes are fed as they arrive
+// from the network and complete `data` payloads are returned once
+// their event is terminated by a blank line. lines are only decoded
+// once complete, a character can be split across two chunks
+
+pub const SSE_DONE: &str = "[DONE]";
+
+pub struct SseParser {
+    buffer: Vec<u8>,
+    data: Vec<String>,
+}
+
+impl SseParser {
+    pub fn new() -> SseParser {
+        SseParser {
+            buffer: vec![],
+            data: vec![],
+        }
+    }
+
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(chunk);
+
+        let mut events = vec![];
+        while let Some(pos) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+
+            if line.is_empty() {
+                // end of event
+                if !self.data.is_empty() {
+                    events.push(self.data.join("\n"));
+                    self.data.clear();
+                }
+                continue;
+            }
+
+            // comments (keep-alive) and other fields are ignored
+            if let Some(value) = line.strip_prefix("data:") {
+                self.data
+                    .push(value.strip_prefix(' ').unwrap_or(value).to_string());
+            }
+        }
+
+        events
+    }
+
+    // flushes an event not terminated by a blank line when the
+    // stream is closed
+    pub fn finish(&mut self) -> Option<String> {
+        let line = std::mem::take(&mut self.buffer);
+        let line = String::from_utf8_lossy(&line);
+        if let Some(value) = line.trim_end().strip_prefix("data:") {
+            self.data
+                .push(value.strip_prefix(' ').unwrap_or(value).to_string());
+        }
+        if self.data.is_empty() {
+            return None;
+        }
+        let data = self.data.join("\n");
+        self.data.clear();
+        Some(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        thread,
+        time::Duration,
+    };
+
+    use super::{SseParser, SSE_DONE};
+
+    // serves the body in the given pieces, flushed apart so they
+    // reach the client as separate chunks
+    fn serve_sse(pieces: Vec<Vec<u8>>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            stream.set_nodelay(true).unwrap();
+            // skip the request head
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            stream
+                .write_all(
+                    b"HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\nconnection: close\r\n\r\n",
+                )
+                .unwrap();
+            for piece in pieces {
+                stream.write_all(&piece).unwrap();
+                stream.flush().unwrap();
+                thread::sleep(Duration::from_millis(30));
+            }
+        });
+        format!("http://{}/", addr)
+    }
+
+    #[test]
+    fn parses_a_stream_from_a_local_server() {
+        let body = "data: h\u{e9}llo\n\n: keep-alive\n\ndata: line one\ndata: line two\n\ndata: [DONE]\n\n"
+            .as_bytes()
+            .to_vec();
+        // split inside the two bytes of é
+        let split = body.iter().position(|b| *b == 0xc3).unwrap() + 1;
+        let url = serve_sse(vec![body[..split].to_vec(), body[split..].to_vec()]);
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let events = runtime.block_on(async {
+            let mut res = reqwest::get(url).await.unwrap();
+            let mut parser = SseParser::new();
+            let mut events = vec![];
+            while let Some(bytes) = res.chunk().await.unwrap() {
+                events.extend(parser.feed(&bytes));
+            }
+            events.extend(parser.finish());
+            events
+        });
+
+        assert_eq!(
+            events,
+            vec![
+                "h\u{e9}llo".to_string(),
+                "line one\nline two".to_string(),
+                SSE_DONE.to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn flushes_an_unterminated_event() {
+        let mut parser = SseParser::new();
+        assert!(parser.feed(b"data: {\"a\": 1}\ndata: last").is_empty());
+        assert_eq!(parser.finish(), Some("{\"a\": 1}\nlast".to_string()));
+        assert_eq!(parser.finish(), None);
+    }
+}
@@ -0,0 +1,234 @@
+// type hints for structured ai answers. a hint is built from the
+// value the user passes (struct declaration, vector of struct or a
+// primitive name), turned into a json schema for the prompt and used
+// to validate and allocate the answer
+
+use std::collections::HashMap;
+
+use serde_json::{json, Value as JsonValue};
+
+use crate::{
+    core::error::{self, type_errors::TypeError, VMError, VMErrorType},
+    memory::MemObject,
+    opcodes::DataType,
+    std::{heap_utils::put_vector, utils::json_to_value},
+    types::{
+        object::structs::{StructDeclaration, StructLiteral},
+        raw::{bool::Bool, f64::F64, RawValue},
+        Value,
+    },
+    vm::Vm,
+};
+
+#[derive(Debug, Clone)]
+pub enum TypeHint {
+    Primitive(DataType),
+    Struct(StructDeclaration),
+    VectorOf(Box<TypeHint>),
+}
+
+impl TypeHint {
+    pub fn from_value(value: &Value, vm: &Vm) -> Result<TypeHint, VMError> {
+        match value {
+            Value::RawValue(RawValue::Utf8(s)) => TypeHint::from_str(&s.value, vm),
+            Value::Handle(_) | Value::BoundAccess(_) => match value.as_mem_obj(vm)? {
+                MemObject::StructDeclaration(decl) => Ok(TypeHint::Struct(decl.clone())),
+                MemObject::String(s) => TypeHint::from_str(&s.value, vm),
+                MemObject::Vector(v) => {
+                    // [Person] -> the first element is the item type
+                    let Some(item) = v.elements.first() else {
+                        return Err(invalid_hint("[]", vm));
+                    };
+                    let item = TypeHint::from_value(item, vm)?;
+                    Ok(TypeHint::VectorOf(Box::new(item)))
+                }
+                other => Err(invalid_hint(&other.get_type(), vm)),
+            },
+            Value::RawValue(r) => Err(invalid_hint(&r.get_type_string(), vm)),
+        }
+    }
+
+    fn from_str(hint: &str, vm: &Vm) -> Result<TypeHint, VMError> {
+        let hint = hint.trim();
+        if let Some(item) = hint.strip_prefix('[').and_then(|h| h.strip_suffix(']')) {
+            let item = TypeHint::from_str(item, vm)?;
+            return Ok(TypeHint::VectorOf(Box::new(item)));
+        }
+
+        let data_type = match hint {
+            "string" | "utf8" => DataType::Utf8,
+            "number" | "f64" => DataType::F64,
+            "bool" => DataType::Bool,
+            "nothing" => DataType::Nothing,
+            _ => return Err(invalid_hint(hint, vm)),
+        };
+        Ok(TypeHint::Primitive(data_type))
+    }
+
+    // schema of the `value` property of the answer
+    pub fn to_schema(&self) -> JsonValue {
+        match self {
+            TypeHint::Primitive(data_type) => data_type_schema(data_type),
+            TypeHint::Struct(decl) => {
+                let mut properties = serde_json::Map::new();
+                let mut required = vec![];
+                for (name, data_type) in &decl.fields {
+                    properties.insert(name.clone(), data_type_schema(data_type));
+                    required.push(JsonValue::String(name.clone()));
+                }
+                json!({
+                    "type": "object",
+                    "title": decl.identifier,
+                    "properties": properties,
+                    "required": required,
+                    "additionalProperties": false,
+                })
+            }
+            TypeHint::VectorOf(item) => json!({
+                "type": "array",
+                "items": item.to_schema(),
+            }),
+        }
+    }
+
+    pub fn validate(&self, json: &JsonValue) -> Result<(), String> {
+        validate_schema(&self.to_schema(), json, "value")
+    }
+
+    // allocates an already validated json value following the hint
+    pub fn to_value(&self, json: &JsonValue, vm: &mut Vm) -> Value {
+        match self {
+            TypeHint::Primitive(data_type) => typed_json_to_value(data_type, json, vm),
+            TypeHint::Struct(decl) => {
+                let mut fields = HashMap::new();
+                for (name, data_type) in &decl.fields {
+                    let field = json.get(name).unwrap_or(&JsonValue::Null);
+                    let value = typed_json_to_value(data_type, field, vm);
+                    fields.insert(name.clone(), value);
+                }
+                let literal = StructLiteral::new(decl.identifier.clone(), fields);
+                Value::Handle(vm.memory.alloc(MemObject::StructLiteral(literal)))
+            }
+            TypeHint::VectorOf(item) => {
+                let elements = json
+                    .as_array()
+                    .map(|items| items.iter().map(|i| item.to_value(i, vm)).collect())
+                    .unwrap_or_default();
+                Value::Handle(put_vector(vm, elements))
+            }
+        }
+    }
+}
+
+fn invalid_hint(received: &str, vm: &Vm) -> VMError {
+    error::throw(
+        VMErrorType::TypeError(TypeError::InvalidTypeUnwrap {
+            expected: "struct declaration, [struct declaration] or type name".to_string(),
+            received: received.to_string(),
+        }),
+        vm,
+    )
+}
+
+fn data_type_schema(data_type: &DataType) -> JsonValue {
+    match data_type {
+        DataType::Utf8 => json!({ "type": "string" }),
+        DataType::Bool => json!({ "type": "boolean" }),
+        DataType::I32 | DataType::I64 | DataType::U32 | DataType::U64 => {
+            json!({ "type": "integer" })
+        }
+        DataType::F64 => json!({ "type": "number" }),
+        DataType::Nothing => json!({ "type": "null" }),
+        DataType::Vector => json!({ "type": "array" }),
+        DataType::StructLiteral => json!({ "type": "object" }),
+        DataType::Lambda | DataType::Unknown => json!({}),
+    }
+}
+
+fn typed_json_to_value(data_type: &DataType, json: &JsonValue, vm: &mut Vm) -> Value {
+    match (data_type, json) {
+        // ego numbers are declared as f64
+        (DataType::F64, JsonValue::Number(n)) => {
+            Value::RawValue(RawValue::F64(F64::new(n.as_f64().unwrap_or(0.0))))
+        }
+        (DataType::Bool, JsonValue::Bool(b)) => Value::RawValue(RawValue::Bool(Bool::new(*b))),
+        _ => json_to_value(vm, json),
+    }
+}
+
+// validates the subset of json schema generated by `to_schema`
+fn validate_schema(schema: &JsonValue, json: &JsonValue, path: &str) -> Result<(), String> {
+    let Some(expected) = schema.get("type").and_then(|t| t.as_str()) else {
+        return Ok(());
+    };
+
+    let matches = match expected {
+        "string" => json.is_string(),
+        "boolean" => json.is_boolean(),
+        "integer" => json.is_i64() || json.is_u64(),
+        "number" => json.is_number(),
+        "null" => json.is_null(),
+        "array" => json.is_array(),
+        "object" => json.is_object(),
+        _ => true,
+    };
+    if !matches {
+        return Err(format!(
+            "{} should be of type {}, found {}",
+            path, expected, json
+        ));
+    }
+
+    if let (Some(items), Some(values)) = (schema.get("items"), json.as_array()) {
+        for (i, value) in values.iter().enumerate() {
+            validate_schema(items, value, &format!("{}[{}]", path, i))?;
+        }
+    }
+
+    if let Some(object) = json.as_object() {
+        let properties = schema.get("properties").and_then(|p| p.as_object());
+        if let Some(required) = schema.get("required").and_then(|r| r.as_array()) {
+            for field in required.iter().filter_map(|f| f.as_str()) {
+                if !object.contains_key(field) {
+                    return Err(format!("{} is missing the field '{}'", path, field));
+                }
+            }
+        }
+        if let Some(properties) = properties {
+            for (field, value) in object {
+                match properties.get(field) {
+                    Some(field_schema) => {
+                        validate_schema(field_schema, value, &format!("{}.{}", path, field))?
+                    }
+                    None => {
+                        return Err(format!("{} has an unknown field '{}'", path, field));
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// best effort to get a json document out of an answer: strips
+// markdown fences and surrounding text
+pub fn repair_json(answer: &str) -> Option<JsonValue> {
+    let cleaned = answer
+        .trim()
+        .trim_start_matches("```json")
+        .trim_start_matches("```")
+        .trim_end_matches("```")
+        .trim();
+
+    if let Ok(json) = serde_json::from_str(cleaned) {
+        return Some(json);
+    }
+
+    let start = cleaned.find('{')?;
+    let end = cleaned.rfind('}')?;
+    if end <= start {
+        return None;
+    }
+    serde_json::from_str(&cleaned[start..=end]).ok()
+}
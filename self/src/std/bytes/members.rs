@@ -0,0 +1,349 @@
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+
+use crate::{
+    core::error::{self, bytes_errors::BytesError, VMError, VMErrorType},
+    memory::{Handle, MemObject},
+    std::heap_utils::{put_bytes, put_string, put_vector},
+    types::{
+        object::{
+            bytes::Bytes,
+            func::{Engine, Function},
+        },
+        raw::{u32::U32, RawValue},
+        Value,
+    },
+    vm::Vm,
+};
+
+fn bytes_error(err: BytesError, vm: &Vm) -> VMError {
+    error::throw(VMErrorType::Bytes(err), vm)
+}
+
+fn resolve_bytes(vm: &Vm, _self: Option<Handle>) -> &Bytes {
+    let _self = _self.expect("bytes members are bound to a bytes value");
+    if let MemObject::Bytes(bytes) = vm.memory.resolve(&_self) {
+        bytes
+    } else {
+        unreachable!()
+    }
+}
+
+// decodes utf8, the error points to the first invalid byte
+pub fn decode_utf8(bytes: Vec<u8>) -> Result<String, BytesError> {
+    String::from_utf8(bytes).map_err(|e| BytesError::InvalidUtf8(e.utf8_error().valid_up_to()))
+}
+
+fn decode_hex(hex: &str) -> Result<Vec<u8>, BytesError> {
+    let hex = hex.trim();
+    if hex.len() % 2 != 0 {
+        return Err(BytesError::InvalidHex(format!(
+            "odd number of digits ({})",
+            hex.len()
+        )));
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            hex.get(i..i + 2)
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or_else(|| BytesError::InvalidHex(format!("invalid digits at {}", i)))
+        })
+        .collect()
+}
+
+// bytes.from_string
+pub fn from_string_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "from_string".to_string(),
+        vec!["string".to_string()],
+        Engine::Native(from_string),
+    ))
+}
+
+fn from_string(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let string = params[0].as_string_obj(vm)?;
+    if debug {
+        vm.trace(format!("BYTES.FROM_STRING <- {}", string));
+    }
+    Ok(Value::Handle(put_bytes(vm, string.into_bytes())))
+}
+
+// bytes.from_hex
+pub fn from_hex_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "from_hex".to_string(),
+        vec!["hex".to_string()],
+        Engine::Native(from_hex),
+    ))
+}
+
+fn from_hex(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let hex = params[0].as_string_obj(vm)?;
+    if debug {
+        vm.trace(format!("BYTES.FROM_HEX <- {}", hex));
+    }
+    let bytes = decode_hex(&hex).map_err(|e| bytes_error(e, vm))?;
+    Ok(Value::Handle(put_bytes(vm, bytes)))
+}
+
+// bytes.from_base64
+pub fn from_base64_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "from_base64".to_string(),
+        vec!["base64".to_string()],
+        Engine::Native(from_base64),
+    ))
+}
+
+fn from_base64(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let encoded = params[0].as_string_obj(vm)?;
+    if debug {
+        vm.trace(format!("BYTES.FROM_BASE64 <- {}", encoded));
+    }
+    let bytes = STANDARD
+        .decode(encoded.trim())
+        .map_err(|e| bytes_error(BytesError::InvalidBase64(e.to_string()), vm))?;
+    Ok(Value::Handle(put_bytes(vm, bytes)))
+}
+
+// bytes.from_vector
+pub fn from_vector_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "from_vector".to_string(),
+        vec!["vector".to_string()],
+        Engine::Native(from_vector),
+    ))
+}
+
+fn from_vector(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let vector = params[0].as_vector_obj(vm)?;
+    if debug {
+        vm.trace(format!("BYTES.FROM_VECTOR <- {}", vector.to_string(vm)));
+    }
+    let mut bytes = Vec::with_capacity(vector.elements.len());
+    for element in &vector.elements {
+        let byte = match element {
+            Value::RawValue(RawValue::I32(v)) => u8::try_from(v.value).ok(),
+            Value::RawValue(RawValue::I64(v)) => u8::try_from(v.value).ok(),
+            Value::RawValue(RawValue::U32(v)) => u8::try_from(v.value).ok(),
+            Value::RawValue(RawValue::U64(v)) => u8::try_from(v.value).ok(),
+            _ => None,
+        };
+        match byte {
+            Some(byte) => bytes.push(byte),
+            None => {
+                return Err(bytes_error(
+                    BytesError::InvalidByte(element.to_string(vm)),
+                    vm,
+                ))
+            }
+        }
+    }
+    Ok(Value::Handle(put_bytes(vm, bytes)))
+}
+
+// Bytes members
+pub fn len_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "len".to_string(),
+        vec![],
+        Engine::Native(len),
+    ))
+}
+
+fn len(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    _params: Vec<Value>,
+    _debug: bool,
+) -> Result<Value, VMError> {
+    let _self = resolve_bytes(vm, _self);
+    Ok(Value::RawValue(RawValue::U32(U32::new(
+        _self.value.len() as u32
+    ))))
+}
+
+pub fn get_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "get".to_string(),
+        vec!["index".to_string()],
+        Engine::Native(get),
+    ))
+}
+
+fn get(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    _debug: bool,
+) -> Result<Value, VMError> {
+    let index = params[0].as_usize(vm)?;
+    let _self = resolve_bytes(vm, _self);
+    match _self.value.get(index) {
+        Some(byte) => Ok(Value::RawValue(RawValue::U32(U32::new(*byte as u32)))),
+        None => Err(bytes_error(
+            BytesError::OutOfBounds {
+                index,
+                len: _self.value.len(),
+            },
+            vm,
+        )),
+    }
+}
+
+pub fn slice_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "slice".to_string(),
+        vec!["start".to_string()],
+        Engine::Native(slice),
+    ))
+}
+
+// slice(start) or slice(start, end), end is exclusive
+fn slice(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    _debug: bool,
+) -> Result<Value, VMError> {
+    let start = params[0].as_usize(vm)?;
+    let value = &resolve_bytes(vm, _self).value;
+    let end = match params.get(1) {
+        Some(end) => end.as_usize(vm)?,
+        None => value.len(),
+    };
+    if end > value.len() || start > end {
+        let index = if end > value.len() { end } else { start };
+        return Err(bytes_error(
+            BytesError::OutOfBounds {
+                index,
+                len: value.len(),
+            },
+            vm,
+        ));
+    }
+
+    let sliced = value[start..end].to_vec();
+    Ok(Value::Handle(put_bytes(vm, sliced)))
+}
+
+pub fn concat_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "concat".to_string(),
+        vec!["other".to_string()],
+        Engine::Native(concat),
+    ))
+}
+
+fn concat(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    _debug: bool,
+) -> Result<Value, VMError> {
+    let other = params[0].as_bytes_obj(vm)?;
+    let mut joined = resolve_bytes(vm, _self).value.clone();
+    joined.extend(other);
+    Ok(Value::Handle(put_bytes(vm, joined)))
+}
+
+pub fn to_string_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "to_string".to_string(),
+        vec![],
+        Engine::Native(to_string),
+    ))
+}
+
+fn to_string(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    _params: Vec<Value>,
+    _debug: bool,
+) -> Result<Value, VMError> {
+    let value = resolve_bytes(vm, _self).value.clone();
+    let string = decode_utf8(value).map_err(|e| bytes_error(e, vm))?;
+    Ok(Value::Handle(put_string(vm, string)))
+}
+
+pub fn to_hex_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "to_hex".to_string(),
+        vec![],
+        Engine::Native(to_hex),
+    ))
+}
+
+fn to_hex(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    _params: Vec<Value>,
+    _debug: bool,
+) -> Result<Value, VMError> {
+    let hex = resolve_bytes(vm, _self)
+        .value
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    Ok(Value::Handle(put_string(vm, hex)))
+}
+
+pub fn to_base64_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "to_base64".to_string(),
+        vec![],
+        Engine::Native(to_base64),
+    ))
+}
+
+fn to_base64(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    _params: Vec<Value>,
+    _debug: bool,
+) -> Result<Value, VMError> {
+    let encoded = STANDARD.encode(&resolve_bytes(vm, _self).value);
+    Ok(Value::Handle(put_string(vm, encoded)))
+}
+
+pub fn to_vector_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "to_vector".to_string(),
+        vec![],
+        Engine::Native(to_vector),
+    ))
+}
+
+fn to_vector(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    _params: Vec<Value>,
+    _debug: bool,
+) -> Result<Value, VMError> {
+    let elements = resolve_bytes(vm, _self)
+        .value
+        .iter()
+        .map(|b| Value::RawValue(RawValue::U32(U32::new(*b as u32))))
+        .collect();
+    Ok(Value::Handle(put_vector(vm, elements)))
+}
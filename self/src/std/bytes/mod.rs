@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+
+use crate::{memory::MemObject, types::Value, vm::Vm};
+mod members;
+
+pub use members::decode_utf8;
+
+// bytes module, constructors
+pub fn generate_struct() -> (String, Vec<(String, MemObject)>) {
+    let mut fields = vec![];
+
+    fields.push(("from_string".to_string(), members::from_string_obj()));
+    fields.push(("from_hex".to_string(), members::from_hex_obj()));
+    fields.push(("from_base64".to_string(), members::from_base64_obj()));
+    fields.push(("from_vector".to_string(), members::from_vector_obj()));
+
+    ("bytes".to_string(), fields)
+}
+
+const MEMBERS: [&str; 8] = [
+    "len",
+    "get",
+    "slice",
+    "concat",
+    "to_string",
+    "to_hex",
+    "to_base64",
+    "to_vector",
+];
+
+// members of every bytes value
+pub fn init_lib() -> Vec<(String, MemObject)> {
+    let mut fields = vec![];
+
+    fields.push(("len".to_string(), members::len_obj()));
+    fields.push(("get".to_string(), members::get_obj()));
+    fields.push(("slice".to_string(), members::slice_obj()));
+    fields.push(("concat".to_string(), members::concat_obj()));
+    fields.push(("to_string".to_string(), members::to_string_obj()));
+    fields.push(("to_hex".to_string(), members::to_hex_obj()));
+    fields.push(("to_base64".to_string(), members::to_base64_obj()));
+    fields.push(("to_vector".to_string(), members::to_vector_obj()));
+
+    fields
+}
+
+pub fn add_handlers(vm: &mut Vm) -> HashMap<String, Value> {
+    let mut loaded_members = HashMap::new();
+
+    // if bytes lib members are already loaded
+    if vm.handlers.contains_key("bytes.len") {
+        for name in MEMBERS {
+            if let Some(mem) = vm.get_handler(&format!("bytes.{}", name)) {
+                loaded_members.insert(name.to_string(), Value::Handle(mem));
+            }
+        }
+    } else {
+        let fields = init_lib();
+        for (handler_name, handler_obj) in fields {
+            let obj_handle = vm.memory.alloc(handler_obj);
+            loaded_members.insert(handler_name.clone(), Value::Handle(obj_handle.clone()));
+
+            let handler_name = format!("bytes.{}", handler_name); // add lib prefix
+            vm.handlers.insert(handler_name, obj_handle);
+        }
+    }
+
+    loaded_members
+}
@@ -10,6 +10,7 @@ use crate::{
         raw::RawValue,
         Value,
     },
+    utils::path::normalize,
     vm::Vm,
 };
 use std::{env, path::PathBuf};
@@ -43,7 +44,7 @@ pub fn set(
     let value = &params[1].as_string_obj(vm)?;
 
     if debug {
-        println!("ENV_SET -> {}({})", key, value)
+        vm.trace(format!("ENV_SET -> {}({})", key, value))
     }
     env::set_var(key, value);
     Ok(Value::RawValue(RawValue::Nothing))
@@ -77,7 +78,7 @@ pub fn get(
     let key = &params[0].as_string_obj(vm)?;
 
     if debug {
-        println!("ENV_GET -> {}", key)
+        vm.trace(format!("ENV_GET -> {}", key))
     }
     let var = env::var(key);
     match var {
@@ -122,13 +123,13 @@ pub fn read(
             p = p.join(".env");
         }
 
-        p
+        normalize(&p)
     } else {
         cwd.join(".env")
     };
 
     if debug {
-        println!("ENV_READ -> {}", path_buf.display());
+        vm.trace(format!("ENV_READ -> {}", path_buf.display()));
     }
 
     let p = path_buf.display().to_string();
@@ -1,14 +1,18 @@
+use std::collections::HashMap;
 use std::fs::{self, OpenOptions};
-use std::io::Write;
-use std::path::Path;
+use std::io::{ErrorKind, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::core::error::fs_errors::FsError;
 use crate::core::error::type_errors::TypeError;
 use crate::core::error::{self, VMErrorType};
 use crate::memory::Handle;
-use crate::std::heap_utils::{put_string, put_vector};
+use crate::std::heap_utils::{put_bytes, put_string, put_vector};
 use crate::std::NativeMember;
-use crate::types::raw::bool::Bool;
+use crate::types::object::structs::StructLiteral;
+use crate::types::raw::{bool::Bool, u32::U32, u64::U64};
 use crate::{
     core::error::VMError,
     memory::MemObject,
@@ -63,8 +67,8 @@ pub fn read_file(
             vm,
             String::from_utf8_lossy(&content).to_string(),
         ))),
-        Err(_) => Err(error::throw(
-            VMErrorType::Fs(FsError::ReadError(format!("{}", path))),
+        Err(e) => Err(error::throw(
+            VMErrorType::Fs(FsError::ReadError(format!("{}", path), e.kind())),
             vm,
         )),
     }
@@ -125,8 +129,8 @@ pub fn read_dir(
             }
             Ok(Value::Handle(put_vector(vm, dir_entries)))
         }
-        Err(_) => Err(error::throw(
-            VMErrorType::Fs(FsError::ReadError(format!("{}", path))),
+        Err(e) => Err(error::throw(
+            VMErrorType::Fs(FsError::ReadError(format!("{}", path), e.kind())),
             vm,
         )),
     }
@@ -217,22 +221,16 @@ pub fn write_file(
             let write_result = f.write(content.as_bytes());
             match write_result {
                 Ok(_) => Ok(Value::RawValue(RawValue::Bool(Bool::new(true)))),
-                Err(err) => {
-                    println!("err{:#?}", err);
-                    Err(error::throw(
-                        VMErrorType::Fs(FsError::WriteError(path.to_string())),
-                        vm,
-                    ))
-                }
+                Err(err) => Err(error::throw(
+                    VMErrorType::Fs(FsError::WriteError(path.to_string(), err.kind())),
+                    vm,
+                )),
             }
         }
-        Err(err) => {
-            println!("err{:#?}", err);
-            Err(error::throw(
-                VMErrorType::Fs(FsError::WriteError(path.to_string())),
-                vm,
-            ))
-        }
+        Err(err) => Err(error::throw(
+            VMErrorType::Fs(FsError::WriteError(path.to_string(), err.kind())),
+            vm,
+        )),
     }
 }
 
@@ -295,9 +293,672 @@ pub fn delete(
 
     match op_result {
         Ok(_) => Ok(Value::RawValue(RawValue::Bool(Bool::new(true)))),
-        Err(_) => Err(error::throw(
-            VMErrorType::Fs(FsError::DeleteError(path.to_string())),
+        Err(e) => Err(error::throw(
+            VMErrorType::Fs(FsError::DeleteError(path.to_string(), e.kind())),
             vm,
         )),
     }
 }
+
+fn fs_error(err: FsError, vm: &Vm) -> VMError {
+    error::throw(VMErrorType::Fs(err), vm)
+}
+
+fn path_value(vm: &mut Vm, path: &Path) -> Value {
+    Value::Handle(put_string(vm, path.to_string_lossy().to_string()))
+}
+
+// append
+pub fn append_def() -> NativeMember {
+    NativeMember {
+        name: "append".to_string(),
+        description: "append content at the end of a file on the given path, the file is created if it doesn't exist".to_string(),
+        params: Some(vec!["path(string)".to_string(), "content(string)".to_string()]),
+    }
+}
+
+pub fn append_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "append".to_string(),
+        vec!["path".to_string(), "content".to_string()],
+        Engine::Native(append),
+    ))
+}
+
+pub fn append(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let path = params[0].as_string_obj(vm)?;
+    let content = params[1].as_string_obj(vm)?;
+    if debug {
+        vm.trace(format!("FS.APPEND <- {}", path));
+    }
+
+    OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(&path)
+        .and_then(|mut f| f.write_all(content.as_bytes()))
+        .map_err(|e| fs_error(FsError::WriteError(path.clone(), e.kind()), vm))?;
+
+    Ok(Value::RawValue(RawValue::Bool(Bool::new(true))))
+}
+
+// exists
+pub fn exists_def() -> NativeMember {
+    NativeMember {
+        name: "exists".to_string(),
+        description: "check if a file or a directory exists on the given path".to_string(),
+        params: Some(vec!["path(string)".to_string()]),
+    }
+}
+
+pub fn exists_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "exists".to_string(),
+        vec!["path".to_string()],
+        Engine::Native(exists),
+    ))
+}
+
+pub fn exists(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let path = params[0].as_string_obj(vm)?;
+    let exists = Path::new(&path).exists();
+    if debug {
+        vm.trace(format!("FS.EXISTS <- {} -> {}", path, exists));
+    }
+    Ok(Value::RawValue(RawValue::Bool(Bool::new(exists))))
+}
+
+// stat
+pub fn stat_def() -> NativeMember {
+    NativeMember {
+        name: "stat".to_string(),
+        description: "get the metadata of a file or a directory: size (bytes), modified (milliseconds since the unix epoch), is_dir, is_file, is_symlink, readonly and permissions (unix mode bits)".to_string(),
+        params: Some(vec!["path(string)".to_string()]),
+    }
+}
+
+pub fn stat_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "stat".to_string(),
+        vec!["path".to_string()],
+        Engine::Native(stat),
+    ))
+}
+
+pub fn stat(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let path = params[0].as_string_obj(vm)?;
+    if debug {
+        vm.trace(format!("FS.STAT <- {}", path));
+    }
+
+    let metadata = fs::symlink_metadata(&path).map_err(|e| match e.kind() {
+        ErrorKind::NotFound => fs_error(FsError::FileNotFound(path.clone()), vm),
+        kind => fs_error(FsError::ReadError(path.clone(), kind), vm),
+    })?;
+    let is_symlink = metadata.file_type().is_symlink();
+    // the rest of the fields describe the link target
+    let metadata = if is_symlink {
+        fs::metadata(&path).unwrap_or(metadata)
+    } else {
+        metadata
+    };
+
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    #[cfg(unix)]
+    let permissions = {
+        use std::os::unix::fs::PermissionsExt;
+        Value::RawValue(RawValue::U32(U32::new(
+            metadata.permissions().mode() & 0o7777,
+        )))
+    };
+    #[cfg(not(unix))]
+    let permissions = Value::RawValue(RawValue::Nothing);
+
+    let bool_value = |b: bool| Value::RawValue(RawValue::Bool(Bool::new(b)));
+    let mut fields = HashMap::new();
+    fields.insert(
+        "size".to_string(),
+        Value::RawValue(RawValue::U64(U64::new(metadata.len()))),
+    );
+    fields.insert(
+        "modified".to_string(),
+        Value::RawValue(RawValue::U64(U64::new(modified))),
+    );
+    fields.insert("is_dir".to_string(), bool_value(metadata.is_dir()));
+    fields.insert("is_file".to_string(), bool_value(metadata.is_file()));
+    fields.insert("is_symlink".to_string(), bool_value(is_symlink));
+    fields.insert(
+        "readonly".to_string(),
+        bool_value(metadata.permissions().readonly()),
+    );
+    fields.insert("permissions".to_string(), permissions);
+    let stat = StructLiteral::new("FileStat".to_string(), fields);
+
+    Ok(Value::Handle(
+        vm.memory.alloc(MemObject::StructLiteral(stat)),
+    ))
+}
+
+// mkdir
+pub fn mkdir_def() -> NativeMember {
+    NativeMember {
+        name: "mkdir".to_string(),
+        description: "create a directory on the given path, its parent must exist".to_string(),
+        params: Some(vec!["path(string)".to_string()]),
+    }
+}
+
+pub fn mkdir_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "mkdir".to_string(),
+        vec!["path".to_string()],
+        Engine::Native(mkdir),
+    ))
+}
+
+pub fn mkdir(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let path = params[0].as_string_obj(vm)?;
+    if debug {
+        vm.trace(format!("FS.MKDIR <- {}", path));
+    }
+    fs::create_dir(&path)
+        .map_err(|e| fs_error(FsError::CreateDirError(path.clone(), e.kind()), vm))?;
+    Ok(Value::RawValue(RawValue::Bool(Bool::new(true))))
+}
+
+// mkdir_all
+pub fn mkdir_all_def() -> NativeMember {
+    NativeMember {
+        name: "mkdir_all".to_string(),
+        description: "create a directory on the given path and all its missing parents, nothing happens if it already exists".to_string(),
+        params: Some(vec!["path(string)".to_string()]),
+    }
+}
+
+pub fn mkdir_all_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "mkdir_all".to_string(),
+        vec!["path".to_string()],
+        Engine::Native(mkdir_all),
+    ))
+}
+
+pub fn mkdir_all(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let path = params[0].as_string_obj(vm)?;
+    if debug {
+        vm.trace(format!("FS.MKDIR_ALL <- {}", path));
+    }
+    fs::create_dir_all(&path)
+        .map_err(|e| fs_error(FsError::CreateDirError(path.clone(), e.kind()), vm))?;
+    Ok(Value::RawValue(RawValue::Bool(Bool::new(true))))
+}
+
+// copy
+pub fn copy_def() -> NativeMember {
+    NativeMember {
+        name: "copy".to_string(),
+        description: "copy a file, or a directory recursively, to the destination path. Existing files are overwritten".to_string(),
+        params: Some(vec!["from(string)".to_string(), "to(string)".to_string()]),
+    }
+}
+
+pub fn copy_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "copy".to_string(),
+        vec!["from".to_string(), "to".to_string()],
+        Engine::Native(copy),
+    ))
+}
+
+pub fn copy(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let from = params[0].as_string_obj(vm)?;
+    let to = params[1].as_string_obj(vm)?;
+    if debug {
+        vm.trace(format!("FS.COPY <- {} -> {}", from, to));
+    }
+
+    let from_path = Path::new(&from);
+    if !from_path.exists() {
+        return Err(fs_error(FsError::FileNotFound(from), vm));
+    }
+    let result = if from_path.is_dir() {
+        // a copy inside the copied dir would never end
+        if let (Ok(source), Ok(target)) =
+            (fs::canonicalize(from_path), resolve_path(Path::new(&to)))
+        {
+            if target.starts_with(&source) {
+                return Err(fs_error(FsError::CopyIntoItself { from, to }, vm));
+            }
+        }
+        copy_dir(from_path, Path::new(&to))
+    } else {
+        fs::copy(from_path, &to).map(|_| ())
+    };
+    result.map_err(|e| {
+        fs_error(
+            FsError::CopyError {
+                from: from.clone(),
+                to: to.clone(),
+                kind: e.kind(),
+            },
+            vm,
+        )
+    })?;
+
+    Ok(Value::RawValue(RawValue::Bool(Bool::new(true))))
+}
+
+// canonical form of a path that may not exist yet, every existing
+// prefix is canonicalized, so `..` goes up from the real directory
+fn resolve_path(path: &Path) -> std::io::Result<PathBuf> {
+    let mut resolved = PathBuf::new();
+    for component in std::path::absolute(path)?.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            component => {
+                resolved.push(component);
+                if resolved.exists() {
+                    resolved = fs::canonicalize(&resolved)?;
+                }
+            }
+        }
+    }
+    Ok(resolved)
+}
+
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+// rename
+pub fn rename_def() -> NativeMember {
+    NativeMember {
+        name: "rename".to_string(),
+        description: "move or rename a file or a directory to the destination path".to_string(),
+        params: Some(vec!["from(string)".to_string(), "to(string)".to_string()]),
+    }
+}
+
+pub fn rename_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "rename".to_string(),
+        vec!["from".to_string(), "to".to_string()],
+        Engine::Native(rename),
+    ))
+}
+
+pub fn rename(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let from = params[0].as_string_obj(vm)?;
+    let to = params[1].as_string_obj(vm)?;
+    if debug {
+        vm.trace(format!("FS.RENAME <- {} -> {}", from, to));
+    }
+
+    if !Path::new(&from).exists() {
+        return Err(fs_error(FsError::FileNotFound(from), vm));
+    }
+    fs::rename(&from, &to).map_err(|e| {
+        fs_error(
+            FsError::RenameError {
+                from: from.clone(),
+                to: to.clone(),
+                kind: e.kind(),
+            },
+            vm,
+        )
+    })?;
+
+    Ok(Value::RawValue(RawValue::Bool(Bool::new(true))))
+}
+
+// glob
+pub fn glob_def() -> NativeMember {
+    NativeMember {
+        name: "glob".to_string(),
+        description: "get the paths matching a glob pattern, like src/**/*.ego".to_string(),
+        params: Some(vec!["pattern(string)".to_string()]),
+    }
+}
+
+pub fn glob_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "glob".to_string(),
+        vec!["pattern".to_string()],
+        Engine::Native(glob),
+    ))
+}
+
+pub fn glob(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let pattern = params[0].as_string_obj(vm)?;
+    if debug {
+        vm.trace(format!("FS.GLOB <- {}", pattern));
+    }
+
+    let paths = glob::glob(&pattern).map_err(|e| {
+        fs_error(
+            FsError::InvalidPattern {
+                pattern: pattern.clone(),
+                message: e.msg.to_string(),
+            },
+            vm,
+        )
+    })?;
+    // unreadable entries are skipped
+    let paths: Vec<PathBuf> = paths.filter_map(Result::ok).collect();
+    let paths = paths.iter().map(|p| path_value(vm, p)).collect();
+
+    Ok(Value::Handle(put_vector(vm, paths)))
+}
+
+// walk
+pub fn walk_def() -> NativeMember {
+    NativeMember {
+        name: "walk".to_string(),
+        description:
+            "get the paths of every file and directory inside of the given directory, recursively"
+                .to_string(),
+        params: Some(vec!["path(string)".to_string()]),
+    }
+}
+
+pub fn walk_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "walk".to_string(),
+        vec!["path".to_string()],
+        Engine::Native(walk),
+    ))
+}
+
+pub fn walk(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let path = params[0].as_string_obj(vm)?;
+    if debug {
+        vm.trace(format!("FS.WALK <- {}", path));
+    }
+
+    let path_obj = Path::new(&path);
+    if !path_obj.exists() {
+        return Err(fs_error(FsError::FileNotFound(path), vm));
+    }
+    if !path_obj.is_dir() {
+        return Err(fs_error(FsError::NotADir(path), vm));
+    }
+
+    let mut paths = vec![];
+    walk_dir(path_obj, &mut paths)
+        .map_err(|e| fs_error(FsError::ReadError(path.clone(), e.kind()), vm))?;
+    let paths = paths.iter().map(|p| path_value(vm, p)).collect();
+
+    Ok(Value::Handle(put_vector(vm, paths)))
+}
+
+// parents come before their entries. symlinked directories are
+// not followed
+fn walk_dir(dir: &Path, paths: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    let mut entries = fs::read_dir(dir)?.collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by_key(|e| e.file_name());
+    for entry in entries {
+        let path = entry.path();
+        let is_dir = entry.file_type()?.is_dir();
+        paths.push(path.clone());
+        if is_dir {
+            walk_dir(&path, paths)?;
+        }
+    }
+    Ok(())
+}
+
+// temp_file
+pub fn temp_file_def() -> NativeMember {
+    NativeMember {
+        name: "temp_file".to_string(),
+        description: "create an empty file with an unique name on the temporary directory and get its path. An optional prefix can be given for the name".to_string(),
+        params: Some(vec!["prefix(string, optional)".to_string()]),
+    }
+}
+
+pub fn temp_file_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "temp_file".to_string(),
+        vec![],
+        Engine::Native(temp_file),
+    ))
+}
+
+pub fn temp_file(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let prefix = temp_prefix(vm, &params)?;
+    let path = create_temp(&prefix, |p| {
+        OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(p)
+            .map(|_| ())
+    })
+    .map_err(|(p, e)| fs_error(FsError::WriteError(p, e.kind()), vm))?;
+    if debug {
+        vm.trace(format!("FS.TEMP_FILE -> {}", path.display()));
+    }
+    Ok(path_value(vm, &path))
+}
+
+// temp_dir
+pub fn temp_dir_def() -> NativeMember {
+    NativeMember {
+        name: "temp_dir".to_string(),
+        description: "create an empty directory with an unique name on the temporary directory and get its path. An optional prefix can be given for the name".to_string(),
+        params: Some(vec!["prefix(string, optional)".to_string()]),
+    }
+}
+
+pub fn temp_dir_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "temp_dir".to_string(),
+        vec![],
+        Engine::Native(temp_dir),
+    ))
+}
+
+pub fn temp_dir(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let prefix = temp_prefix(vm, &params)?;
+    let path = create_temp(&prefix, |p| fs::create_dir(p))
+        .map_err(|(p, e)| fs_error(FsError::CreateDirError(p, e.kind()), vm))?;
+    if debug {
+        vm.trace(format!("FS.TEMP_DIR -> {}", path.display()));
+    }
+    Ok(path_value(vm, &path))
+}
+
+fn temp_prefix(vm: &Vm, params: &[Value]) -> Result<String, VMError> {
+    match params.first() {
+        Some(Value::RawValue(RawValue::Nothing)) | None => Ok("ego".to_string()),
+        Some(prefix) => prefix.as_string_obj(vm),
+    }
+}
+
+// names are made unique with the pid, the time and a counter,
+// creation fails if the path is taken so another one is tried
+fn create_temp(
+    prefix: &str,
+    create: impl Fn(&Path) -> std::io::Result<()>,
+) -> Result<PathBuf, (String, std::io::Error)> {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let dir = std::env::temp_dir();
+    loop {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or(0);
+        let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let path = dir.join(format!(
+            "{}-{}-{:x}{:x}",
+            prefix,
+            std::process::id(),
+            nanos,
+            count
+        ));
+        match create(&path) {
+            Ok(()) => return Ok(path),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err((path.to_string_lossy().to_string(), e)),
+        }
+    }
+}
+
+// read_bytes
+pub fn read_bytes_def() -> NativeMember {
+    NativeMember {
+        name: "read_bytes".to_string(),
+        description:
+            "read a file on the given path as bytes, for binary files like images or archives"
+                .to_string(),
+        params: Some(vec!["path(string)".to_string()]),
+    }
+}
+
+pub fn read_bytes_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "read_bytes".to_string(),
+        vec!["path".to_string()],
+        Engine::Native(read_bytes),
+    ))
+}
+
+pub fn read_bytes(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let path = params[0].as_string_obj(vm)?;
+    if debug {
+        vm.trace(format!("FS.READ_BYTES <- {}", path));
+    }
+
+    let path_obj = Path::new(&path);
+    if !path_obj.exists() {
+        return Err(fs_error(FsError::FileNotFound(path), vm));
+    }
+    if !path_obj.is_file() {
+        return Err(fs_error(FsError::NotAFile(path), vm));
+    }
+    let content =
+        fs::read(path_obj).map_err(|e| fs_error(FsError::ReadError(path.clone(), e.kind()), vm))?;
+
+    Ok(Value::Handle(put_bytes(vm, content)))
+}
+
+// write_bytes
+pub fn write_bytes_def() -> NativeMember {
+    NativeMember {
+        name: "write_bytes".to_string(),
+        description: "write bytes to a file on the given path, the file is created or overwritten"
+            .to_string(),
+        params: Some(vec![
+            "path(string)".to_string(),
+            "content(bytes)".to_string(),
+        ]),
+    }
+}
+
+pub fn write_bytes_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "write_bytes".to_string(),
+        vec!["path".to_string(), "content".to_string()],
+        Engine::Native(write_bytes),
+    ))
+}
+
+pub fn write_bytes(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let path = params[0].as_string_obj(vm)?;
+    let content = params[1].as_bytes_obj(vm)?;
+    if debug {
+        vm.trace(format!(
+            "FS.WRITE_BYTES <- {} ({} bytes)",
+            path,
+            content.len()
+        ));
+    }
+
+    fs::write(&path, content)
+        .map_err(|e| fs_error(FsError::WriteError(path.clone(), e.kind()), vm))?;
+
+    Ok(Value::RawValue(RawValue::Bool(Bool::new(true))))
+}
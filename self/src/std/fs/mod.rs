@@ -1,12 +1,19 @@
 mod members;
+pub mod types;
+mod watch;
 
 use crate::{
     memory::MemObject,
     std::{
         fs::members::{
-            delete_obj, read_dir_def, read_dir_obj, read_file_def, read_file_obj, write_file_def,
+            append_def, append_obj, copy_def, copy_obj, delete_obj, exists_def, exists_obj,
+            glob_def, glob_obj, mkdir_all_def, mkdir_all_obj, mkdir_def, mkdir_obj, read_bytes_def,
+            read_bytes_obj, read_dir_def, read_dir_obj, read_file_def, read_file_obj, rename_def,
+            rename_obj, stat_def, stat_obj, temp_dir_def, temp_dir_obj, temp_file_def,
+            temp_file_obj, walk_def, walk_obj, write_bytes_def, write_bytes_obj, write_file_def,
             write_file_obj,
         },
+        fs::watch::{watch_def, watch_obj},
         NativeModuleDef,
     },
 };
@@ -18,12 +25,44 @@ pub fn generate_struct() -> (String, Vec<(String, MemObject)>) {
     fields.push(("read_dir".to_string(), read_dir_obj()));
     fields.push(("write_file".to_string(), write_file_obj()));
     fields.push(("delete".to_string(), delete_obj()));
+    fields.push(("append".to_string(), append_obj()));
+    fields.push(("exists".to_string(), exists_obj()));
+    fields.push(("stat".to_string(), stat_obj()));
+    fields.push(("mkdir".to_string(), mkdir_obj()));
+    fields.push(("mkdir_all".to_string(), mkdir_all_obj()));
+    fields.push(("copy".to_string(), copy_obj()));
+    fields.push(("rename".to_string(), rename_obj()));
+    fields.push(("glob".to_string(), glob_obj()));
+    fields.push(("walk".to_string(), walk_obj()));
+    fields.push(("temp_file".to_string(), temp_file_obj()));
+    fields.push(("temp_dir".to_string(), temp_dir_obj()));
+    fields.push(("read_bytes".to_string(), read_bytes_obj()));
+    fields.push(("write_bytes".to_string(), write_bytes_obj()));
+    fields.push(("watch".to_string(), watch_obj()));
 
     ("fs".to_string(), fields)
 }
 
 pub fn generate_mod_def() -> NativeModuleDef {
-    let members = vec![write_file_def(), read_file_def(), read_dir_def()];
+    let members = vec![
+        write_file_def(),
+        read_file_def(),
+        read_dir_def(),
+        append_def(),
+        exists_def(),
+        stat_def(),
+        mkdir_def(),
+        mkdir_all_def(),
+        copy_def(),
+        rename_def(),
+        glob_def(),
+        walk_def(),
+        temp_file_def(),
+        temp_dir_def(),
+        read_bytes_def(),
+        write_bytes_def(),
+        watch_def(),
+    ];
 
     NativeModuleDef {
         module: "fs".to_string(),
@@ -0,0 +1,42 @@
+use std::collections::HashMap;
+
+use tokio::sync::mpsc;
+
+use crate::{
+    std::{fs::watch::stop_obj, heap_utils::put_string},
+    types::{object::structs::StructLiteral, Value},
+    vm::Vm,
+};
+
+// a directory (or file) watched with `fs.watch`. the polling
+// task owns the state, the vm only keeps the stop channel
+#[derive(Debug)]
+pub struct Watcher {
+    pub path: String,
+    pub stop: mpsc::UnboundedSender<()>,
+    pub shape: StructLiteral,
+}
+
+impl Watcher {
+    pub fn new_initialized(vm: &mut Vm, path: String, stop: mpsc::UnboundedSender<()>) -> Watcher {
+        let mut fields = HashMap::new();
+        let path_handle = put_string(vm, path.clone());
+        fields.insert("path".to_string(), Value::Handle(path_handle));
+        let stop_handle = vm.memory.alloc(stop_obj());
+        fields.insert("stop".to_string(), Value::Handle(stop_handle));
+
+        Watcher {
+            path,
+            stop,
+            shape: StructLiteral::new("Watcher".to_string(), fields),
+        }
+    }
+
+    pub fn to_string(&self) -> String {
+        format!("Watcher({})", self.path)
+    }
+
+    pub fn property_access(&self, property: &str) -> Option<Value> {
+        self.shape.property_access(property)
+    }
+}
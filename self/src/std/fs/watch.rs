@@ -0,0 +1,314 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+use futures::future::BoxFuture;
+use serde_json::json;
+use tokio::{sync::mpsc, time::interval};
+
+use crate::{
+    core::error::{self, fs_errors::FsError, type_errors::TypeError, VMError, VMErrorType},
+    events::Event,
+    memory::{Handle, MemObject},
+    std::{fs::types::Watcher, NativeMember},
+    types::{
+        object::{
+            func::{Engine, Function},
+            native_struct::NativeStruct,
+        },
+        raw::RawValue,
+        Value,
+    },
+    vm::Vm,
+};
+
+// there's no os notification backend, the watched tree is
+// scanned on every poll and compared with the previous scan
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+const DEFAULT_DEBOUNCE_MS: u64 = 100;
+
+// options struct: { recursive, debounce_ms }
+struct WatchOptions {
+    recursive: bool,
+    debounce: Duration,
+}
+
+fn watch_options(vm: &Vm, value: &Value) -> Result<WatchOptions, VMError> {
+    let mut options = WatchOptions {
+        recursive: false,
+        debounce: Duration::from_millis(DEFAULT_DEBOUNCE_MS),
+    };
+    if let Value::RawValue(RawValue::Nothing) = value {
+        return Ok(options);
+    }
+    let fields = value.as_struct_obj(vm)?;
+    for (name, field) in &fields.fields {
+        if let Value::RawValue(RawValue::Nothing) = field {
+            continue;
+        }
+        match name.as_str() {
+            "recursive" => options.recursive = field.as_bool(vm)?,
+            "debounce_ms" => options.debounce = Duration::from_millis(field.as_usize(vm)? as u64),
+            _ => {}
+        }
+    }
+    Ok(options)
+}
+
+// watch
+pub fn watch_def() -> NativeMember {
+    NativeMember {
+        name: "watch".to_string(),
+        description: "watch a file or a directory and call the callback with a {kind, path} struct for each change, kind is create, modify or remove. Options: {recursive(bool), debounce_ms(number)}. Returns a watcher with a stop() method".to_string(),
+        params: Some(vec![
+            "path(string)".to_string(),
+            "options(struct, optional)".to_string(),
+            "callback(function)".to_string(),
+        ]),
+    }
+}
+
+pub fn watch_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "watch".to_string(),
+        vec!["path".to_string(), "callback".to_string()],
+        Engine::NativeAsync(watch),
+    ))
+}
+
+// fs.watch(path, callback) or fs.watch(path, options, callback)
+pub fn watch(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> BoxFuture<'_, Result<Value, VMError>> {
+    Box::pin(async move {
+        let (path, options, callback) = match params.as_slice() {
+            [path, callback] => (
+                path,
+                watch_options(vm, &Value::RawValue(RawValue::Nothing))?,
+                callback,
+            ),
+            [path, options, callback] => (path, watch_options(vm, options)?, callback),
+            _ => {
+                return Err(error::throw(
+                    VMErrorType::TypeError(TypeError::InvalidArgsCount {
+                        expected: 3,
+                        received: params.len() as u32,
+                    }),
+                    vm,
+                ))
+            }
+        };
+        let path = path.as_string_obj(vm)?;
+        let callback = callback.as_function_obj(vm)?;
+        if debug {
+            vm.trace(format!(
+                "FS.WATCH <- {} (recursive: {})",
+                path, options.recursive
+            ));
+        }
+
+        if !Path::new(&path).exists() {
+            return Err(error::throw(
+                VMErrorType::Fs(FsError::FileNotFound(path)),
+                vm,
+            ));
+        }
+        // scanned from an absolute path, so os.chdir doesn't move
+        // the watcher, but reported as it was given
+        let root = std::env::current_dir().unwrap_or_default().join(&path);
+        let shown_root = PathBuf::from(&path);
+
+        // the baseline is taken before returning, changes made right
+        // after the call are reported
+        let mut previous = scan(root.clone(), options.recursive).await;
+        let vm_notifier = vm.get_vm_notifier();
+        let keep_alive = vm.keep_alive();
+        let (stop_sender, mut stop) = mpsc::unbounded_channel::<()>();
+        tokio::spawn(async move {
+            let _keep_alive = keep_alive;
+            let mut pending: HashMap<PathBuf, (ChangeKind, Instant)> = HashMap::new();
+            let mut tick = interval(POLL_INTERVAL);
+            loop {
+                tokio::select! {
+                    _ = tick.tick() => {}
+                    _ = stop.recv() => break,
+                }
+
+                let current = scan(root.clone(), options.recursive).await;
+                for (path, kind) in diff(&previous, &current) {
+                    let merged = match pending.remove(&path) {
+                        Some((before, _)) => merge(before, kind),
+                        None => Some(kind),
+                    };
+                    if let Some(kind) = merged {
+                        pending.insert(path, (kind, Instant::now()));
+                    }
+                }
+                previous = current;
+
+                let mut settled: Vec<PathBuf> = pending
+                    .iter()
+                    .filter(|(_, (_, changed))| changed.elapsed() >= options.debounce)
+                    .map(|(path, _)| path.clone())
+                    .collect();
+                settled.sort();
+                for path in settled {
+                    let Some((kind, _)) = pending.remove(&path) else {
+                        continue;
+                    };
+                    let shown = match path.strip_prefix(&root) {
+                        Ok(relative) if relative.as_os_str().is_empty() => shown_root.clone(),
+                        Ok(relative) => shown_root.join(relative),
+                        Err(_) => path.clone(),
+                    };
+                    let change = json!({
+                        "kind": kind.as_str(),
+                        "path": shown.to_string_lossy(),
+                    });
+                    if vm_notifier
+                        .send(Event::Call(callback.clone(), vec![change]))
+                        .is_err()
+                    {
+                        return;
+                    }
+                }
+            }
+        });
+
+        let watcher = Watcher::new_initialized(vm, path, stop_sender);
+        Ok(Value::Handle(vm.memory.alloc(MemObject::NativeStruct(
+            NativeStruct::Watcher(watcher),
+        ))))
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ChangeKind {
+    Create,
+    Modify,
+    Remove,
+}
+
+impl ChangeKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ChangeKind::Create => "create",
+            ChangeKind::Modify => "modify",
+            ChangeKind::Remove => "remove",
+        }
+    }
+}
+
+// changes on the same path inside of the debounce window are
+// reported as one, none if they cancel each other
+fn merge(before: ChangeKind, after: ChangeKind) -> Option<ChangeKind> {
+    match (before, after) {
+        (ChangeKind::Create, ChangeKind::Modify) => Some(ChangeKind::Create),
+        (ChangeKind::Create, ChangeKind::Remove) => None,
+        (ChangeKind::Remove, ChangeKind::Create) => Some(ChangeKind::Modify),
+        (_, after) => Some(after),
+    }
+}
+
+#[derive(PartialEq)]
+struct EntryState {
+    is_dir: bool,
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+type Snapshot = HashMap<PathBuf, EntryState>;
+
+async fn scan(root: PathBuf, recursive: bool) -> Snapshot {
+    tokio::task::spawn_blocking(move || {
+        let mut snapshot = HashMap::new();
+        match fs::metadata(&root) {
+            Ok(metadata) if metadata.is_dir() => scan_dir(&root, recursive, &mut snapshot),
+            Ok(metadata) => {
+                snapshot.insert(root, entry_state(&metadata));
+            }
+            // a removed root shows up as the removal of its entries
+            Err(_) => {}
+        }
+        snapshot
+    })
+    .await
+    .unwrap_or_default()
+}
+
+fn scan_dir(dir: &Path, recursive: bool, snapshot: &mut Snapshot) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let path = entry.path();
+        if recursive && metadata.is_dir() {
+            scan_dir(&path, recursive, snapshot);
+        }
+        snapshot.insert(path, entry_state(&metadata));
+    }
+}
+
+fn entry_state(metadata: &fs::Metadata) -> EntryState {
+    EntryState {
+        is_dir: metadata.is_dir(),
+        len: metadata.len(),
+        modified: metadata.modified().ok(),
+    }
+}
+
+fn diff(previous: &Snapshot, current: &Snapshot) -> Vec<(PathBuf, ChangeKind)> {
+    let mut changes = vec![];
+    for (path, state) in current {
+        match previous.get(path) {
+            None => changes.push((path.clone(), ChangeKind::Create)),
+            // directories change with their entries, which are
+            // already reported
+            Some(before) if before != state && !state.is_dir => {
+                changes.push((path.clone(), ChangeKind::Modify))
+            }
+            _ => {}
+        }
+    }
+    for path in previous.keys() {
+        if !current.contains_key(path) {
+            changes.push((path.clone(), ChangeKind::Remove));
+        }
+    }
+    changes
+}
+
+// Watcher type methods
+pub fn stop_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "stop".to_string(),
+        vec![],
+        Engine::Native(stop),
+    ))
+}
+
+pub fn stop(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    _params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let _self = _self.expect("watcher methods are bound to a watcher");
+    let MemObject::NativeStruct(NativeStruct::Watcher(watcher)) = vm.memory.resolve(&_self) else {
+        unreachable!()
+    };
+    if debug {
+        vm.trace(format!("FS.WATCHER.STOP <- {}", watcher.path));
+    }
+    let _ = watcher.stop.send(());
+    Ok(Value::RawValue(RawValue::Nothing))
+}
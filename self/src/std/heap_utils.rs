@@ -1,7 +1,7 @@
 use crate::{
     memory::{Handle, MemObject},
     types::{
-        object::{string::SelfString, vector::Vector},
+        object::{bytes::Bytes, string::SelfString, vector::Vector},
         Value,
     },
     vm::Vm,
@@ -16,3 +16,8 @@ pub fn put_vector(vm: &mut Vm, vector: Vec<Value>) -> Handle {
     vm.memory
         .alloc(MemObject::Vector(Vector::new_initialized(vector, vm)))
 }
+
+pub fn put_bytes(vm: &mut Vm, bytes: Vec<u8>) -> Handle {
+    let bytes_obj = Bytes::new(bytes, vm);
+    vm.memory.alloc(MemObject::Bytes(bytes_obj))
+}
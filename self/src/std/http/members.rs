@@ -2,7 +2,9 @@ use crate::{
     core::error::{self, net_errors::NetErrors, type_errors::TypeError, VMError, VMErrorType},
     memory::{Handle, MemObject},
     std::{
-        heap_utils::put_string,
+        bytes::decode_utf8,
+        heap_utils::{put_bytes, put_string},
+        http::types::HttpResponse,
         mcp::types::{McpClient, McpTool},
         NativeMember,
     },
@@ -58,7 +60,7 @@ pub fn get(
         //let config = &params[1].as_struct_obj(vm)?;
 
         if debug {
-            println!("HTTP.GET -> {}", url);
+            vm.trace(format!("HTTP.GET -> {}", url));
         }
 
         let client = Client::new();
@@ -85,3 +87,113 @@ pub fn get(
         Ok(Value::Handle(handle))
     })
 }
+
+// http.fetch
+pub fn fetch_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "fetch".to_string(),
+        vec!["url".to_string()],
+        Engine::NativeAsync(fetch),
+    ))
+}
+
+pub fn fetch_def() -> NativeMember {
+    NativeMember {
+        name: "fetch".to_string(),
+        description: "Http GET request to the given url. Returns a response with status, url, headers and the text() and bytes() methods to read the body.".to_string(),
+        params: Some(vec!["url(string)".to_string()]),
+    }
+}
+
+pub fn fetch(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> BoxFuture<'_, Result<Value, VMError>> {
+    Box::pin(async move {
+        let url = params[0].as_string_obj(vm)?;
+        if debug {
+            vm.trace(format!("HTTP.FETCH -> {}", url));
+        }
+
+        let read_error = |vm: &Vm| {
+            error::throw(
+                VMErrorType::Net(NetErrors::ReadError(format!("cannot get {}", url))),
+                vm,
+            )
+        };
+        let response = match Client::new().get(&url).send().await {
+            Ok(response) => response,
+            Err(_) => return Err(read_error(vm)),
+        };
+        let status = response.status().as_u16();
+        let final_url = response.url().to_string();
+        let headers = response
+            .headers()
+            .iter()
+            .map(|(name, value)| {
+                (
+                    name.to_string(),
+                    String::from_utf8_lossy(value.as_bytes()).to_string(),
+                )
+            })
+            .collect();
+        let body = match response.bytes().await {
+            Ok(body) => body.to_vec(),
+            Err(_) => return Err(read_error(vm)),
+        };
+
+        let response = HttpResponse::new_initialized(vm, final_url, status, headers, body);
+        Ok(Value::Handle(vm.memory.alloc(MemObject::NativeStruct(
+            NativeStruct::HttpResponse(response),
+        ))))
+    })
+}
+
+fn resolve_response(vm: &Vm, _self: Option<Handle>) -> &HttpResponse {
+    let _self = _self.expect("response methods are bound to a response");
+    if let MemObject::NativeStruct(NativeStruct::HttpResponse(r)) = vm.memory.resolve(&_self) {
+        r
+    } else {
+        unreachable!()
+    }
+}
+
+// HttpResponse type methods
+pub fn text_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "text".to_string(),
+        vec![],
+        Engine::Native(text),
+    ))
+}
+
+pub fn text(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    _params: Vec<Value>,
+    _debug: bool,
+) -> Result<Value, VMError> {
+    let body = resolve_response(vm, _self).body.clone();
+    let text = decode_utf8(body).map_err(|e| error::throw(VMErrorType::Bytes(e), vm))?;
+    Ok(Value::Handle(put_string(vm, text)))
+}
+
+pub fn bytes_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "bytes".to_string(),
+        vec![],
+        Engine::Native(bytes),
+    ))
+}
+
+pub fn bytes(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    _params: Vec<Value>,
+    _debug: bool,
+) -> Result<Value, VMError> {
+    let body = resolve_response(vm, _self).body.clone();
+    Ok(Value::Handle(put_bytes(vm, body)))
+}
@@ -7,23 +7,25 @@ MOMENT TO MOVE FAST LET'S BUILD IT IN RUST
 use crate::{
     memory::MemObject,
     std::{
-        http::members::{get_def, get_obj},
+        http::members::{fetch_def, fetch_obj, get_def, get_obj},
         NativeModuleDef,
     },
 };
 
 mod members;
+pub mod types;
 
 pub fn generate_struct() -> (String, Vec<(String, MemObject)>) {
     let mut fields = vec![];
 
     fields.push(("get".to_string(), get_obj()));
+    fields.push(("fetch".to_string(), fetch_obj()));
 
     ("http".to_string(), fields)
 }
 
 pub fn generate_mod_def() -> NativeModuleDef {
-    let members = vec![get_def()];
+    let members = vec![get_def(), fetch_def()];
 
     NativeModuleDef {
         module: "http".to_string(),
@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+
+use crate::{
+    memory::MemObject,
+    std::{
+        heap_utils::put_string,
+        http::members::{bytes_obj, text_obj},
+    },
+    types::{
+        object::structs::StructLiteral,
+        raw::{u32::U32, RawValue},
+        Value,
+    },
+    vm::Vm,
+};
+
+// response of `http.fetch`, the body is kept as bytes until
+// it's read with text() or bytes()
+#[derive(Debug)]
+pub struct HttpResponse {
+    pub url: String,
+    pub status: u16,
+    pub body: Vec<u8>,
+    pub shape: StructLiteral,
+}
+
+impl HttpResponse {
+    pub fn new_initialized(
+        vm: &mut Vm,
+        url: String,
+        status: u16,
+        headers: Vec<(String, String)>,
+        body: Vec<u8>,
+    ) -> HttpResponse {
+        let mut fields = HashMap::new();
+        fields.insert(
+            "status".to_string(),
+            Value::RawValue(RawValue::U32(U32::new(status as u32))),
+        );
+        let url_handle = put_string(vm, url.clone());
+        fields.insert("url".to_string(), Value::Handle(url_handle));
+
+        let mut header_fields = HashMap::new();
+        for (name, value) in headers {
+            let value_handle = put_string(vm, value);
+            header_fields.insert(name, Value::Handle(value_handle));
+        }
+        let headers_handle = vm.memory.alloc(MemObject::StructLiteral(StructLiteral::new(
+            "HttpHeaders".to_string(),
+            header_fields,
+        )));
+        fields.insert("headers".to_string(), Value::Handle(headers_handle));
+
+        for (name, obj) in [("text", text_obj()), ("bytes", bytes_obj())] {
+            let handle = vm.memory.alloc(obj);
+            fields.insert(name.to_string(), Value::Handle(handle));
+        }
+
+        HttpResponse {
+            url,
+            status,
+            body,
+            shape: StructLiteral::new("HttpResponse".to_string(), fields),
+        }
+    }
+
+    pub fn to_string(&self) -> String {
+        format!("HttpResponse({} {})", self.status, self.url)
+    }
+
+    pub fn property_access(&self, property: &str) -> Option<Value> {
+        self.shape.property_access(property)
+    }
+}
@@ -1,21 +1,175 @@
-use std::io::{self, Write};
+use std::io::{self, BufRead, IsTerminal, Read};
 
 use crate::{
-    core::error::VMError,
+    core::error::{self, io_errors::IoError, VMError, VMErrorType},
     memory::{Handle, MemObject},
-    std::{heap_utils::put_string, NativeMember},
+    output::OutputStream,
+    std::{heap_utils::put_string, io::types::StdinLines, NativeMember},
     types::{
-        object::func::{Engine, Function},
+        object::{
+            func::{Engine, Function},
+            native_struct::NativeStruct,
+        },
+        raw::{bool::Bool, RawValue},
         Value,
     },
     vm::Vm,
 };
 
+fn io_error(err: IoError, vm: &Vm) -> VMError {
+    error::throw(VMErrorType::Io(err), vm)
+}
+
+// reads a line without its line ending, none on EOF
+fn next_line(vm: &Vm) -> Result<Option<String>, VMError> {
+    // flush what was printed before reading, so prompts show up
+    vm.flush_output();
+    let mut input = String::new();
+    let read = io::stdin()
+        .lock()
+        .read_line(&mut input)
+        .map_err(|e| io_error(IoError::ReadError(e.to_string()), vm))?;
+    if read == 0 {
+        return Ok(None);
+    }
+    if input.ends_with('\n') {
+        input.pop();
+        if input.ends_with('\r') {
+            input.pop();
+        }
+    }
+    Ok(Some(input))
+}
+
+fn line_value(vm: &mut Vm, line: Option<String>) -> Value {
+    match line {
+        Some(line) => Value::Handle(put_string(vm, line)),
+        None => Value::RawValue(RawValue::Nothing),
+    }
+}
+
+// the terminal echo is turned off while the line is typed
+#[cfg(unix)]
+fn next_secret_line(vm: &Vm) -> Result<Option<String>, VMError> {
+    use std::os::fd::AsRawFd;
+
+    let fd = io::stdin().as_raw_fd();
+    if !io::stdin().is_terminal() {
+        return next_line(vm);
+    }
+    let Some(_hidden) = HiddenEcho::new(fd) else {
+        return next_line(vm);
+    };
+    next_line(vm)
+}
+
+// signals that would end the process with the echo still off
+#[cfg(unix)]
+const RESTORE_SIGNALS: [libc::c_int; 3] = [libc::SIGINT, libc::SIGTERM, libc::SIGHUP];
+
+// terminal and signal state to put back, read from the signal
+// handler. only one secret line is read at a time, stdin is locked
+#[cfg(unix)]
+struct EchoState {
+    fd: libc::c_int,
+    original: libc::termios,
+    previous: [libc::sigaction; RESTORE_SIGNALS.len()],
+}
+
+#[cfg(unix)]
+static mut ECHO_STATE: Option<EchoState> = None;
+
+// hides the echo until dropped, or until one of RESTORE_SIGNALS
+// arrives, then the signal goes on to its previous handler
+#[cfg(unix)]
+struct HiddenEcho;
+
+#[cfg(unix)]
+impl HiddenEcho {
+    fn new(fd: libc::c_int) -> Option<HiddenEcho> {
+        use std::ptr::addr_of_mut;
+
+        unsafe {
+            let mut original = std::mem::zeroed::<libc::termios>();
+            if libc::tcgetattr(fd, &mut original) != 0 {
+                return None;
+            }
+
+            let mut action = std::mem::zeroed::<libc::sigaction>();
+            action.sa_sigaction = restore_echo as *const () as usize;
+            libc::sigemptyset(&mut action.sa_mask);
+            let state = addr_of_mut!(ECHO_STATE);
+            *state = Some(EchoState {
+                fd,
+                original,
+                previous: std::mem::zeroed(),
+            });
+            if let Some(state) = (*state).as_mut() {
+                for (signal, previous) in RESTORE_SIGNALS.iter().zip(state.previous.iter_mut()) {
+                    libc::sigaction(*signal, &action, previous);
+                }
+            }
+
+            let mut hidden = original;
+            hidden.c_lflag &= !libc::ECHO;
+            hidden.c_lflag |= libc::ECHONL;
+            libc::tcsetattr(fd, libc::TCSANOW, &hidden);
+        }
+        Some(HiddenEcho)
+    }
+}
+
+#[cfg(unix)]
+impl Drop for HiddenEcho {
+    fn drop(&mut self) {
+        use std::ptr::addr_of_mut;
+
+        unsafe {
+            if let Some(state) = (*addr_of_mut!(ECHO_STATE)).take() {
+                libc::tcsetattr(state.fd, libc::TCSANOW, &state.original);
+                for (signal, previous) in RESTORE_SIGNALS.iter().zip(state.previous.iter()) {
+                    libc::sigaction(*signal, previous, std::ptr::null_mut());
+                }
+            }
+        }
+    }
+}
+
+// only async-signal-safe calls in here
+#[cfg(unix)]
+extern "C" fn restore_echo(signal: libc::c_int) {
+    use std::ptr::addr_of;
+
+    unsafe {
+        if let Some(state) = (*addr_of!(ECHO_STATE)).as_ref() {
+            libc::tcsetattr(state.fd, libc::TCSANOW, &state.original);
+            if let Some(index) = RESTORE_SIGNALS.iter().position(|s| *s == signal) {
+                libc::sigaction(signal, &state.previous[index], std::ptr::null_mut());
+            }
+        }
+        libc::raise(signal);
+    }
+}
+
+#[cfg(not(unix))]
+fn next_secret_line(vm: &Vm) -> Result<Option<String>, VMError> {
+    next_line(vm)
+}
+
+// `\n` inside strings is kept literally, same as the print opcode
+// it's turned into a line break on output
+fn output_string(vm: &Vm, params: &[Value]) -> String {
+    params
+        .iter()
+        .map(|value| value.to_string(vm).replace("\\n", "\n"))
+        .collect()
+}
+
 // read_line
 pub fn read_line_def() -> NativeMember {
     NativeMember {
         name: "read_line".to_string(),
-        description: "Reads a line from standard input (stdin) and returns it as a string."
+        description: "Reads a line from standard input (stdin) and returns it as a string without the line ending. Returns nothing at the end of the input."
             .to_string(),
         params: Some(vec!["".to_string()]),
     }
@@ -32,19 +186,331 @@ pub fn read_line_obj() -> MemObject {
 pub fn read_line(
     vm: &mut Vm,
     _self: Option<Handle>,
-    params: Vec<Value>,
+    _params: Vec<Value>,
     debug: bool,
 ) -> Result<Value, VMError> {
-    // flush the previous stdout
-    io::stdout().flush().unwrap();
+    let line = next_line(vm)?;
+    if debug {
+        vm.trace(format!("IO.READ_LINE -> {:?}", line));
+    }
+    Ok(line_value(vm, line))
+}
+
+// read_all
+pub fn read_all_def() -> NativeMember {
+    NativeMember {
+        name: "read_all".to_string(),
+        description: "Reads the standard input until its end and returns it as a string."
+            .to_string(),
+        params: None,
+    }
+}
 
-    // stdin buffer
+pub fn read_all_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "read_all".to_string(),
+        vec![],
+        Engine::Native(read_all),
+    ))
+}
+
+pub fn read_all(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    _params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    vm.flush_output();
     let mut input = String::new();
     io::stdin()
-        .read_line(&mut input)
-        .expect("Failed to read line");
-    let input = input.trim(); // remove the new line
+        .lock()
+        .read_to_string(&mut input)
+        .map_err(|e| io_error(IoError::ReadError(e.to_string()), vm))?;
+    if debug {
+        vm.trace(format!("IO.READ_ALL -> {} bytes", input.len()));
+    }
+    Ok(Value::Handle(put_string(vm, input)))
+}
+
+// lines
+pub fn lines_def() -> NativeMember {
+    NativeMember {
+        name: "lines".to_string(),
+        description: "Iterator over the lines of the standard input. Its next() returns the next line or nothing at the end of the input."
+            .to_string(),
+        params: None,
+    }
+}
+
+pub fn lines_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "lines".to_string(),
+        vec![],
+        Engine::Native(lines),
+    ))
+}
+
+pub fn lines(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    _params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    if debug {
+        vm.trace("IO.LINES".to_string());
+    }
+    let lines = StdinLines::new_initialized(vm);
+    Ok(Value::Handle(vm.memory.alloc(MemObject::NativeStruct(
+        NativeStruct::StdinLines(lines),
+    ))))
+}
+
+// StdinLines type methods
+pub fn next_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "next".to_string(),
+        vec![],
+        Engine::Native(next),
+    ))
+}
+
+pub fn next(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    _params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let line = next_line(vm)?;
+    if debug {
+        vm.trace(format!("IO.LINES.NEXT -> {:?}", line));
+    }
+    Ok(line_value(vm, line))
+}
+
+// write
+pub fn write_def() -> NativeMember {
+    NativeMember {
+        name: "write".to_string(),
+        description: "Writes the values to the standard output without adding a new line."
+            .to_string(),
+        params: Some(vec!["...values".to_string()]),
+    }
+}
+
+pub fn write_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "write".to_string(),
+        vec![],
+        Engine::Native(write),
+    ))
+}
+
+pub fn write(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let output = output_string(vm, &params);
+    if debug {
+        vm.trace(format!("IO.WRITE <- {:?}", output));
+    }
+    vm.write_output(OutputStream::Stdout, &output);
+    vm.flush_output();
+    Ok(Value::RawValue(RawValue::Nothing))
+}
+
+// eprint
+pub fn eprint_def() -> NativeMember {
+    NativeMember {
+        name: "eprint".to_string(),
+        description: "Writes the values to the standard error followed by a new line.".to_string(),
+        params: Some(vec!["...values".to_string()]),
+    }
+}
+
+pub fn eprint_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "eprint".to_string(),
+        vec![],
+        Engine::Native(eprint),
+    ))
+}
+
+pub fn eprint(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let output = output_string(vm, &params);
+    if debug {
+        vm.trace(format!("IO.EPRINT <- {:?}", output));
+    }
+    vm.write_output(OutputStream::Stderr, &format!("{}\n", output));
+    Ok(Value::RawValue(RawValue::Nothing))
+}
+
+// is_tty
+pub fn is_tty_def() -> NativeMember {
+    NativeMember {
+        name: "is_tty".to_string(),
+        description: "Checks if the stream (stdin by default, stdout or stderr) is an interactive terminal instead of a pipe or a file."
+            .to_string(),
+        params: Some(vec!["stream(string, optional)".to_string()]),
+    }
+}
+
+pub fn is_tty_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "is_tty".to_string(),
+        vec![],
+        Engine::Native(is_tty),
+    ))
+}
+
+pub fn is_tty(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let stream = match params.first() {
+        Some(stream) => stream.as_string_obj(vm)?,
+        None => "stdin".to_string(),
+    };
+    let tty = match stream.as_str() {
+        "stdin" => io::stdin().is_terminal(),
+        "stdout" => io::stdout().is_terminal(),
+        "stderr" => io::stderr().is_terminal(),
+        _ => return Err(io_error(IoError::InvalidStream(stream), vm)),
+    };
+    if debug {
+        vm.trace(format!("IO.IS_TTY <- {} -> {}", stream, tty));
+    }
+    Ok(Value::RawValue(RawValue::Bool(Bool::new(tty))))
+}
+
+// options struct: { default, secret }
+struct PromptOptions {
+    default: Option<String>,
+    secret: bool,
+}
+
+fn prompt_options(vm: &Vm, value: Option<&Value>) -> Result<PromptOptions, VMError> {
+    let mut options = PromptOptions {
+        default: None,
+        secret: false,
+    };
+    let Some(value) = value else {
+        return Ok(options);
+    };
+    if let Value::RawValue(RawValue::Nothing) = value {
+        return Ok(options);
+    }
+    let fields = value.as_struct_obj(vm)?;
+    for (name, field) in &fields.fields {
+        if let Value::RawValue(RawValue::Nothing) = field {
+            continue;
+        }
+        match name.as_str() {
+            "default" => options.default = Some(field.as_string_obj(vm)?),
+            "secret" => options.secret = field.as_bool(vm)?,
+            _ => {}
+        }
+    }
+    Ok(options)
+}
+
+// prompt
+pub fn prompt_def() -> NativeMember {
+    NativeMember {
+        name: "prompt".to_string(),
+        description: "Shows the message and reads the answer of the user. An empty answer gives the default, secret hides the typed input. Returns nothing at the end of the input when there is no default."
+            .to_string(),
+        params: Some(vec![
+            "message(string)".to_string(),
+            "options({ default: string, secret: bool }, optional)".to_string(),
+        ]),
+    }
+}
+
+pub fn prompt_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "prompt".to_string(),
+        vec!["message".to_string()],
+        Engine::Native(prompt),
+    ))
+}
+
+pub fn prompt(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let mut message = params[0].as_string_obj(vm)?;
+    let options = prompt_options(vm, params.get(1))?;
+    if let (Some(default), false) = (&options.default, options.secret) {
+        message = format!("{} ({})", message, default);
+    }
+    if debug {
+        vm.trace(format!("IO.PROMPT <- {}", message));
+    }
+
+    vm.write_output(
+        OutputStream::Stdout,
+        &format!("{} ", message.replace("\\n", "\n")),
+    );
+    let line = if options.secret {
+        next_secret_line(vm)?
+    } else {
+        next_line(vm)?
+    };
+    let answer = match line {
+        Some(line) if line.is_empty() => options.default.or(Some(line)),
+        None => options.default,
+        line => line,
+    };
+    Ok(line_value(vm, answer))
+}
+
+// confirm
+pub fn confirm_def() -> NativeMember {
+    NativeMember {
+        name: "confirm".to_string(),
+        description: "Asks a yes/no question. Returns true when the user answers y or yes, false otherwise or at the end of the input."
+            .to_string(),
+        params: Some(vec!["message(string)".to_string()]),
+    }
+}
+
+pub fn confirm_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "confirm".to_string(),
+        vec!["message".to_string()],
+        Engine::Native(confirm),
+    ))
+}
+
+pub fn confirm(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let message = params[0].as_string_obj(vm)?;
+    if debug {
+        vm.trace(format!("IO.CONFIRM <- {}", message));
+    }
 
-    let stdin_handle = put_string(vm, input.to_string());
-    Ok(Value::Handle(stdin_handle))
+    vm.write_output(
+        OutputStream::Stdout,
+        &format!("{} [y/N] ", message.replace("\\n", "\n")),
+    );
+    let confirmed = match next_line(vm)? {
+        Some(answer) => matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"),
+        None => false,
+    };
+    Ok(Value::RawValue(RawValue::Bool(Bool::new(confirmed))))
 }
@@ -1,9 +1,14 @@
 mod members;
+pub mod types;
 
 use crate::{
     memory::MemObject,
     std::{
-        io::members::{read_line_def, read_line_obj},
+        io::members::{
+            confirm_def, confirm_obj, eprint_def, eprint_obj, is_tty_def, is_tty_obj, lines_def,
+            lines_obj, prompt_def, prompt_obj, read_all_def, read_all_obj, read_line_def,
+            read_line_obj, write_def, write_obj,
+        },
         NativeModuleDef,
     },
 };
@@ -12,12 +17,28 @@ pub fn generate_struct() -> (String, Vec<(String, MemObject)>) {
     let mut fields = vec![];
 
     fields.push(("read_line".to_string(), read_line_obj()));
+    fields.push(("read_all".to_string(), read_all_obj()));
+    fields.push(("lines".to_string(), lines_obj()));
+    fields.push(("write".to_string(), write_obj()));
+    fields.push(("eprint".to_string(), eprint_obj()));
+    fields.push(("is_tty".to_string(), is_tty_obj()));
+    fields.push(("prompt".to_string(), prompt_obj()));
+    fields.push(("confirm".to_string(), confirm_obj()));
 
     ("io".to_string(), fields)
 }
 
 pub fn generate_mod_def() -> NativeModuleDef {
-    let members = vec![read_line_def()];
+    let members = vec![
+        read_line_def(),
+        read_all_def(),
+        lines_def(),
+        write_def(),
+        eprint_def(),
+        is_tty_def(),
+        prompt_def(),
+        confirm_def(),
+    ];
 
     NativeModuleDef {
         module: "io".to_string(),
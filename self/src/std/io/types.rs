@@ -0,0 +1,34 @@
+use std::collections::HashMap;
+
+use crate::{
+    std::io::members::next_obj,
+    types::{object::structs::StructLiteral, Value},
+    vm::Vm,
+};
+
+// iterator returned by `io.lines`, stdin is shared by the whole
+// process so it doesn't hold any state besides its methods
+#[derive(Debug)]
+pub struct StdinLines {
+    pub shape: StructLiteral,
+}
+
+impl StdinLines {
+    pub fn new_initialized(vm: &mut Vm) -> StdinLines {
+        let mut fields = HashMap::new();
+        let next_handle = vm.memory.alloc(next_obj());
+        fields.insert("next".to_string(), Value::Handle(next_handle));
+
+        StdinLines {
+            shape: StructLiteral::new("StdinLines".to_string(), fields),
+        }
+    }
+
+    pub fn to_string(&self) -> String {
+        "StdinLines".to_string()
+    }
+
+    pub fn property_access(&self, property: &str) -> Option<Value> {
+        self.shape.property_access(property)
+    }
+}
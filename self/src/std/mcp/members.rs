@@ -4,9 +4,17 @@
 */
 
 use crate::{
-    core::error::{self, type_errors::TypeError, VMError, VMErrorType},
+    core::error::{self, mcp_errors::McpError, type_errors::TypeError, VMError, VMErrorType},
     memory::{Handle, MemObject},
-    std::mcp::types::{McpClient, McpTool},
+    output::StderrOutput,
+    std::{
+        heap_utils::put_string,
+        mcp::{
+            server::{McpServeTransport, McpServedTool, McpServer},
+            types::{McpClient, McpClientRef, McpTool, McpTransport},
+        },
+        utils::{json_to_value, value_to_json},
+    },
     types::{
         object::{
             func::{Engine, Function},
@@ -20,16 +28,27 @@ use crate::{
 };
 use futures::future::BoxFuture;
 use rmcp::{
-    model::{ClientCapabilities, ClientInfo, Implementation},
-    transport::StreamableHttpClientTransport,
+    model::{
+        CallToolRequestParam, ClientCapabilities, ClientInfo, GetPromptRequestParam,
+        Implementation, JsonObject, ReadResourceRequestParam, ResourceContents,
+    },
+    service::ServiceError,
+    transport::{
+        stdio,
+        streamable_http_server::{session::local::LocalSessionManager, StreamableHttpService},
+        SseClientTransport, StreamableHttpClientTransport, TokioChildProcess,
+    },
     ServiceExt,
 };
+use serde_json::Value as JsonValue;
+use tokio::{net::TcpListener, process::Command};
 
 // init an mcp connection
 pub fn init_obj() -> MemObject {
     MemObject::Function(Function::new(
         "init".to_string(),
-        vec!["url".to_string()],
+        // (url, config) or just (config). only the first is required
+        vec!["config".to_string()],
         Engine::NativeAsync(init),
     ))
 }
@@ -41,37 +60,71 @@ pub fn init(
     debug: bool,
 ) -> BoxFuture<'_, Result<Value, VMError>> {
     Box::pin(async move {
-        if params.len() < 2 {
+        if params.len() < 1 {
             return Err(error::throw(
                 VMErrorType::TypeError(TypeError::InvalidArgsCount {
-                    expected: 2,
+                    expected: 1,
                     received: params.len() as u32,
                 }),
                 vm,
             ));
         }
 
-        let url = &params[0].as_string_obj(vm)?;
-        let config = &params[1].as_struct_obj(vm)?;
+        // mcp.init(url, config) or mcp.init(config)
+        let (url, config) = match value_to_json(vm, &params[0]) {
+            JsonValue::String(url) => {
+                let config = params
+                    .get(1)
+                    .map(|c| value_to_json(vm, c))
+                    .unwrap_or(JsonValue::Null);
+                (Some(url), config)
+            }
+            config => (None, config),
+        };
+        let transport = McpTransport::from_config(url, &config)
+            .map_err(|e| error::throw(VMErrorType::Mcp(McpError::InitError(e)), vm))?;
 
         if debug {
-            println!("MCP_INIT -> {}", url);
+            vm.trace(format!("MCP_INIT -> {}", transport.to_string()));
         }
 
-        let transport = StreamableHttpClientTransport::from_uri(url.to_string());
         let client_info = ClientInfo {
             protocol_version: Default::default(),
             capabilities: ClientCapabilities::default(),
             client_info: Implementation::default(),
         };
 
-        let client = client_info
-            .serve(transport)
-            .await
-            //.map_err(|e| VMError::new(&format!("mcp: {e}")))?;
-            .map_err(|e| error::throw(VMErrorType::ExportInvalidMemberType, vm))?;
+        let client = match &transport {
+            McpTransport::Http(url) => client_info
+                .serve(StreamableHttpClientTransport::from_uri(url.clone()))
+                .await
+                .map_err(|e| e.to_string()),
+            McpTransport::Sse(url) => match SseClientTransport::start(url.clone()).await {
+                Ok(t) => client_info.serve(t).await.map_err(|e| e.to_string()),
+                Err(e) => Err(e.to_string()),
+            },
+            McpTransport::Stdio { command, args, env } => {
+                let mut cmd = Command::new(command);
+                cmd.args(args).envs(env.clone());
+                match TokioChildProcess::new(cmd) {
+                    Ok(t) => client_info.serve(t).await.map_err(|e| e.to_string()),
+                    Err(e) => Err(e.to_string()),
+                }
+            }
+        };
+        let client = client.map_err(|e| {
+            error::throw(
+                VMErrorType::Mcp(McpError::InitError(format!(
+                    "{}: {}",
+                    transport.to_string(),
+                    e
+                ))),
+                vm,
+            )
+        })?;
 
-        let mcp_client = McpClient::initizialize_new(url.to_string(), client, vm);
+        let mcp_client = McpClient::initizialize_new(transport.to_string(), client, vm);
+        vm.register_mcp_client(mcp_client.client.clone());
         let handle = vm
             .memory
             .alloc(MemObject::NativeStruct(NativeStruct::McpClient(mcp_client)));
@@ -79,6 +132,129 @@ pub fn init(
     })
 }
 
+// serve ego functions as mcp tools
+pub fn serve_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "serve".to_string(),
+        // (config, tools, descriptions). descriptions are optional
+        vec!["config".to_string(), "tools".to_string()],
+        Engine::NativeAsync(serve),
+    ))
+}
+
+// blocks until the client disconnects (stdio) or the server stops
+// (http). meanwhile the vm keeps handling its events, tool calls
+// included
+pub fn serve(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> BoxFuture<'_, Result<Value, VMError>> {
+    Box::pin(async move {
+        if params.len() < 2 {
+            return Err(error::throw(
+                VMErrorType::TypeError(TypeError::InvalidArgsCount {
+                    expected: 2,
+                    received: params.len() as u32,
+                }),
+                vm,
+            ));
+        }
+
+        let config = value_to_json(vm, &params[0]);
+        let transport = McpServeTransport::from_config(&config)
+            .map_err(|e| error::throw(VMErrorType::Mcp(McpError::ServeError(e)), vm))?;
+        let tools = served_tools(vm, &params[1], params.get(2))?;
+
+        let field = |name: &str, default: &str| {
+            config
+                .get(name)
+                .and_then(|v| v.as_str())
+                .unwrap_or(default)
+                .to_string()
+        };
+        let server = McpServer::new(
+            field("name", "ego"),
+            field("version", env!("CARGO_PKG_VERSION")),
+            tools,
+            vm.get_vm_notifier(),
+        );
+
+        // stdout carries the json-rpc frames, what the tools print
+        // and the traces go to stderr meanwhile
+        let previous_output = match transport {
+            McpServeTransport::Stdio => Some(vm.swap_output(Box::new(StderrOutput))),
+            McpServeTransport::Http { .. } => None,
+        };
+        let result = run_server(vm, &transport, server, debug).await;
+        if let Some(output) = previous_output {
+            vm.swap_output(output);
+        }
+        result?;
+
+        Ok(Value::RawValue(RawValue::Nothing))
+    })
+}
+
+async fn run_server(
+    vm: &mut Vm,
+    transport: &McpServeTransport,
+    server: McpServer,
+    debug: bool,
+) -> Result<(), VMError> {
+    if debug {
+        vm.trace(format!("MCP.SERVE -> {}", transport.to_string()));
+    }
+
+    let serve_error = |e: String, vm: &Vm| {
+        error::throw(
+            VMErrorType::Mcp(McpError::ServeError(format!(
+                "{}: {}",
+                transport.to_string(),
+                e
+            ))),
+            vm,
+        )
+    };
+
+    let running: BoxFuture<'static, Result<(), String>> = match transport {
+        McpServeTransport::Stdio => {
+            let service = server
+                .serve(stdio())
+                .await
+                .map_err(|e| serve_error(e.to_string(), vm))?;
+            Box::pin(async move {
+                service
+                    .waiting()
+                    .await
+                    .map(|_| ())
+                    .map_err(|e| e.to_string())
+            })
+        }
+        McpServeTransport::Http { address, path } => {
+            let listener = TcpListener::bind(address)
+                .await
+                .map_err(|e| serve_error(e.to_string(), vm))?;
+            let service = StreamableHttpService::new(
+                move || Ok(server.clone()),
+                LocalSessionManager::default().into(),
+                Default::default(),
+            );
+            let router = axum::Router::new().route_service(path, service);
+            Box::pin(async move {
+                axum::serve(listener, router)
+                    .await
+                    .map_err(|e| e.to_string())
+            })
+        }
+    };
+
+    vm.with_events(running)
+        .await?
+        .map_err(|e| serve_error(e, vm))
+}
+
 // list mcp server tools
 pub fn list_tools_obj() -> MemObject {
     MemObject::Function(Function::new(
@@ -92,28 +268,39 @@ pub fn list_tools(
     vm: &mut Vm,
     _self: Option<Handle>,
     _params: Vec<Value>,
-    _debug: bool,
+    debug: bool,
 ) -> BoxFuture<Result<Value, VMError>> {
-    let client_arc = match _self {
-        Some(h) => match vm.memory.resolve(&h) {
-            MemObject::NativeStruct(NativeStruct::McpClient(mc)) => mc.client.clone(),
-            _ => {
-                unreachable!();
-            }
-        },
-        None => unreachable!(),
-    };
+    let (url, client_arc) = get_client(vm, &_self);
 
     Box::pin(async move {
-        let guard = client_arc.lock().await;
-        let client = guard.as_ref().expect("mcp client it's not connected");
+        let tools = {
+            let guard = client_arc.lock().await;
+            let Some(client) = guard.as_ref() else {
+                return Err(error::throw(
+                    VMErrorType::Mcp(McpError::NotConnected(url)),
+                    vm,
+                ));
+            };
+            client.list_all_tools().await
+        };
+        let tools = tools.map_err(|e| request_error(e, vm))?;
 
-        let tools_obj = client.list_tools(None).await.expect("panic");
-        let mut tools_refs = Vec::with_capacity(tools_obj.tools.len());
+        if debug {
+            vm.trace(format!("MCP.LIST_TOOLS -> {} tools", tools.len()));
+        }
 
-        for t in &tools_obj.tools {
+        let mut tools_refs = Vec::with_capacity(tools.len());
+        for t in &tools {
             let desc = t.description.as_ref().map(|d| d.to_string());
-            let mtool = McpTool::new(t.name.to_string(), desc, vm);
+            let input_schema = JsonValue::Object(t.input_schema.as_ref().clone());
+            let mtool = McpTool::new(
+                t.name.to_string(),
+                desc,
+                input_schema,
+                url.clone(),
+                client_arc.clone(),
+                vm,
+            );
 
             let handle = vm
                 .memory
@@ -127,6 +314,266 @@ pub fn list_tools(
     })
 }
 
+// call a tool by name: client.call_tool(name, args)
+pub fn call_tool_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "call_tool".to_string(),
+        vec!["name".to_string()], // args are optional
+        Engine::NativeAsync(call_tool),
+    ))
+}
+
+pub fn call_tool(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> BoxFuture<Result<Value, VMError>> {
+    let (url, client_arc) = get_client(vm, &_self);
+
+    Box::pin(async move {
+        if params.len() < 1 {
+            return Err(error::throw(
+                VMErrorType::TypeError(TypeError::InvalidArgsCount {
+                    expected: 1,
+                    received: params.len() as u32,
+                }),
+                vm,
+            ));
+        }
+        let name = params[0].as_string_obj(vm)?;
+        let args = json_args(params.get(1), vm)?;
+
+        run_tool(vm, url, client_arc, name, args, debug).await
+    })
+}
+
+// call the tool itself: tool.call(args)
+pub fn tool_call_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "call".to_string(),
+        vec![], // args are optional
+        Engine::NativeAsync(tool_call),
+    ))
+}
+
+pub fn tool_call(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> BoxFuture<Result<Value, VMError>> {
+    let (name, url, client_arc) = match _self {
+        Some(h) => match vm.memory.resolve(&h) {
+            MemObject::NativeStruct(NativeStruct::McpTool(t)) => {
+                (t.name.clone(), t.url.clone(), t.client.clone())
+            }
+            _ => unreachable!(),
+        },
+        None => unreachable!(),
+    };
+
+    Box::pin(async move {
+        let args = json_args(params.get(0), vm)?;
+        run_tool(vm, url, client_arc, name, args, debug).await
+    })
+}
+
+// list the resources exposed by the server
+pub fn list_resources_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "list_resources".to_string(),
+        vec![],
+        Engine::NativeAsync(list_resources),
+    ))
+}
+
+pub fn list_resources(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    _params: Vec<Value>,
+    debug: bool,
+) -> BoxFuture<Result<Value, VMError>> {
+    let (url, client_arc) = get_client(vm, &_self);
+
+    Box::pin(async move {
+        let resources = {
+            let guard = client_arc.lock().await;
+            let Some(client) = guard.as_ref() else {
+                return Err(error::throw(
+                    VMErrorType::Mcp(McpError::NotConnected(url)),
+                    vm,
+                ));
+            };
+            client.list_all_resources().await
+        };
+        let resources = resources.map_err(|e| request_error(e, vm))?;
+
+        if debug {
+            vm.trace(format!(
+                "MCP.LIST_RESOURCES -> {} resources",
+                resources.len()
+            ));
+        }
+
+        let json = serde_json::to_value(&resources).unwrap_or(JsonValue::Null);
+        Ok(json_to_value(vm, &json))
+    })
+}
+
+// read a resource. text contents are returned as a string, blobs
+// as their base64 encoding
+pub fn read_resource_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "read_resource".to_string(),
+        vec!["uri".to_string()],
+        Engine::NativeAsync(read_resource),
+    ))
+}
+
+pub fn read_resource(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> BoxFuture<Result<Value, VMError>> {
+    let (url, client_arc) = get_client(vm, &_self);
+
+    Box::pin(async move {
+        if params.len() < 1 {
+            return Err(error::throw(
+                VMErrorType::TypeError(TypeError::InvalidArgsCount {
+                    expected: 1,
+                    received: params.len() as u32,
+                }),
+                vm,
+            ));
+        }
+        let uri = params[0].as_string_obj(vm)?;
+
+        if debug {
+            vm.trace(format!("MCP.READ_RESOURCE <- {}", uri));
+        }
+
+        let result = {
+            let guard = client_arc.lock().await;
+            let Some(client) = guard.as_ref() else {
+                return Err(error::throw(
+                    VMErrorType::Mcp(McpError::NotConnected(url)),
+                    vm,
+                ));
+            };
+            client
+                .read_resource(ReadResourceRequestParam { uri: uri.clone() })
+                .await
+        };
+        let result = result.map_err(|e| request_error(e, vm))?;
+
+        let content: Vec<String> = result
+            .contents
+            .into_iter()
+            .map(|c| match c {
+                ResourceContents::TextResourceContents { text, .. } => text,
+                ResourceContents::BlobResourceContents { blob, .. } => blob,
+            })
+            .collect();
+
+        Ok(Value::Handle(put_string(vm, content.join("\n"))))
+    })
+}
+
+// list the prompts exposed by the server
+pub fn list_prompts_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "list_prompts".to_string(),
+        vec![],
+        Engine::NativeAsync(list_prompts),
+    ))
+}
+
+pub fn list_prompts(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    _params: Vec<Value>,
+    debug: bool,
+) -> BoxFuture<Result<Value, VMError>> {
+    let (url, client_arc) = get_client(vm, &_self);
+
+    Box::pin(async move {
+        let prompts = {
+            let guard = client_arc.lock().await;
+            let Some(client) = guard.as_ref() else {
+                return Err(error::throw(
+                    VMErrorType::Mcp(McpError::NotConnected(url)),
+                    vm,
+                ));
+            };
+            client.list_all_prompts().await
+        };
+        let prompts = prompts.map_err(|e| request_error(e, vm))?;
+
+        if debug {
+            vm.trace(format!("MCP.LIST_PROMPTS -> {} prompts", prompts.len()));
+        }
+
+        let json = serde_json::to_value(&prompts).unwrap_or(JsonValue::Null);
+        Ok(json_to_value(vm, &json))
+    })
+}
+
+// get a prompt filled with the given arguments
+pub fn get_prompt_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "get_prompt".to_string(),
+        vec!["name".to_string()], // args are optional
+        Engine::NativeAsync(get_prompt),
+    ))
+}
+
+pub fn get_prompt(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> BoxFuture<Result<Value, VMError>> {
+    let (url, client_arc) = get_client(vm, &_self);
+
+    Box::pin(async move {
+        if params.len() < 1 {
+            return Err(error::throw(
+                VMErrorType::TypeError(TypeError::InvalidArgsCount {
+                    expected: 1,
+                    received: params.len() as u32,
+                }),
+                vm,
+            ));
+        }
+        let name = params[0].as_string_obj(vm)?;
+        let arguments = json_args(params.get(1), vm)?;
+
+        if debug {
+            vm.trace(format!("MCP.GET_PROMPT <- {}", name));
+        }
+
+        let result = {
+            let guard = client_arc.lock().await;
+            let Some(client) = guard.as_ref() else {
+                return Err(error::throw(
+                    VMErrorType::Mcp(McpError::NotConnected(url)),
+                    vm,
+                ));
+            };
+            client
+                .get_prompt(GetPromptRequestParam { name, arguments })
+                .await
+        };
+        let result = result.map_err(|e| request_error(e, vm))?;
+
+        let json = serde_json::to_value(&result).unwrap_or(JsonValue::Null);
+        Ok(json_to_value(vm, &json))
+    })
+}
+
 // shutdown mcp connection
 pub fn shutdown_obj() -> MemObject {
     MemObject::Function(Function::new(
@@ -161,3 +608,394 @@ pub fn shutdown(
         Ok(Value::RawValue(RawValue::Nothing))
     })
 }
+
+// utils functions
+fn get_client(vm: &Vm, this: &Option<Handle>) -> (String, McpClientRef) {
+    match this {
+        Some(h) => match vm.memory.resolve(h) {
+            MemObject::NativeStruct(NativeStruct::McpClient(mc)) => {
+                (mc.url.clone(), mc.client.clone())
+            }
+            _ => unreachable!(),
+        },
+        None => unreachable!(),
+    }
+}
+
+fn request_error(err: ServiceError, vm: &Vm) -> VMError {
+    error::throw(
+        VMErrorType::Mcp(McpError::RequestError(err.to_string())),
+        vm,
+    )
+}
+
+// tool and prompt arguments must be a struct (or nothing)
+fn json_args(value: Option<&Value>, vm: &Vm) -> Result<Option<JsonObject>, VMError> {
+    let Some(value) = value else {
+        return Ok(None);
+    };
+    match value_to_json(vm, value) {
+        JsonValue::Null => Ok(None),
+        JsonValue::Object(map) => Ok(Some(map)),
+        _ => Err(error::throw(
+            VMErrorType::TypeError(TypeError::InvalidTypeUnwrap {
+                expected: "struct".to_string(),
+                received: value.get_resolved_type(vm),
+            }),
+            vm,
+        )),
+    }
+}
+
+// tools are ego functions, named after their identifier. the
+// optional descriptions struct maps tool names to their description
+fn served_tools(
+    vm: &Vm,
+    tools: &Value,
+    descriptions: Option<&Value>,
+) -> Result<Vec<McpServedTool>, VMError> {
+    let descriptions = descriptions
+        .map(|d| value_to_json(vm, d))
+        .unwrap_or(JsonValue::Null);
+
+    let elements = match tools.as_mem_obj(vm)? {
+        MemObject::Vector(v) => v.elements.clone(),
+        MemObject::Function(_) => vec![tools.clone()],
+        _ => {
+            return Err(error::throw(
+                VMErrorType::TypeError(TypeError::InvalidTypeUnwrap {
+                    expected: "vector of functions".to_string(),
+                    received: tools.get_resolved_type(vm),
+                }),
+                vm,
+            ))
+        }
+    };
+
+    let mut served = vec![];
+    for element in elements {
+        let function = element.as_function_obj(vm)?.clone();
+        let description = descriptions
+            .get(&function.identifier)
+            .and_then(|d| d.as_str())
+            .map(String::from);
+        served.push(McpServedTool {
+            name: function.identifier.clone(),
+            description,
+            function,
+        });
+    }
+    Ok(served)
+}
+
+pub async fn run_tool(
+    vm: &mut Vm,
+    url: String,
+    client_arc: McpClientRef,
+    name: String,
+    arguments: Option<JsonObject>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    if debug {
+        vm.trace(format!(
+            "MCP.CALL_TOOL <- {}({})",
+            name,
+            arguments
+                .as_ref()
+                .map(|a| JsonValue::Object(a.clone()).to_string())
+                .unwrap_or_default()
+        ));
+    }
+
+    let result = {
+        let guard = client_arc.lock().await;
+        let Some(client) = guard.as_ref() else {
+            return Err(error::throw(
+                VMErrorType::Mcp(McpError::NotConnected(url)),
+                vm,
+            ));
+        };
+        client
+            .call_tool(CallToolRequestParam {
+                name: name.clone().into(),
+                arguments,
+            })
+            .await
+    };
+    let result = result.map_err(|e| request_error(e, vm))?;
+
+    let text: Vec<String> = result
+        .content
+        .iter()
+        .filter_map(|c| c.as_text().map(|t| t.text.clone()))
+        .collect();
+
+    if result.is_error.unwrap_or(false) {
+        return Err(error::throw(
+            VMErrorType::Mcp(McpError::ToolError {
+                tool: name,
+                message: text.join("\n"),
+            }),
+            vm,
+        ));
+    }
+
+    if debug {
+        vm.trace(format!("MCP.CALL_TOOL -> {}", text.join("\n")));
+    }
+
+    // structured output when the tool has one, otherwise its text
+    // content. other contents (images, resources) are kept as json
+    if let Some(structured) = &result.structured_content {
+        return Ok(json_to_value(vm, structured));
+    }
+    if text.len() == result.content.len() {
+        return Ok(Value::Handle(put_string(vm, text.join("\n"))));
+    }
+    let json = serde_json::to_value(&result.content).unwrap_or(JsonValue::Null);
+    Ok(json_to_value(vm, &json))
+}
+
+#[cfg(test)]
+mod tests {
+    use rmcp::{
+        handler::server::ServerHandler,
+        model::{
+            AnnotateAble, CallToolResult, ClientInfo, Content, ErrorData, GetPromptRequestParam,
+            GetPromptResult, ListPromptsResult, ListResourcesResult, ListToolsResult,
+            PaginatedRequestParam, Prompt, PromptMessage, PromptMessageRole, RawResource,
+            ReadResourceRequestParam, ReadResourceResult, ResourceContents, ServerCapabilities,
+            ServerInfo, Tool,
+        },
+        service::RequestContext,
+        RoleServer, ServiceExt,
+    };
+    use serde_json::{json, Value as JsonValue};
+    use std::sync::Arc;
+
+    use super::{
+        call_tool, get_prompt, list_prompts, list_resources, list_tools, read_resource, tool_call,
+    };
+    use crate::{
+        memory::{Handle, MemObject},
+        std::{
+            heap_utils::put_string,
+            mcp::types::McpClient,
+            utils::{json_to_value, value_to_json},
+        },
+        types::{object::native_struct::NativeStruct, Value},
+        vm::Vm,
+    };
+
+    // echo returns its arguments as structured content, greet
+    // answers with text
+    #[derive(Clone)]
+    struct TestServer;
+
+    impl ServerHandler for TestServer {
+        fn get_info(&self) -> ServerInfo {
+            ServerInfo {
+                capabilities: ServerCapabilities::builder()
+                    .enable_tools()
+                    .enable_resources()
+                    .enable_prompts()
+                    .build(),
+                ..ServerInfo::default()
+            }
+        }
+
+        async fn list_tools(
+            &self,
+            _request: Option<PaginatedRequestParam>,
+            _context: RequestContext<RoleServer>,
+        ) -> Result<ListToolsResult, ErrorData> {
+            let schema = Arc::new(serde_json::Map::new());
+            Ok(ListToolsResult::with_all_items(vec![
+                Tool::new("echo", "returns its arguments", schema.clone()),
+                Tool::new("greet", "says hello", schema),
+            ]))
+        }
+
+        async fn call_tool(
+            &self,
+            request: rmcp::model::CallToolRequestParam,
+            _context: RequestContext<RoleServer>,
+        ) -> Result<CallToolResult, ErrorData> {
+            let arguments = JsonValue::Object(request.arguments.unwrap_or_default());
+            match request.name.as_ref() {
+                "echo" => Ok(CallToolResult::structured(arguments)),
+                "greet" => Ok(CallToolResult::success(vec![Content::text(format!(
+                    "hello {}",
+                    arguments["name"].as_str().unwrap_or("nobody")
+                ))])),
+                _ => Ok(CallToolResult::error(vec![Content::text("unknown tool")])),
+            }
+        }
+
+        async fn list_resources(
+            &self,
+            _request: Option<PaginatedRequestParam>,
+            _context: RequestContext<RoleServer>,
+        ) -> Result<ListResourcesResult, ErrorData> {
+            Ok(ListResourcesResult::with_all_items(vec![RawResource::new(
+                "mem://notes",
+                "notes",
+            )
+            .no_annotation()]))
+        }
+
+        async fn read_resource(
+            &self,
+            request: ReadResourceRequestParam,
+            _context: RequestContext<RoleServer>,
+        ) -> Result<ReadResourceResult, ErrorData> {
+            Ok(ReadResourceResult {
+                contents: vec![
+                    ResourceContents::text("first", request.uri.clone()),
+                    ResourceContents::text("second", request.uri),
+                ],
+            })
+        }
+
+        async fn list_prompts(
+            &self,
+            _request: Option<PaginatedRequestParam>,
+            _context: RequestContext<RoleServer>,
+        ) -> Result<ListPromptsResult, ErrorData> {
+            Ok(ListPromptsResult::with_all_items(vec![Prompt::new(
+                "review",
+                Some("review some code"),
+                None,
+            )]))
+        }
+
+        async fn get_prompt(
+            &self,
+            request: GetPromptRequestParam,
+            _context: RequestContext<RoleServer>,
+        ) -> Result<GetPromptResult, ErrorData> {
+            let language = request
+                .arguments
+                .and_then(|a| a.get("language").cloned())
+                .unwrap_or(JsonValue::Null);
+            Ok(GetPromptResult {
+                description: None,
+                messages: vec![PromptMessage::new_text(
+                    PromptMessageRole::User,
+                    format!("review this {}", language.as_str().unwrap_or("code")),
+                )],
+            })
+        }
+    }
+
+    // a vm with a client connected to the test server through an
+    // in-memory pipe
+    async fn connect() -> (Vm, Handle) {
+        let (server_io, client_io) = tokio::io::duplex(64 * 1024);
+        tokio::spawn(async move {
+            if let Ok(service) = TestServer.serve(server_io).await {
+                let _ = service.waiting().await;
+            }
+        });
+        let client = ClientInfo::default().serve(client_io).await.unwrap();
+
+        let mut vm = Vm::new(vec![]);
+        let client = McpClient::initizialize_new("test".to_string(), client, &mut vm);
+        let handle = vm
+            .memory
+            .alloc(MemObject::NativeStruct(NativeStruct::McpClient(client)));
+        (vm, handle)
+    }
+
+    fn string(vm: &mut Vm, text: &str) -> Value {
+        Value::Handle(put_string(vm, text.to_string()))
+    }
+
+    #[tokio::test]
+    async fn calls_tools_by_name() {
+        let (mut vm, client) = connect().await;
+        let arguments =
+            json!({"text": "hi", "count": 2, "tags": ["a", "b"], "nested": {"ok": true}});
+
+        let name = string(&mut vm, "echo");
+        let args = json_to_value(&mut vm, &arguments);
+        let result = call_tool(&mut vm, Some(client.clone()), vec![name, args], false)
+            .await
+            .unwrap();
+        assert_eq!(value_to_json(&vm, &result), arguments);
+
+        let name = string(&mut vm, "greet");
+        let args = json_to_value(&mut vm, &json!({"name": "ego"}));
+        let result = call_tool(&mut vm, Some(client.clone()), vec![name, args], false)
+            .await
+            .unwrap();
+        assert_eq!(value_to_json(&vm, &result), json!("hello ego"));
+
+        let name = string(&mut vm, "missing");
+        assert!(call_tool(&mut vm, Some(client), vec![name], false)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn calls_listed_tools() {
+        let (mut vm, client) = connect().await;
+
+        let tools = list_tools(&mut vm, Some(client), vec![], false)
+            .await
+            .unwrap();
+        let MemObject::Vector(tools) = tools.as_mem_obj(&vm).unwrap() else {
+            panic!("expected a vector of tools");
+        };
+        let echo = tools.elements[0].as_handle().unwrap();
+
+        let arguments = json!({"list": [1, 2.5, "three", null]});
+        let args = json_to_value(&mut vm, &arguments);
+        let result = tool_call(&mut vm, Some(echo), vec![args], false)
+            .await
+            .unwrap();
+        assert_eq!(value_to_json(&vm, &result), arguments);
+    }
+
+    #[tokio::test]
+    async fn lists_and_reads_resources() {
+        let (mut vm, client) = connect().await;
+
+        let resources = list_resources(&mut vm, Some(client.clone()), vec![], false)
+            .await
+            .unwrap();
+        assert_eq!(
+            value_to_json(&vm, &resources),
+            json!([{"uri": "mem://notes", "name": "notes"}])
+        );
+
+        let uri = string(&mut vm, "mem://notes");
+        let content = read_resource(&mut vm, Some(client), vec![uri], false)
+            .await
+            .unwrap();
+        assert_eq!(value_to_json(&vm, &content), json!("first\nsecond"));
+    }
+
+    #[tokio::test]
+    async fn lists_and_gets_prompts() {
+        let (mut vm, client) = connect().await;
+
+        let prompts = list_prompts(&mut vm, Some(client.clone()), vec![], false)
+            .await
+            .unwrap();
+        assert_eq!(
+            value_to_json(&vm, &prompts),
+            json!([{"name": "review", "description": "review some code"}])
+        );
+
+        let name = string(&mut vm, "review");
+        let args = json_to_value(&mut vm, &json!({"language": "rust"}));
+        let prompt = get_prompt(&mut vm, Some(client), vec![name, args], false)
+            .await
+            .unwrap();
+        assert_eq!(
+            value_to_json(&vm, &prompt),
+            json!({"messages": [{"role": "user", "content": {"type": "text", "text": "review this rust"}}]})
+        );
+    }
+}
@@ -1,11 +1,16 @@
 pub mod members;
+pub mod server;
 pub mod types;
-use crate::{memory::MemObject, std::mcp::members::init_obj};
+use crate::{
+    memory::MemObject,
+    std::mcp::members::{init_obj, serve_obj},
+};
 
 pub fn generate_struct() -> (String, Vec<(String, MemObject)>) {
     let mut fields = vec![];
 
     fields.push(("init".to_string(), init_obj()));
+    fields.push(("serve".to_string(), serve_obj()));
 
     ("mcp".to_string(), fields)
 }
@@ -0,0 +1,173 @@
+// server side of mcp: ego functions served as tools. calls arrive
+// on the transport tasks and are dispatched into the vm through the
+// events queue, the vm answers on a oneshot channel once the
+// function returns
+
+use std::sync::Arc;
+
+use rmcp::{
+    model::{
+        CallToolRequestParam, CallToolResult, Content, ErrorData, Implementation, JsonObject,
+        ListToolsResult, PaginatedRequestParam, ServerCapabilities, ServerInfo, Tool,
+    },
+    service::RequestContext,
+    RoleServer, ServerHandler,
+};
+use serde_json::{json, Value as JsonValue};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::{events::Event, types::object::func::Function};
+
+const DEFAULT_ADDRESS: &str = "127.0.0.1:8000";
+const DEFAULT_PATH: &str = "/mcp";
+
+// how the server is exposed. built from the `mcp.serve` config:
+//   mcp.serve({name, version, transport: "stdio"}, tools)
+//   mcp.serve({name, version, transport: "http", address, path}, tools)
+#[derive(Debug, Clone)]
+pub enum McpServeTransport {
+    Stdio,
+    Http { address: String, path: String },
+}
+
+impl McpServeTransport {
+    pub fn from_config(config: &JsonValue) -> Result<McpServeTransport, String> {
+        let field = |name: &str| config.get(name).and_then(|v| v.as_str()).map(String::from);
+        let transport = field("transport").unwrap_or("stdio".to_string());
+
+        match transport.as_str() {
+            "stdio" => Ok(McpServeTransport::Stdio),
+            "http" | "streamable_http" => Ok(McpServeTransport::Http {
+                address: field("address").unwrap_or(DEFAULT_ADDRESS.to_string()),
+                path: field("path").unwrap_or(DEFAULT_PATH.to_string()),
+            }),
+            other => Err(format!("unknown transport '{}'", other)),
+        }
+    }
+
+    pub fn to_string(&self) -> String {
+        match self {
+            McpServeTransport::Stdio => "stdio".to_string(),
+            McpServeTransport::Http { address, path } => format!("http://{}{}", address, path),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct McpServedTool {
+    pub name: String,
+    pub description: Option<String>,
+    pub function: Function,
+}
+
+impl McpServedTool {
+    // ego params are untyped, so the schema only carries their names
+    fn to_tool(&self) -> Tool {
+        let properties: JsonObject = self
+            .function
+            .parameters
+            .iter()
+            .map(|p| (p.clone(), json!({})))
+            .collect();
+        let schema = json!({
+            "type": "object",
+            "properties": properties,
+            "required": self.function.parameters,
+        });
+        let schema: JsonObject = match schema {
+            JsonValue::Object(schema) => schema,
+            _ => JsonObject::new(),
+        };
+
+        let mut tool = Tool::new(self.name.clone(), "", Arc::new(schema));
+        tool.description = self.description.clone().map(Into::into);
+        tool
+    }
+}
+
+#[derive(Clone)]
+pub struct McpServer {
+    info: Implementation,
+    tools: Arc<Vec<McpServedTool>>,
+    vm_notifier: mpsc::UnboundedSender<Event>,
+}
+
+impl McpServer {
+    pub fn new(
+        name: String,
+        version: String,
+        tools: Vec<McpServedTool>,
+        vm_notifier: mpsc::UnboundedSender<Event>,
+    ) -> McpServer {
+        McpServer {
+            info: Implementation {
+                name,
+                version,
+                ..Implementation::default()
+            },
+            tools: Arc::new(tools),
+            vm_notifier,
+        }
+    }
+}
+
+impl ServerHandler for McpServer {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            server_info: self.info.clone(),
+            ..ServerInfo::default()
+        }
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, ErrorData> {
+        Ok(ListToolsResult {
+            tools: self.tools.iter().map(|t| t.to_tool()).collect(),
+            next_cursor: None,
+        })
+    }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let Some(tool) = self.tools.iter().find(|t| t.name == request.name) else {
+            return Err(ErrorData::invalid_params(
+                format!("unknown tool '{}'", request.name),
+                None,
+            ));
+        };
+
+        // named arguments to positional ones, missing ones are nothing
+        let arguments = request.arguments.unwrap_or_default();
+        let args = tool
+            .function
+            .parameters
+            .iter()
+            .map(|p| arguments.get(p).cloned().unwrap_or(JsonValue::Null))
+            .collect();
+
+        let (reply, answer) = oneshot::channel();
+        let event = Event::Request(tool.function.clone(), args, reply);
+        if self.vm_notifier.send(event).is_err() {
+            return Err(ErrorData::internal_error("vm is not running", None));
+        }
+
+        match answer.await {
+            Ok(Ok(JsonValue::String(text))) => {
+                Ok(CallToolResult::success(vec![Content::text(text)]))
+            }
+            Ok(Ok(value @ JsonValue::Object(_))) => Ok(CallToolResult::structured(value)),
+            Ok(Ok(value)) => Ok(CallToolResult::success(vec![Content::text(
+                value.to_string(),
+            )])),
+            Ok(Err(message)) => Ok(CallToolResult::error(vec![Content::text(message)])),
+            Err(_) => Err(ErrorData::internal_error("vm dropped the tool call", None)),
+        }
+    }
+}
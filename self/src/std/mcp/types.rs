@@ -3,20 +3,28 @@ use std::{collections::HashMap, sync::Arc};
 use futures::lock::Mutex;
 use rmcp::{model::InitializeRequestParam, service::RunningService, RoleClient};
 
+use serde_json::Value as JsonValue;
+
 use crate::{
-    memory::MemObject,
     std::{
         heap_utils::put_string,
-        mcp::members::{list_tools_obj, shutdown_obj},
+        mcp::members::{
+            call_tool_obj, get_prompt_obj, list_prompts_obj, list_resources_obj, list_tools_obj,
+            read_resource_obj, shutdown_obj, tool_call_obj,
+        },
+        utils::json_to_value,
     },
     types::{object::structs::StructLiteral, Value},
     vm::Vm,
 };
 
+// shared between the client and its tools, none once shut down
+pub type McpClientRef = Arc<Mutex<Option<RunningService<RoleClient, InitializeRequestParam>>>>;
+
 #[derive(Debug)]
 pub struct McpClient {
     pub url: String,
-    pub client: Arc<Mutex<Option<RunningService<RoleClient, InitializeRequestParam>>>>,
+    pub client: McpClientRef,
     pub shape: StructLiteral,
 }
 
@@ -40,11 +48,19 @@ impl McpClient {
     ) -> McpClient {
         let mut fields = HashMap::new();
 
-        let shutdown_handle = vm.memory.alloc(shutdown_obj());
-        let list_tools_handle = vm.memory.alloc(list_tools_obj());
-
-        fields.insert("shutdown".to_string(), Value::Handle(shutdown_handle));
-        fields.insert("list_tools".to_string(), Value::Handle(list_tools_handle));
+        let members = [
+            ("shutdown", shutdown_obj()),
+            ("list_tools", list_tools_obj()),
+            ("call_tool", call_tool_obj()),
+            ("list_resources", list_resources_obj()),
+            ("read_resource", read_resource_obj()),
+            ("list_prompts", list_prompts_obj()),
+            ("get_prompt", get_prompt_obj()),
+        ];
+        for (name, obj) in members {
+            let handle = vm.memory.alloc(obj);
+            fields.insert(name.to_string(), Value::Handle(handle));
+        }
 
         McpClient {
             url,
@@ -61,11 +77,22 @@ impl McpClient {
 #[derive(Debug)]
 pub struct McpTool {
     pub name: String,
+    pub description: Option<String>,
+    pub input_schema: JsonValue,
+    pub url: String,
+    pub client: McpClientRef,
     pub shape: StructLiteral,
 }
 
 impl McpTool {
-    pub fn new(name: String, description: Option<String>, vm: &mut Vm) -> McpTool {
+    pub fn new(
+        name: String,
+        description: Option<String>,
+        input_schema: JsonValue,
+        url: String,
+        client: McpClientRef,
+        vm: &mut Vm,
+    ) -> McpTool {
         let mut fields = HashMap::new();
 
         // add name in struct and in struct.shape to allow
@@ -73,13 +100,23 @@ impl McpTool {
         let name_handle = put_string(vm, name.clone());
         fields.insert("name".to_string(), Value::Handle(name_handle));
 
-        if let Some(desc) = description {
-            let desc_handle = put_string(vm, desc);
+        if let Some(desc) = &description {
+            let desc_handle = put_string(vm, desc.clone());
             fields.insert("description".to_string(), Value::Handle(desc_handle));
         }
 
+        let input_schema_value = json_to_value(vm, &input_schema);
+        fields.insert("input_schema".to_string(), input_schema_value);
+
+        let call_handle = vm.memory.alloc(tool_call_obj());
+        fields.insert("call".to_string(), Value::Handle(call_handle));
+
         McpTool {
             name: name,
+            description,
+            input_schema,
+            url,
+            client,
             shape: StructLiteral::new("McpTool".to_string(), fields),
         }
     }
@@ -88,3 +125,73 @@ impl McpTool {
         format!("McpTool({})", self.name)
     }
 }
+
+// how to reach the server. built from the `mcp.init` params:
+//   mcp.init(url, config)
+//   mcp.init({transport: "stdio", command, args, env})
+//   mcp.init({transport: "sse", url})
+#[derive(Debug, Clone)]
+pub enum McpTransport {
+    Http(String),
+    Sse(String),
+    Stdio {
+        command: String,
+        args: Vec<String>,
+        env: Vec<(String, String)>,
+    },
+}
+
+impl McpTransport {
+    pub fn from_config(url: Option<String>, config: &JsonValue) -> Result<McpTransport, String> {
+        let field = |name: &str| config.get(name).and_then(|v| v.as_str()).map(String::from);
+        let url = url.or(field("url"));
+        let transport = field("transport").unwrap_or("http".to_string());
+
+        match transport.as_str() {
+            "http" | "streamable_http" => match url {
+                Some(url) => Ok(McpTransport::Http(url)),
+                None => Err("http transport needs an url".to_string()),
+            },
+            "sse" => match url {
+                Some(url) => Ok(McpTransport::Sse(url)),
+                None => Err("sse transport needs an url".to_string()),
+            },
+            "stdio" => {
+                let Some(command) = field("command") else {
+                    return Err("stdio transport needs a command".to_string());
+                };
+                let args = config
+                    .get("args")
+                    .and_then(|a| a.as_array())
+                    .map(|a| a.iter().map(json_to_arg).collect())
+                    .unwrap_or_default();
+                let env = config
+                    .get("env")
+                    .and_then(|e| e.as_object())
+                    .map(|e| e.iter().map(|(k, v)| (k.clone(), json_to_arg(v))).collect())
+                    .unwrap_or_default();
+                Ok(McpTransport::Stdio { command, args, env })
+            }
+            other => Err(format!("unknown transport '{}'", other)),
+        }
+    }
+
+    pub fn to_string(&self) -> String {
+        match self {
+            McpTransport::Http(url) => url.clone(),
+            McpTransport::Sse(url) => format!("sse:{}", url),
+            McpTransport::Stdio { command, args, .. } => {
+                format!("stdio:{} {}", command, args.join(" "))
+                    .trim()
+                    .to_string()
+            }
+        }
+    }
+}
+
+fn json_to_arg(value: &JsonValue) -> String {
+    match value {
+        JsonValue::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
@@ -1,6 +1,7 @@
 use crate::memory::MemObject;
 
 pub mod ai;
+pub mod bytes;
 pub mod env;
 pub mod fs;
 pub mod heap_utils;
@@ -11,12 +12,15 @@ pub mod native;
 pub mod net;
 pub mod os;
 pub mod path;
+pub mod process;
 pub mod schedule;
 pub mod selfmod;
 pub mod selfstring;
-mod utils;
+pub mod time;
+pub mod utils;
 pub mod vector;
 pub mod web;
+pub mod ws;
 
 pub enum NativeModule {
     AI,
@@ -30,8 +34,12 @@ pub enum NativeModule {
     Http,
     Web,
     Path,
+    Process,
     Schedule,
     Io,
+    Bytes,
+    Ws,
+    Time,
 }
 
 pub fn get_native_module_type(module_name: &str) -> Option<NativeModule> {
@@ -44,11 +52,15 @@ pub fn get_native_module_type(module_name: &str) -> Option<NativeModule> {
         "net" => Some(NativeModule::Net),
         "env" => Some(NativeModule::Env),
         "path" => Some(NativeModule::Path),
+        "process" => Some(NativeModule::Process),
         "mcp" => Some(NativeModule::Mcp),
         "http" => Some(NativeModule::Http),
         "web" => Some(NativeModule::Web),
         "schedule" => Some(NativeModule::Schedule),
         "io" => Some(NativeModule::Io),
+        "bytes" => Some(NativeModule::Bytes),
+        "ws" => Some(NativeModule::Ws),
+        "time" => Some(NativeModule::Time),
         _ => None,
     }
 }
@@ -65,11 +77,15 @@ pub fn generate_native_module(
         NativeModule::Net => net::generate_struct(),
         NativeModule::Env => env::generate_struct(),
         NativeModule::Path => path::generate_struct(),
+        NativeModule::Process => process::generate_struct(),
         NativeModule::Mcp => mcp::generate_struct(),
         NativeModule::Http => http::generate_struct(),
         NativeModule::Web => web::generate_struct(),
         NativeModule::Schedule => schedule::generate_struct(),
         NativeModule::Io => io::generate_struct(),
+        NativeModule::Bytes => bytes::generate_struct(),
+        NativeModule::Ws => ws::generate_struct(),
+        NativeModule::Time => time::generate_struct(),
     }
 }
 
@@ -87,6 +103,11 @@ pub fn gen_native_modules_defs() -> Vec<NativeModuleDef> {
         web::generate_mod_def(),
         http::generate_mod_def(),
         io::generate_mod_def(),
+        process::generate_mod_def(),
+        os::generate_mod_def(),
+        path::generate_mod_def(),
+        ws::generate_mod_def(),
+        time::generate_mod_def(),
     ];
 }
 
@@ -0,0 +1,415 @@
+// typed calls to shared library symbols. signatures are declared
+// from ego (`lib.fn("add", ["i32", "i32"], "i32")`) and checked
+// before any call, so a mismatch is an error and not undefined
+// behaviour.
+//
+// calls go through libffi, which builds them for the declared
+// signature following the calling convention of the platform.
+// variadic functions list their fixed arguments before a "..."
+// marker and the types of the variadic ones after it, like
+// `lib.fn("printf", ["utf8", "...", "i32"], "i32")`
+
+use std::{
+    ffi::{c_char, c_void, CStr, CString},
+    ptr::addr_of_mut,
+};
+
+use libffi::{
+    low::{self, ffi_cif, ffi_type, types, CodePtr},
+    raw::{ffi_arg, ffi_sarg},
+};
+
+use crate::{
+    core::error::{self, ffi_errors::FfiError, VMError, VMErrorType},
+    memory::MemObject,
+    std::heap_utils::put_string,
+    types::{
+        raw::{bool::Bool, f64::F64, i32::I32, i64::I64, u32::U32, u64::U64, RawValue},
+        Value,
+    },
+    vm::Vm,
+};
+
+pub const VARIADIC_MARKER: &str = "...";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FfiType {
+    I32,
+    I64,
+    U32,
+    U64,
+    F64,
+    Bool,
+    Utf8,
+    Ptr,
+    Buffer,
+    Nothing,
+}
+
+impl FfiType {
+    pub fn from_str(name: &str) -> Result<FfiType, FfiError> {
+        match name {
+            "i32" => Ok(FfiType::I32),
+            "i64" => Ok(FfiType::I64),
+            "u32" => Ok(FfiType::U32),
+            "u64" => Ok(FfiType::U64),
+            "f64" | "number" => Ok(FfiType::F64),
+            "bool" => Ok(FfiType::Bool),
+            "utf8" | "string" => Ok(FfiType::Utf8),
+            "ptr" | "pointer" => Ok(FfiType::Ptr),
+            "buffer" => Ok(FfiType::Buffer),
+            "nothing" | "void" => Ok(FfiType::Nothing),
+            other => Err(FfiError::UnknownType(other.to_string())),
+        }
+    }
+
+    // variadic arguments are promoted as in C, bools are passed as int
+    fn ffi_type(&self, variadic: bool) -> *mut ffi_type {
+        match self {
+            FfiType::I32 => addr_of_mut!(types::sint32),
+            FfiType::I64 => addr_of_mut!(types::sint64),
+            FfiType::U32 => addr_of_mut!(types::uint32),
+            FfiType::U64 => addr_of_mut!(types::uint64),
+            FfiType::F64 => addr_of_mut!(types::double),
+            FfiType::Bool if variadic => addr_of_mut!(types::sint32),
+            FfiType::Bool => addr_of_mut!(types::uint8),
+            FfiType::Utf8 | FfiType::Ptr | FfiType::Buffer => addr_of_mut!(types::pointer),
+            FfiType::Nothing => addr_of_mut!(types::void),
+        }
+    }
+
+    pub fn to_string(&self) -> String {
+        match self {
+            FfiType::I32 => "i32",
+            FfiType::I64 => "i64",
+            FfiType::U32 => "u32",
+            FfiType::U64 => "u64",
+            FfiType::F64 => "f64",
+            FfiType::Bool => "bool",
+            FfiType::Utf8 => "utf8",
+            FfiType::Ptr => "ptr",
+            FfiType::Buffer => "buffer",
+            FfiType::Nothing => "nothing",
+        }
+        .to_string()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FfiSignature {
+    pub symbol: String,
+    pub args: Vec<FfiType>,
+    pub ret: FfiType,
+    // number of fixed arguments of variadic functions
+    pub fixed: Option<usize>,
+}
+
+impl FfiSignature {
+    pub fn new(
+        symbol: String,
+        args: Vec<FfiType>,
+        ret: FfiType,
+        fixed: Option<usize>,
+    ) -> Result<FfiSignature, FfiError> {
+        if args.contains(&FfiType::Nothing) {
+            return Err(FfiError::UnsupportedSignature(format!(
+                "'{}': nothing is only valid as return type",
+                symbol
+            )));
+        }
+        if ret == FfiType::Buffer {
+            return Err(FfiError::UnsupportedSignature(format!(
+                "'{}': buffers cannot be returned, return a ptr instead",
+                symbol
+            )));
+        }
+        if fixed == Some(0) {
+            return Err(FfiError::UnsupportedSignature(format!(
+                "'{}': variadic functions need at least one fixed argument",
+                symbol
+            )));
+        }
+
+        Ok(FfiSignature {
+            symbol,
+            args,
+            ret,
+            fixed,
+        })
+    }
+
+    pub fn to_string(&self) -> String {
+        let mut args: Vec<String> = self.args.iter().map(|a| a.to_string()).collect();
+        if let Some(fixed) = self.fixed {
+            args.insert(fixed, VARIADIC_MARKER.to_string());
+        }
+        format!(
+            "{}({}) -> {}",
+            self.symbol,
+            args.join(", "),
+            self.ret.to_string()
+        )
+    }
+}
+
+// arguments are kept with their C size while the call is made,
+// libffi reads them through pointers
+enum FfiArg {
+    I32(i32),
+    I64(i64),
+    U32(u32),
+    U64(u64),
+    U8(u8),
+    F64(f64),
+    Ptr(*mut c_void),
+}
+
+impl FfiArg {
+    fn as_ptr(&mut self) -> *mut c_void {
+        match self {
+            FfiArg::I32(v) => v as *mut i32 as *mut c_void,
+            FfiArg::I64(v) => v as *mut i64 as *mut c_void,
+            FfiArg::U32(v) => v as *mut u32 as *mut c_void,
+            FfiArg::U64(v) => v as *mut u64 as *mut c_void,
+            FfiArg::U8(v) => v as *mut u8 as *mut c_void,
+            FfiArg::F64(v) => v as *mut f64 as *mut c_void,
+            FfiArg::Ptr(v) => v as *mut *mut c_void as *mut c_void,
+        }
+    }
+}
+
+// integers are checked against the range of the declared type
+fn int_arg(value: &Value) -> Option<i128> {
+    match value {
+        Value::RawValue(RawValue::I32(v)) => Some(v.value as i128),
+        Value::RawValue(RawValue::I64(v)) => Some(v.value as i128),
+        Value::RawValue(RawValue::U32(v)) => Some(v.value as i128),
+        Value::RawValue(RawValue::U64(v)) => Some(v.value as i128),
+        _ => None,
+    }
+}
+
+// calls `symbol` following `signature`. `symbol` must be the address
+// of a function with that signature
+pub fn call_symbol(
+    vm: &mut Vm,
+    symbol: *const c_void,
+    signature: &FfiSignature,
+    args: &[Value],
+) -> Result<Value, VMError> {
+    if args.len() != signature.args.len() {
+        return Err(ffi_error(
+            FfiError::ArgsCount {
+                symbol: signature.symbol.clone(),
+                expected: signature.args.len() as u32,
+                received: args.len() as u32,
+            },
+            vm,
+        ));
+    }
+
+    // strings and buffers must outlive the call
+    let mut strings: Vec<CString> = vec![];
+    let mut buffers: Vec<(Vec<u8>, Option<Value>)> = vec![];
+    let mut ffi_args = vec![];
+
+    for (index, (ffi_type, arg)) in signature.args.iter().zip(args).enumerate() {
+        let variadic = signature.fixed.is_some_and(|fixed| index >= fixed);
+        let mismatch = |vm: &Vm| {
+            let received = match int_arg(arg) {
+                Some(int) => format!("{} {}", arg.get_resolved_type(vm), int),
+                None => arg.get_resolved_type(vm),
+            };
+            ffi_error(
+                FfiError::ArgType {
+                    symbol: signature.symbol.clone(),
+                    index,
+                    expected: ffi_type.to_string(),
+                    received,
+                },
+                vm,
+            )
+        };
+        let int = || int_arg(arg).ok_or_else(|| mismatch(vm));
+
+        let ffi_arg = match ffi_type {
+            FfiType::F64 => FfiArg::F64(match arg {
+                Value::RawValue(RawValue::F64(v)) => v.value,
+                _ => int()? as f64,
+            }),
+            FfiType::I32 => FfiArg::I32(i32::try_from(int()?).map_err(|_| mismatch(vm))?),
+            FfiType::I64 => FfiArg::I64(i64::try_from(int()?).map_err(|_| mismatch(vm))?),
+            FfiType::U32 => FfiArg::U32(u32::try_from(int()?).map_err(|_| mismatch(vm))?),
+            FfiType::U64 => FfiArg::U64(u64::try_from(int()?).map_err(|_| mismatch(vm))?),
+            FfiType::Bool => match arg {
+                Value::RawValue(RawValue::Bool(b)) if variadic => FfiArg::I32(b.value as i32),
+                Value::RawValue(RawValue::Bool(b)) => FfiArg::U8(b.value as u8),
+                _ => return Err(mismatch(vm)),
+            },
+            FfiType::Ptr => match arg {
+                Value::RawValue(RawValue::Nothing) => FfiArg::Ptr(std::ptr::null_mut()),
+                _ => {
+                    let address = usize::try_from(int()?).map_err(|_| mismatch(vm))?;
+                    FfiArg::Ptr(address as *mut c_void)
+                }
+            },
+            FfiType::Utf8 => {
+                let string = arg.as_string_obj(vm).map_err(|_| mismatch(vm))?;
+                let c_string = CString::new(string).map_err(|e| {
+                    ffi_error(
+                        FfiError::InvalidCString(format!("argument {}: {}", index, e)),
+                        vm,
+                    )
+                })?;
+                let ptr = c_string.as_ptr() as *mut c_void;
+                strings.push(c_string);
+                FfiArg::Ptr(ptr)
+            }
+            FfiType::Buffer => {
+                let (mut bytes, write_back) = buffer_arg(vm, arg).ok_or_else(|| mismatch(vm))?;
+                // the heap allocation doesn't move with the vec
+                let ptr = bytes.as_mut_ptr() as *mut c_void;
+                buffers.push((bytes, write_back));
+                FfiArg::Ptr(ptr)
+            }
+            FfiType::Nothing => unreachable!(),
+        };
+        ffi_args.push(ffi_arg);
+    }
+
+    let mut arg_types: Vec<*mut ffi_type> = signature
+        .args
+        .iter()
+        .enumerate()
+        .map(|(index, t)| t.ffi_type(signature.fixed.is_some_and(|fixed| index >= fixed)))
+        .collect();
+    let mut arg_values: Vec<*mut c_void> = ffi_args.iter_mut().map(FfiArg::as_ptr).collect();
+    let mut cif = ffi_cif::default();
+    let prepared = unsafe {
+        match signature.fixed {
+            Some(fixed) => low::prep_cif_var(
+                &mut cif,
+                low::ffi_abi_FFI_DEFAULT_ABI,
+                fixed,
+                arg_types.len(),
+                signature.ret.ffi_type(false),
+                arg_types.as_mut_ptr(),
+            ),
+            None => low::prep_cif(
+                &mut cif,
+                low::ffi_abi_FFI_DEFAULT_ABI,
+                arg_types.len(),
+                signature.ret.ffi_type(false),
+                arg_types.as_mut_ptr(),
+            ),
+        }
+    };
+    if let Err(e) = prepared {
+        return Err(ffi_error(
+            FfiError::UnsupportedSignature(format!("'{}': {:?}", signature.to_string(), e)),
+            vm,
+        ));
+    }
+
+    let code = CodePtr::from_ptr(symbol);
+    let values = arg_values.as_mut_ptr();
+    // integers smaller than a register come back widened to one
+    let result = unsafe {
+        match signature.ret {
+            FfiType::F64 => FfiReturn::Float(low::call::<f64>(&mut cif, code, values)),
+            FfiType::I64 => FfiReturn::Int(low::call::<i64>(&mut cif, code, values) as i128),
+            FfiType::U64 => FfiReturn::Int(low::call::<u64>(&mut cif, code, values) as i128),
+            FfiType::I32 => FfiReturn::Int(low::call::<ffi_sarg>(&mut cif, code, values) as i128),
+            FfiType::U32 | FfiType::Bool | FfiType::Nothing => {
+                FfiReturn::Int(low::call::<ffi_arg>(&mut cif, code, values) as i128)
+            }
+            FfiType::Utf8 | FfiType::Ptr | FfiType::Buffer => {
+                FfiReturn::Ptr(low::call::<*mut c_void>(&mut cif, code, values))
+            }
+        }
+    };
+
+    // buffers built from vectors get the bytes written by the callee
+    drop(strings);
+    for (bytes, write_back) in buffers {
+        if let Some(Value::Handle(h)) = write_back {
+            if let MemObject::Vector(v) = vm.memory.resolve_mut(&h) {
+                v.elements = bytes
+                    .iter()
+                    .map(|b| Value::RawValue(RawValue::I32(I32::new(*b as i32))))
+                    .collect();
+            }
+        }
+    }
+
+    let out_of_range = |value: i128, vm: &Vm| {
+        ffi_error(
+            FfiError::ReturnType {
+                symbol: signature.symbol.clone(),
+                expected: signature.ret.to_string(),
+                received: value.to_string(),
+            },
+            vm,
+        )
+    };
+    Ok(match (&signature.ret, result) {
+        (FfiType::F64, FfiReturn::Float(v)) => Value::RawValue(RawValue::F64(F64::new(v))),
+        (FfiType::I32, FfiReturn::Int(v)) => Value::RawValue(RawValue::I32(I32::new(
+            i32::try_from(v).map_err(|_| out_of_range(v, vm))?,
+        ))),
+        (FfiType::I64, FfiReturn::Int(v)) => Value::RawValue(RawValue::I64(I64::new(
+            i64::try_from(v).map_err(|_| out_of_range(v, vm))?,
+        ))),
+        (FfiType::U32, FfiReturn::Int(v)) => Value::RawValue(RawValue::U32(U32::new(
+            u32::try_from(v).map_err(|_| out_of_range(v, vm))?,
+        ))),
+        (FfiType::U64, FfiReturn::Int(v)) => Value::RawValue(RawValue::U64(U64::new(
+            u64::try_from(v).map_err(|_| out_of_range(v, vm))?,
+        ))),
+        (FfiType::Bool, FfiReturn::Int(v)) => Value::RawValue(RawValue::Bool(Bool::new(
+            u8::try_from(v).map_err(|_| out_of_range(v, vm))? != 0,
+        ))),
+        (FfiType::Ptr, FfiReturn::Ptr(ptr)) => {
+            Value::RawValue(RawValue::U64(U64::new(ptr as usize as u64)))
+        }
+        (FfiType::Utf8, FfiReturn::Ptr(ptr)) if ptr.is_null() => Value::RawValue(RawValue::Nothing),
+        (FfiType::Utf8, FfiReturn::Ptr(ptr)) => {
+            // the string is owned by the library, it's copied and not freed
+            let string = unsafe { CStr::from_ptr(ptr as *const c_char) }
+                .to_string_lossy()
+                .to_string();
+            Value::Handle(put_string(vm, string))
+        }
+        _ => Value::RawValue(RawValue::Nothing),
+    })
+}
+
+// vectors of bytes are written back after the call, strings are
+// passed as read-only bytes
+fn buffer_arg(vm: &Vm, arg: &Value) -> Option<(Vec<u8>, Option<Value>)> {
+    match arg.as_mem_obj(vm).ok()? {
+        MemObject::Vector(v) => {
+            let mut bytes = vec![];
+            for element in &v.elements {
+                let byte = match element {
+                    Value::RawValue(r) => r.as_isize()?,
+                    _ => return None,
+                };
+                bytes.push(u8::try_from(byte).ok()?);
+            }
+            Some((bytes, Some(arg.clone())))
+        }
+        MemObject::String(s) => Some((s.value.clone().into_bytes(), None)),
+        _ => None,
+    }
+}
+
+enum FfiReturn {
+    Int(i128),
+    Float(f64),
+    Ptr(*mut c_void),
+}
+
+pub fn ffi_error(err: FfiError, vm: &Vm) -> VMError {
+    error::throw(VMErrorType::Ffi(err), vm)
+}
@@ -1,12 +1,17 @@
 use core::ffi::c_str;
-use std::ffi::{c_char, CString};
+use std::ffi::{c_char, c_void, CString};
+use std::io::ErrorKind;
 use std::path::Path;
+use std::sync::Arc;
 
+use crate::core::error::ffi_errors::FfiError;
 use crate::core::error::fs_errors::FsError;
 use crate::core::error::type_errors::TypeError;
 use crate::core::error::{self, VMErrorType};
 use crate::memory::Handle;
-use crate::std::native::types::NativeLib;
+use crate::std::native::ffi::{call_symbol, ffi_error, FfiSignature, FfiType, VARIADIC_MARKER};
+use crate::std::native::plugin::load_plugin;
+use crate::std::native::types::{NativeFn, NativeLib};
 use crate::std::NativeMember;
 use crate::types::object::native_struct::NativeStruct;
 use crate::{
@@ -25,7 +30,7 @@ use libloading::{Library, Symbol};
 pub fn load_lib_def() -> NativeMember {
     NativeMember {
         name: "load_lib".to_string(),
-        description: "load a shared library (.so, .dll) to the self-vm context. works as ffi."
+        description: "load a shared library (.so, .dll) to the self-vm context. works as ffi. libraries exporting ego_plugin_init are loaded as a module"
             .to_string(),
         params: Some(vec!["path(string)".to_string()]),
     }
@@ -63,11 +68,20 @@ pub fn load_lib(
     let lib = unsafe {
         Library::new(path_obj).map_err(|e| {
             error::throw(
-                VMErrorType::Fs(FsError::ReadError(format!("{}: {}", path, e))),
+                VMErrorType::Fs(FsError::ReadError(
+                    format!("{}: {}", path, e),
+                    ErrorKind::Other,
+                )),
                 vm,
             )
         })
     }?;
+    let lib = Arc::new(lib);
+
+    // plugins are loaded as a whole module
+    if let Some(module) = load_plugin(&lib, &path, vm)? {
+        return Ok(module);
+    }
 
     let natlib = NativeLib::new_initialized(path, lib, vm);
     let natlib_handle = vm
@@ -105,8 +119,28 @@ pub fn call(
         ));
     }
 
-    // get function symbol from lib
     let function_name = params[0].as_string_obj(vm)?;
+
+    // symbols declared with `lib.fn` are called with their signature
+    if let Some(signature) = _self.signatures.get(&function_name).cloned() {
+        let library = _self.library.clone();
+        let symbol = get_symbol(&library, &_self.path, &function_name, vm)?;
+        if debug {
+            vm.trace(format!("NATIVE.CALL <- {}", signature.to_string()));
+        }
+        return call_symbol(vm, symbol, &signature, &params[1..]);
+    }
+
+    // undeclared symbols keep the original signature: fn(*const c_char)
+    if params.len() < 2 {
+        return Err(error::throw(
+            VMErrorType::TypeError(TypeError::InvalidArgsCount {
+                expected: 2,
+                received: params.len() as u32,
+            }),
+            vm,
+        ));
+    }
     let function_arg = params[1].as_string_obj(vm)?;
 
     let func: Symbol<unsafe extern "C" fn(*const c_char)> = unsafe {
@@ -115,10 +149,10 @@ pub fn call(
             .get(format!("{}\0", function_name).as_bytes())
             .map_err(|e| {
                 error::throw(
-                    VMErrorType::Fs(FsError::ReadError(format!(
-                        "{}: no symbol {}: {}",
-                        _self.path, function_name, e
-                    ))),
+                    VMErrorType::Fs(FsError::ReadError(
+                        format!("{}: no symbol {}: {}", _self.path, function_name, e),
+                        ErrorKind::NotFound,
+                    )),
                     vm,
                 )
             })
@@ -126,7 +160,10 @@ pub fn call(
 
     let name = CString::new(function_arg).map_err(|e| {
         error::throw(
-            VMErrorType::Fs(FsError::ReadError(format!("bad cstr: {}", e))),
+            VMErrorType::Fs(FsError::ReadError(
+                format!("bad cstr: {}", e),
+                ErrorKind::InvalidInput,
+            )),
             vm,
         )
     })?;
@@ -141,3 +178,110 @@ pub fn call(
 
     Ok(Value::RawValue(RawValue::Nothing))
 }
+
+// declares the signature of a symbol: lib.fn("add", ["i32", "i32"], "i32")
+pub fn declare_fn(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let Some(lib_handle) = _self else {
+        unreachable!()
+    };
+    let (library, path) = match vm.memory.resolve(&lib_handle) {
+        MemObject::NativeStruct(NativeStruct::NativeLib(v)) => (v.library.clone(), v.path.clone()),
+        _ => unreachable!(),
+    };
+
+    if params.len() < 2 {
+        return Err(error::throw(
+            VMErrorType::TypeError(TypeError::InvalidArgsCount {
+                expected: 3,
+                received: params.len() as u32,
+            }),
+            vm,
+        ));
+    }
+
+    let name = params[0].as_string_obj(vm)?;
+    let mut args = vec![];
+    let mut fixed = None;
+    for arg in params[1].as_vector_obj(vm)?.elements.iter() {
+        let arg = arg.as_string_obj(vm)?;
+        if arg == VARIADIC_MARKER && fixed.is_none() {
+            fixed = Some(args.len());
+            continue;
+        }
+        args.push(FfiType::from_str(&arg).map_err(|e| ffi_error(e, vm))?);
+    }
+    // the return type is optional, nothing by default
+    let ret = match params.get(2) {
+        Some(Value::RawValue(RawValue::Nothing)) | None => FfiType::Nothing,
+        Some(ret) => {
+            let ret = ret.as_string_obj(vm)?;
+            FfiType::from_str(&ret).map_err(|e| ffi_error(e, vm))?
+        }
+    };
+
+    let signature =
+        FfiSignature::new(name.clone(), args, ret, fixed).map_err(|e| ffi_error(e, vm))?;
+    get_symbol(&library, &path, &name, vm)?;
+
+    if debug {
+        vm.trace(format!("NATIVE.FN <- {}", signature.to_string()));
+    }
+
+    if let MemObject::NativeStruct(NativeStruct::NativeLib(lib)) =
+        vm.memory.resolve_mut(&lib_handle)
+    {
+        lib.signatures.insert(name, signature.clone());
+    }
+
+    let native_fn = NativeFn::new_initialized(path, library, signature, vm);
+    let handle = vm
+        .memory
+        .alloc(MemObject::NativeStruct(NativeStruct::NativeFn(native_fn)));
+    Ok(Value::Handle(handle))
+}
+
+// NativeFn type methods
+pub fn native_fn_call(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let (library, path, signature) = match _self.map(|h| vm.memory.resolve(&h)) {
+        Some(MemObject::NativeStruct(NativeStruct::NativeFn(f))) => {
+            (f.library.clone(), f.path.clone(), f.signature.clone())
+        }
+        _ => unreachable!(),
+    };
+
+    let symbol = get_symbol(&library, &path, &signature.symbol, vm)?;
+    if debug {
+        vm.trace(format!("NATIVE.CALL <- {}", signature.to_string()));
+    }
+    call_symbol(vm, symbol, &signature, &params)
+}
+
+// utils functions
+fn get_symbol(
+    library: &Library,
+    path: &str,
+    name: &str,
+    vm: &Vm,
+) -> Result<*const c_void, VMError> {
+    let symbol: Symbol<*const c_void> = unsafe { library.get(format!("{}\0", name).as_bytes()) }
+        .map_err(|_| {
+            ffi_error(
+                FfiError::SymbolNotFound {
+                    lib: path.to_string(),
+                    symbol: name.to_string(),
+                },
+                vm,
+            )
+        })?;
+    Ok(*symbol)
+}
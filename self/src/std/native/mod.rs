@@ -3,7 +3,9 @@ use crate::{
     std::{native::members::load_lib_obj, NativeModuleDef},
 };
 
+pub mod ffi;
 mod members;
+pub mod plugin;
 pub mod types;
 
 pub fn generate_struct() -> (String, Vec<(String, MemObject)>) {
@@ -0,0 +1,197 @@
+// c-abi plugins: a shared library exporting `ego_plugin_init` is
+// loaded by `native.load_lib` as a whole module instead of a bag
+// of symbols. values cross the boundary as json strings, so the
+// plugin can be written in any language able to export c symbols.
+//
+//   typedef char *(*ego_plugin_fn)(const char *args_json);
+//
+//   typedef struct {
+//       const char *name;
+//       const char *description; // may be null
+//       ego_plugin_fn call;
+//   } ego_plugin_member;
+//
+//   typedef struct {
+//       uint32_t abi_version;    // EGO_PLUGIN_ABI_VERSION
+//       const char *name;
+//       const ego_plugin_member *members;
+//       size_t members_len;
+//       void (*free_string)(char *); // frees call results, may be null
+//   } ego_plugin_module;
+//
+//   const ego_plugin_module *ego_plugin_init(void);
+//
+// members receive their arguments as a json array and answer with
+// `{"value": ...}` or `{"error": "message"}`. null means nothing
+
+use std::{
+    collections::HashMap,
+    ffi::{c_char, CStr, CString},
+    sync::Arc,
+};
+
+use libloading::{Library, Symbol};
+use serde_json::Value as JsonValue;
+
+use crate::{
+    core::error::{ffi_errors::FfiError, VMError},
+    memory::MemObject,
+    std::{
+        native::ffi::ffi_error,
+        utils::{json_to_value, value_to_json},
+    },
+    types::{
+        object::{
+            func::{Engine, Function},
+            structs::StructLiteral,
+        },
+        Value,
+    },
+    vm::Vm,
+};
+
+pub const EGO_PLUGIN_ABI_VERSION: u32 = 1;
+const EGO_PLUGIN_INIT: &[u8] = b"ego_plugin_init\0";
+
+pub type EgoPluginFn = unsafe extern "C" fn(args_json: *const c_char) -> *mut c_char;
+
+#[repr(C)]
+pub struct EgoPluginMember {
+    pub name: *const c_char,
+    pub description: *const c_char,
+    pub call: EgoPluginFn,
+}
+
+#[repr(C)]
+pub struct EgoPluginModule {
+    pub abi_version: u32,
+    pub name: *const c_char,
+    pub members: *const EgoPluginMember,
+    pub members_len: usize,
+    pub free_string: Option<unsafe extern "C" fn(*mut c_char)>,
+}
+
+// a plugin member, run by the vm as Engine::Plugin
+#[derive(Debug, Clone)]
+pub struct PluginFunction {
+    // keeps the library loaded while the function is alive
+    pub library: Arc<Library>,
+    pub module: String,
+    pub name: String,
+    pub description: Option<String>,
+    call: EgoPluginFn,
+    free_string: Option<unsafe extern "C" fn(*mut c_char)>,
+}
+
+impl PluginFunction {
+    pub fn call(&self, vm: &mut Vm, args: Vec<Value>) -> Result<Value, VMError> {
+        let args = JsonValue::Array(args.iter().map(|a| value_to_json(vm, a)).collect());
+        let args = CString::new(args.to_string())
+            .map_err(|e| ffi_error(FfiError::InvalidCString(e.to_string()), vm))?;
+
+        let answer = unsafe {
+            let ptr = (self.call)(args.as_ptr());
+            if ptr.is_null() {
+                None
+            } else {
+                let answer = CStr::from_ptr(ptr).to_string_lossy().to_string();
+                if let Some(free_string) = self.free_string {
+                    free_string(ptr);
+                }
+                Some(answer)
+            }
+        };
+        let Some(answer) = answer else {
+            return Ok(json_to_value(vm, &JsonValue::Null));
+        };
+
+        let answer: JsonValue = serde_json::from_str(&answer)
+            .map_err(|e| self.error(format!("invalid answer '{}': {}", answer, e), vm))?;
+        if let Some(message) = answer.get("error") {
+            let message = message
+                .as_str()
+                .map(String::from)
+                .unwrap_or(message.to_string());
+            return Err(self.error(message, vm));
+        }
+        let value = answer.get("value").cloned().unwrap_or(JsonValue::Null);
+        Ok(json_to_value(vm, &value))
+    }
+
+    fn error(&self, message: String, vm: &Vm) -> VMError {
+        ffi_error(
+            FfiError::PluginError(format!("{}.{}: {}", self.module, self.name, message)),
+            vm,
+        )
+    }
+}
+
+// none if the library is not a plugin
+pub fn load_plugin(
+    library: &Arc<Library>,
+    path: &str,
+    vm: &mut Vm,
+) -> Result<Option<Value>, VMError> {
+    let init: Symbol<unsafe extern "C" fn() -> *const EgoPluginModule> =
+        match unsafe { library.get(EGO_PLUGIN_INIT) } {
+            Ok(init) => init,
+            Err(_) => return Ok(None),
+        };
+
+    let module = unsafe { init() };
+    if module.is_null() {
+        return Err(ffi_error(
+            FfiError::PluginError(format!("{}: ego_plugin_init returned null", path)),
+            vm,
+        ));
+    }
+    let module = unsafe { &*module };
+    if module.abi_version != EGO_PLUGIN_ABI_VERSION {
+        return Err(ffi_error(
+            FfiError::PluginError(format!(
+                "{}: abi version {} is not supported (expected {})",
+                path, module.abi_version, EGO_PLUGIN_ABI_VERSION
+            )),
+            vm,
+        ));
+    }
+
+    let module_name = unsafe { c_string(module.name) }.unwrap_or(path.to_string());
+    let members = if module.members.is_null() {
+        &[][..]
+    } else {
+        unsafe { std::slice::from_raw_parts(module.members, module.members_len) }
+    };
+
+    let mut fields = HashMap::new();
+    for member in members {
+        let Some(name) = (unsafe { c_string(member.name) }) else {
+            continue;
+        };
+        let function = PluginFunction {
+            library: library.clone(),
+            module: module_name.clone(),
+            name: name.clone(),
+            description: unsafe { c_string(member.description) },
+            call: member.call,
+            free_string: module.free_string,
+        };
+        let handle = vm.memory.alloc(MemObject::Function(Function::new(
+            name.clone(),
+            vec![],
+            Engine::Plugin(function),
+        )));
+        fields.insert(name, Value::Handle(handle));
+    }
+
+    let module_struct = StructLiteral::new(module_name, fields);
+    let handle = vm.memory.alloc(MemObject::StructLiteral(module_struct));
+    Ok(Some(Value::Handle(handle)))
+}
+
+unsafe fn c_string(ptr: *const c_char) -> Option<String> {
+    if ptr.is_null() {
+        return None;
+    }
+    Some(CStr::from_ptr(ptr).to_string_lossy().to_string())
+}
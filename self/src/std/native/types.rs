@@ -1,10 +1,13 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 
 use libloading::Library;
 
 use crate::{
     memory::MemObject,
-    std::native::members::call,
+    std::native::{
+        ffi::FfiSignature,
+        members::{call, declare_fn, native_fn_call},
+    },
     types::{
         object::{
             func::{Engine, Function},
@@ -18,13 +21,16 @@ use crate::{
 
 #[derive(Debug)]
 pub struct NativeLib {
-    pub library: Library,
+    // shared with the functions declared on it, so the library
+    // is not unloaded while they're alive
+    pub library: Arc<Library>,
     pub path: String,
+    pub signatures: HashMap<String, FfiSignature>,
     pub shape: StructLiteral,
 }
 
 impl NativeLib {
-    pub fn new_initialized(path: String, library: Library, vm: &mut Vm) -> NativeLib {
+    pub fn new_initialized(path: String, library: Arc<Library>, vm: &mut Vm) -> NativeLib {
         // todo: we should here use a memory load type for type specific functions
         // the same applies to all native types like Action. This custom load
         // function will load the function if it doesnt already exists in memory
@@ -33,13 +39,21 @@ impl NativeLib {
             vec![],
             Engine::Native(call),
         )));
+        let fn_function = vm.memory.alloc(MemObject::Function(Function::new(
+            "fn".to_string(),
+            // (name, args, returns). the return type is optional
+            vec!["name".to_string(), "args".to_string()],
+            Engine::Native(declare_fn),
+        )));
 
         let mut fields = HashMap::new();
         fields.insert("call".to_string(), Value::Handle(call_function));
+        fields.insert("fn".to_string(), Value::Handle(fn_function));
 
         NativeLib {
             path,
             library,
+            signatures: HashMap::new(),
             shape: StructLiteral::new("NativeLib".to_string(), fields),
         }
     }
@@ -57,3 +71,53 @@ impl NativeLib {
         }
     }
 }
+
+// a library symbol with a declared signature, returned by `lib.fn`
+#[derive(Debug)]
+pub struct NativeFn {
+    pub library: Arc<Library>,
+    pub path: String,
+    pub signature: FfiSignature,
+    pub shape: StructLiteral,
+}
+
+impl NativeFn {
+    pub fn new_initialized(
+        path: String,
+        library: Arc<Library>,
+        signature: FfiSignature,
+        vm: &mut Vm,
+    ) -> NativeFn {
+        let call_function = vm.memory.alloc(MemObject::Function(Function::new(
+            "call".to_string(),
+            vec![],
+            Engine::Native(native_fn_call),
+        )));
+
+        let mut fields = HashMap::new();
+        fields.insert("call".to_string(), Value::Handle(call_function));
+
+        NativeFn {
+            library,
+            path,
+            signature,
+            shape: StructLiteral::new("NativeFn".to_string(), fields),
+        }
+    }
+
+    pub fn to_string(&self) -> String {
+        format!("NativeFn({})", self.signature.to_string())
+    }
+
+    pub fn property_access(&self, property: &str) -> Option<Value> {
+        match property {
+            "name" => Some(Value::RawValue(RawValue::Utf8(Utf8::new(
+                self.signature.symbol.clone(),
+            )))),
+            "signature" => Some(Value::RawValue(RawValue::Utf8(Utf8::new(
+                self.signature.to_string(),
+            )))),
+            _ => self.shape.property_access(property),
+        }
+    }
+}
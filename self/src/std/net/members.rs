@@ -1,15 +1,22 @@
-use core::panic;
 use std::collections::HashMap;
-use std::io::{Read, Write};
-use std::net::{TcpListener, TcpStream};
+use std::future::Future;
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use tokio::net::{TcpListener, TcpStream, UdpSocket};
+#[cfg(unix)]
+use tokio::net::{UnixListener, UnixStream};
+use tokio_rustls::TlsStream;
 
 use crate::core::error::net_errors::NetErrors;
+use crate::core::error::type_errors::TypeError;
 use crate::core::error::{self, VMErrorType};
 use crate::memory::Handle;
-use crate::std::heap_utils::put_string;
-use crate::std::net::types::{NetServer, NetStream, StreamKind};
-use crate::std::net::utils::tls;
+use crate::std::heap_utils::{put_bytes, put_string};
+use crate::std::net::types::{ListenerKind, NetServer, NetStream, NetUdpSocket, StreamKind};
+use crate::std::net::utils::{listen_options, port_param, tls};
 use crate::types::object::native_struct::NativeStruct;
+use crate::types::object::structs::StructLiteral;
 use crate::types::raw::u64::U64;
 use crate::types::raw::RawValue;
 use crate::{
@@ -22,73 +29,186 @@ use crate::{
     vm::Vm,
 };
 
+const READ_SIZE: usize = 4096;
+// a client that connects and never says hello can't hold accept()
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+type AsyncMethod = for<'a> fn(
+    &'a mut Vm,
+    Option<Handle>,
+    Vec<Value>,
+    bool,
+) -> BoxFuture<'a, Result<Value, VMError>>;
+
+type NativeMethod = fn(&mut Vm, Option<Handle>, Vec<Value>, bool) -> Result<Value, VMError>;
+
+fn net_error(err: NetErrors, vm: &Vm) -> VMError {
+    error::throw(VMErrorType::Net(err), vm)
+}
+
 // connect
 pub fn connect_ref() -> MemObject {
     MemObject::Function(Function::new(
         "connect".to_string(),
         vec!["host".to_string()],
-        Engine::Native(connect),
+        Engine::NativeAsync(connect),
     ))
 }
 
+// connect(host) or connect(host, tls), the host includes the port
 pub fn connect(
     vm: &mut Vm,
     _self: Option<Handle>,
     params: Vec<Value>,
     debug: bool,
-) -> Result<Value, VMError> {
-    let host = params[0].as_string_obj(vm)?;
-    let use_tls = if let Some(second) = params.get(1) {
-        second.as_bool(vm)?
-    } else {
-        false // default if not passed
-    };
-
-    let stream = if use_tls {
-        let tls_stream = tls(&host);
-        if let Ok(_stream) = tls_stream {
-            StreamKind::Tls(_stream)
+) -> BoxFuture<'_, Result<Value, VMError>> {
+    Box::pin(async move {
+        let host = params[0].as_string_obj(vm)?;
+        let use_tls = if let Some(second) = params.get(1) {
+            second.as_bool(vm)?
         } else {
-            return Err(error::throw(
-                VMErrorType::Net(NetErrors::NetConnectError(format!("host {}", host))),
-                vm,
-            ));
+            false // default if not passed
+        };
+        if debug {
+            vm.trace(format!("NET.CONNECT <- {} (tls: {})", host, use_tls));
         }
+
+        let stream = vm
+            .with_events(async {
+                if use_tls {
+                    tls(&host).await.map(|s| StreamKind::Tls(Box::new(s)))
+                } else {
+                    TcpStream::connect(&host)
+                        .await
+                        .map(StreamKind::Plain)
+                        .map_err(|e| e.to_string())
+                }
+            })
+            .await?
+            .map_err(|e| {
+                net_error(
+                    NetErrors::NetConnectError(format!("host {}: {}", host, e)),
+                    vm,
+                )
+            })?;
+
+        let shape = stream_shape(vm, &host);
+        let net_stream = NetStream::new(host, stream, shape);
+        let net_stream_ref = vm
+            .memory
+            .alloc(MemObject::NativeStruct(NativeStruct::NetStream(net_stream)));
+
+        Ok(Value::Handle(net_stream_ref))
+    })
+}
+
+// host and methods of a NetStream
+fn stream_shape(vm: &mut Vm, host: &str) -> HashMap<String, Value> {
+    let mut shape = HashMap::new();
+    let host_ref = put_string(vm, host.to_string());
+    shape.insert("host".to_string(), Value::Handle(host_ref));
+
+    // required params only, read_bytes takes an optional max
+    let methods: [(&str, &[&str], AsyncMethod); 5] = [
+        ("write", &["data"], write),
+        ("read", &[], read),
+        ("write_bytes", &["data"], write_bytes),
+        ("read_bytes", &[], read_bytes),
+        ("close", &[], close),
+    ];
+    for (name, params, method) in methods {
+        let method_ref = vm.memory.alloc(MemObject::Function(Function::new(
+            name.to_string(),
+            params.iter().map(|p| p.to_string()).collect(),
+            Engine::NativeAsync(method),
+        )));
+        shape.insert(name.to_string(), Value::Handle(method_ref));
+    }
+
+    let set_timeout_ref = vm.memory.alloc(MemObject::Function(Function::new(
+        "set_timeout".to_string(),
+        vec!["ms".to_string()],
+        Engine::Native(set_timeout),
+    )));
+    shape.insert("set_timeout".to_string(), Value::Handle(set_timeout_ref));
+    shape
+}
+
+fn resolve_stream(vm: &mut Vm, _self: Option<Handle>) -> &mut NetStream {
+    let _self = _self.expect("net stream methods are bound to a stream");
+    if let MemObject::NativeStruct(NativeStruct::NetStream(ns)) = vm.memory.resolve_mut(&_self) {
+        ns
     } else {
-        if let Ok(stream) = TcpStream::connect(host.clone()) {
-            StreamKind::Plain(stream)
-        } else {
-            return Err(error::throw(
-                VMErrorType::Net(NetErrors::NetConnectError(format!("host {}", host))),
-                vm,
-            ));
+        unreachable!()
+    }
+}
+
+// applies the read timeout, if any, to a pending read
+async fn timed_read<T>(
+    timeout: Option<Duration>,
+    host: &str,
+    read: impl Future<Output = std::io::Result<T>>,
+) -> Result<T, NetErrors> {
+    let read = match timeout {
+        Some(timeout) => {
+            tokio::time::timeout(timeout, read)
+                .await
+                .map_err(|_| NetErrors::Timeout {
+                    host: host.to_string(),
+                    ms: timeout.as_millis() as u64,
+                })?
         }
+        None => read.await,
     };
+    read.map_err(|_| NetErrors::ReadError(host.to_string()))
+}
 
-    let mut shape = HashMap::new();
-    let owned_host = host.clone();
-    let host_ref = put_string(vm, host.clone());
-    let write_ref = vm.memory.alloc(MemObject::Function(Function::new(
-        "write".to_string(),
-        vec![],
-        Engine::Native(write),
-    )));
-    let read_ref = vm.memory.alloc(MemObject::Function(Function::new(
-        "read".to_string(),
-        vec![],
-        Engine::Native(read),
-    )));
+// reads what is available up to max bytes, an empty read means
+// the peer closed the connection
+async fn read_stream(vm: &mut Vm, _self: Option<Handle>, max: usize) -> Result<Vec<u8>, VMError> {
+    let net_stream = resolve_stream(vm, _self);
+    let (host, stream, timeout) = (
+        net_stream.host.clone(),
+        net_stream.stream.clone(),
+        net_stream.read_timeout,
+    );
+    let mut stream = stream
+        .try_lock_owned()
+        .map_err(|_| net_error(NetErrors::Busy(host.clone()), vm))?;
 
-    shape.insert("host".to_string(), Value::Handle(host_ref));
-    shape.insert("write".to_string(), Value::Handle(write_ref));
-    shape.insert("read".to_string(), Value::Handle(read_ref));
+    let mut buffer = vec![0; max];
+    let read = vm
+        .with_events(async {
+            let stream = stream
+                .as_mut()
+                .ok_or_else(|| NetErrors::Closed(host.clone()))?;
+            timed_read(timeout, &host, stream.read(&mut buffer)).await
+        })
+        .await?
+        .map_err(|e| net_error(e, vm))?;
+
+    buffer.truncate(read);
+    Ok(buffer)
+}
 
-    let net_stream = NetStream::new(owned_host, stream, shape);
-    let net_stream_ref = vm
-        .memory
-        .alloc(MemObject::NativeStruct(NativeStruct::NetStream(net_stream)));
+async fn write_stream(vm: &mut Vm, _self: Option<Handle>, data: &[u8]) -> Result<(), VMError> {
+    let net_stream = resolve_stream(vm, _self);
+    let (host, stream) = (net_stream.host.clone(), net_stream.stream.clone());
+    let mut stream = stream
+        .try_lock_owned()
+        .map_err(|_| net_error(NetErrors::Busy(host.clone()), vm))?;
 
-    return Ok(Value::Handle(net_stream_ref));
+    vm.with_events(async {
+        let stream = stream
+            .as_mut()
+            .ok_or_else(|| NetErrors::Closed(host.clone()))?;
+        stream
+            .write_all(data)
+            .await
+            .map_err(|_| NetErrors::WriteError(host.clone()))
+    })
+    .await?
+    .map_err(|e| net_error(e, vm))
 }
 
 fn write(
@@ -96,64 +216,116 @@ fn write(
     _self: Option<Handle>,
     params: Vec<Value>,
     debug: bool,
-) -> Result<Value, VMError> {
-    // get params
-    let data = params[0].as_string_obj(vm)?;
-    // resolve 'self'
-    let _self = if let Some(_this) = _self {
-        if let MemObject::NativeStruct(NativeStruct::NetStream(ns)) = vm.memory.resolve_mut(&_this)
-        {
-            ns
-        } else {
-            unreachable!()
+) -> BoxFuture<'_, Result<Value, VMError>> {
+    Box::pin(async move {
+        let data = params[0].as_string_obj(vm)?;
+        if debug {
+            vm.trace(format!("NET.WRITE <- {} bytes", data.len()));
         }
-    } else {
-        unreachable!()
-    };
+        write_stream(vm, _self, data.as_bytes()).await?;
+        Ok(Value::RawValue(RawValue::U64(U64::new(data.len() as u64))))
+    })
+}
 
-    let write_result = _self.stream.write(data.as_bytes());
-    if let Ok(bytes) = write_result {
-        Ok(Value::RawValue(RawValue::U64(U64::new(bytes as u64))))
-    } else {
-        Err(error::throw(
-            VMErrorType::Net(NetErrors::WriteError(_self.host.to_string())),
+fn read(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    _params: Vec<Value>,
+    debug: bool,
+) -> BoxFuture<'_, Result<Value, VMError>> {
+    Box::pin(async move {
+        let buffer = read_stream(vm, _self, READ_SIZE).await?;
+        if debug {
+            vm.trace(format!("NET.READ -> {} bytes", buffer.len()));
+        }
+        Ok(Value::Handle(put_string(
             vm,
-        ))
-    }
+            String::from_utf8_lossy(&buffer).to_string(),
+        )))
+    })
 }
 
-fn read(
+fn write_bytes(
     vm: &mut Vm,
     _self: Option<Handle>,
     params: Vec<Value>,
     debug: bool,
-) -> Result<Value, VMError> {
-    // resolve 'self'
-    let _self = if let Some(_this) = _self {
-        if let MemObject::NativeStruct(NativeStruct::NetStream(ns)) = vm.memory.resolve_mut(&_this)
-        {
-            ns
-        } else {
-            unreachable!()
+) -> BoxFuture<'_, Result<Value, VMError>> {
+    Box::pin(async move {
+        let data = params[0].as_bytes_obj(vm)?;
+        if debug {
+            vm.trace(format!("NET.WRITE_BYTES <- {} bytes", data.len()));
         }
-    } else {
-        unreachable!()
-    };
+        write_stream(vm, _self, &data).await?;
+        Ok(Value::RawValue(RawValue::U64(U64::new(data.len() as u64))))
+    })
+}
 
-    let mut buffer = [0; 4096];
-    let read_result = _self.stream.read(&mut buffer);
-    let bytes_count = if let Ok(bytes_count) = read_result {
-        bytes_count
-    } else {
-        return Err(error::throw(
-            VMErrorType::Net(NetErrors::ReadError(_self.host.to_string())),
-            vm,
-        ));
+// read_bytes() or read_bytes(max), reads what is available up
+// to max bytes (4096 by default)
+fn read_bytes(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> BoxFuture<'_, Result<Value, VMError>> {
+    Box::pin(async move {
+        let max = match params.first() {
+            Some(max) => max.as_usize(vm)?,
+            None => READ_SIZE,
+        };
+        let buffer = read_stream(vm, _self, max).await?;
+        if debug {
+            vm.trace(format!(
+                "NET.READ_BYTES -> {} bytes (max {})",
+                buffer.len(),
+                max
+            ));
+        }
+        Ok(Value::Handle(put_bytes(vm, buffer)))
+    })
+}
+
+// set_timeout(ms), reads fail once they wait longer. 0 disables it
+fn set_timeout(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let ms = params[0].as_usize(vm)? as u64;
+    let net_stream = resolve_stream(vm, _self);
+    net_stream.read_timeout = match ms {
+        0 => None,
+        ms => Some(Duration::from_millis(ms)),
     };
-    Ok(Value::Handle(put_string(
-        vm,
-        String::from_utf8_lossy(&buffer[..bytes_count]).to_string(),
-    )))
+    if debug {
+        vm.trace(format!("NET.SET_TIMEOUT <- {}ms", ms));
+    }
+    Ok(Value::RawValue(RawValue::Nothing))
+}
+
+fn close(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    _params: Vec<Value>,
+    debug: bool,
+) -> BoxFuture<'_, Result<Value, VMError>> {
+    Box::pin(async move {
+        let net_stream = resolve_stream(vm, _self);
+        let (host, stream) = (net_stream.host.clone(), net_stream.stream.clone());
+        if debug {
+            vm.trace(format!("NET.CLOSE <- {}", host));
+        }
+        let mut stream = stream
+            .try_lock_owned()
+            .map_err(|_| net_error(NetErrors::Busy(host.clone()), vm))?;
+        // the peer may be gone already, there is nothing to report then
+        if let Some(mut stream) = stream.take() {
+            let _ = stream.shutdown().await;
+        }
+        Ok(Value::RawValue(RawValue::Nothing))
+    })
 }
 
 ///// listen
@@ -161,81 +333,442 @@ pub fn listen_ref() -> MemObject {
     MemObject::Function(Function::new(
         "listen".to_string(),
         vec!["port".to_string()],
-        Engine::Native(listen),
+        Engine::NativeAsync(listen),
     ))
 }
 
+// listen(port) or listen(port, options)
 pub fn listen(
     vm: &mut Vm,
     _self: Option<Handle>,
     params: Vec<Value>,
     debug: bool,
-) -> Result<Value, VMError> {
-    let port = params[0].as_string_obj(vm)?;
-    let host = format!("127.0.0.1:{}", port);
-    let server = match TcpListener::bind(host.clone()) {
-        Ok(v) => v,
-        Err(err) => panic!("cannot listen on the provided port"),
-    };
+) -> BoxFuture<'_, Result<Value, VMError>> {
+    Box::pin(async move {
+        let port = port_param(vm, &params[0])?;
+        let options = listen_options(vm, params.get(1))?;
+        let host = format!("{}:{}", options.host, port);
 
+        let tls = options
+            .tls_acceptor()
+            .map_err(|e| net_error(NetErrors::TlsError(e), vm))?;
+        if debug {
+            vm.trace(format!("NET.LISTEN <- {} (tls: {})", host, tls.is_some()));
+        }
+
+        let listener = TcpListener::bind(&host).await.map_err(|e| {
+            net_error(
+                NetErrors::ListenError {
+                    host: host.clone(),
+                    message: e.to_string(),
+                },
+                vm,
+            )
+        })?;
+
+        let shape = server_shape(vm, &host);
+        let net_server = NetServer::new(host, ListenerKind::Tcp(listener), tls, shape);
+        Ok(Value::Handle(vm.memory.alloc(MemObject::NativeStruct(
+            NativeStruct::NetServer(net_server),
+        ))))
+    })
+}
+
+// host and methods of a NetServer
+fn server_shape(vm: &mut Vm, host: &str) -> HashMap<String, Value> {
     let mut shape = HashMap::new();
+    let host_ref = put_string(vm, host.to_string());
+    shape.insert("host".to_string(), Value::Handle(host_ref));
     let accept_ref = vm.memory.alloc(MemObject::Function(Function::new(
         "accept".to_string(),
         vec![],
-        Engine::Native(accept),
+        Engine::NativeAsync(accept),
     )));
     shape.insert("accept".to_string(), Value::Handle(accept_ref));
+    let close_ref = vm.memory.alloc(MemObject::Function(Function::new(
+        "close".to_string(),
+        vec![],
+        Engine::Native(close_server),
+    )));
+    shape.insert("close".to_string(), Value::Handle(close_ref));
+    shape
+}
 
-    let net_server = NetServer::new(server, shape);
-    return Ok(Value::Handle(vm.memory.alloc(MemObject::NativeStruct(
-        NativeStruct::NetServer(net_server),
-    ))));
+fn resolve_server(vm: &mut Vm, _self: Option<Handle>) -> &mut NetServer {
+    let _self = _self.expect("net server methods are bound to a server");
+    if let MemObject::NativeStruct(NativeStruct::NetServer(ns)) = vm.memory.resolve_mut(&_self) {
+        ns
+    } else {
+        unreachable!()
+    }
 }
 
+// waits for the next connection, the tls handshake is done
+// before handing the stream back
 fn accept(
     vm: &mut Vm,
     _self: Option<Handle>,
-    params: Vec<Value>,
+    _params: Vec<Value>,
+    debug: bool,
+) -> BoxFuture<'_, Result<Value, VMError>> {
+    Box::pin(async move {
+        let server = resolve_server(vm, _self);
+        let (host, listener, acceptor) = (
+            server.host.clone(),
+            server.listener.clone(),
+            server.tls.clone(),
+        );
+        let listener = listener.ok_or_else(|| net_error(NetErrors::Closed(host.clone()), vm))?;
+
+        let (stream, peer) = vm
+            .with_events(async {
+                let (stream, peer) = listener
+                    .accept(&host)
+                    .await
+                    .map_err(|e| NetErrors::AcceptError(format!("{}: {}", host, e)))?;
+                let stream = match (acceptor, stream) {
+                    (Some(acceptor), StreamKind::Plain(stream)) => {
+                        let handshake = acceptor.accept(stream);
+                        let stream = tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, handshake)
+                            .await
+                            .map_err(|_| {
+                                NetErrors::TlsError(format!(
+                                    "{}: no handshake after {}s",
+                                    peer,
+                                    TLS_HANDSHAKE_TIMEOUT.as_secs()
+                                ))
+                            })?
+                            .map_err(|e| NetErrors::TlsError(format!("{}: {}", peer, e)))?;
+                        StreamKind::Tls(Box::new(TlsStream::Server(stream)))
+                    }
+                    (_, stream) => stream,
+                };
+                Ok((stream, peer))
+            })
+            .await?
+            .map_err(|e| net_error(e, vm))?;
+
+        if debug {
+            vm.trace(format!("NET.ACCEPT -> {}", peer));
+        }
+        let shape = stream_shape(vm, &peer);
+        let net_stream = NetStream::new(peer, stream, shape);
+        let net_stream_ref = vm
+            .memory
+            .alloc(MemObject::NativeStruct(NativeStruct::NetStream(net_stream)));
+        Ok(Value::Handle(net_stream_ref))
+    })
+}
+
+// stops listening, accepted streams stay open
+fn close_server(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    _params: Vec<Value>,
     debug: bool,
 ) -> Result<Value, VMError> {
-    // resolve 'self'
-    let _self = if let Some(_this) = _self {
-        if let MemObject::NativeStruct(NativeStruct::NetServer(ns)) = vm.memory.resolve_mut(&_this)
-        {
-            ns
-        } else {
-            unreachable!()
+    let server = resolve_server(vm, _self);
+    let listener = server.listener.take();
+    let host = server.host.clone();
+    if debug {
+        vm.trace(format!("NET.SERVER.CLOSE <- {}", host));
+    }
+    // unix sockets leave their file behind
+    #[cfg(unix)]
+    if let Some(ListenerKind::Unix(_)) = listener.as_deref() {
+        let _ = std::fs::remove_file(&host);
+    }
+    Ok(Value::RawValue(RawValue::Nothing))
+}
+
+///// unix sockets
+pub fn unix_connect_ref() -> MemObject {
+    MemObject::Function(Function::new(
+        "unix_connect".to_string(),
+        vec!["path".to_string()],
+        Engine::NativeAsync(unix_connect),
+    ))
+}
+
+#[cfg(unix)]
+async fn connect_unix(path: &str) -> std::io::Result<StreamKind> {
+    UnixStream::connect(path).await.map(StreamKind::Unix)
+}
+
+#[cfg(not(unix))]
+async fn connect_unix(_path: &str) -> std::io::Result<StreamKind> {
+    Err(std::io::ErrorKind::Unsupported.into())
+}
+
+// the stream has the same methods as the tcp one
+pub fn unix_connect(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> BoxFuture<'_, Result<Value, VMError>> {
+    Box::pin(async move {
+        let path = params[0].as_string_obj(vm)?;
+        if debug {
+            vm.trace(format!("NET.UNIX_CONNECT <- {}", path));
+        }
+
+        let stream = vm.with_events(connect_unix(&path)).await?.map_err(|e| {
+            net_error(
+                NetErrors::NetConnectError(format!("socket {}: {}", path, e)),
+                vm,
+            )
+        })?;
+
+        let shape = stream_shape(vm, &path);
+        let net_stream = NetStream::new(path, stream, shape);
+        let net_stream_ref = vm
+            .memory
+            .alloc(MemObject::NativeStruct(NativeStruct::NetStream(net_stream)));
+        Ok(Value::Handle(net_stream_ref))
+    })
+}
+
+pub fn unix_listen_ref() -> MemObject {
+    MemObject::Function(Function::new(
+        "unix_listen".to_string(),
+        vec!["path".to_string()],
+        Engine::NativeAsync(unix_listen),
+    ))
+}
+
+#[cfg(unix)]
+fn bind_unix(path: &str) -> std::io::Result<ListenerKind> {
+    UnixListener::bind(path).map(ListenerKind::Unix)
+}
+
+#[cfg(not(unix))]
+fn bind_unix(_path: &str) -> std::io::Result<ListenerKind> {
+    Err(std::io::ErrorKind::Unsupported.into())
+}
+
+// the socket file is removed on close()
+pub fn unix_listen(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> BoxFuture<'_, Result<Value, VMError>> {
+    Box::pin(async move {
+        let path = params[0].as_string_obj(vm)?;
+        if debug {
+            vm.trace(format!("NET.UNIX_LISTEN <- {}", path));
         }
+
+        let listener = bind_unix(&path).map_err(|e| {
+            net_error(
+                NetErrors::ListenError {
+                    host: path.clone(),
+                    message: e.to_string(),
+                },
+                vm,
+            )
+        })?;
+
+        let shape = server_shape(vm, &path);
+        let net_server = NetServer::new(path, listener, None, shape);
+        Ok(Value::Handle(vm.memory.alloc(MemObject::NativeStruct(
+            NativeStruct::NetServer(net_server),
+        ))))
+    })
+}
+
+///// udp
+pub fn udp_bind_ref() -> MemObject {
+    MemObject::Function(Function::new(
+        "udp_bind".to_string(),
+        vec!["addr".to_string()],
+        Engine::NativeAsync(udp_bind),
+    ))
+}
+
+// udp_bind("127.0.0.1:0") binds to a free port, the bound
+// address is in the addr field
+pub fn udp_bind(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> BoxFuture<'_, Result<Value, VMError>> {
+    Box::pin(async move {
+        let addr = params[0].as_string_obj(vm)?;
+        let bind_error = |message: String, vm: &Vm| {
+            net_error(
+                NetErrors::ListenError {
+                    host: addr.clone(),
+                    message,
+                },
+                vm,
+            )
+        };
+        let socket = UdpSocket::bind(&addr)
+            .await
+            .map_err(|e| bind_error(e.to_string(), vm))?;
+        let bound = socket
+            .local_addr()
+            .map_err(|e| bind_error(e.to_string(), vm))?
+            .to_string();
+        if debug {
+            vm.trace(format!("NET.UDP_BIND <- {} -> {}", addr, bound));
+        }
+
+        let mut shape = HashMap::new();
+        let addr_ref = put_string(vm, bound.clone());
+        shape.insert("addr".to_string(), Value::Handle(addr_ref));
+        let methods: [(&str, AsyncMethod); 2] = [("send_to", send_to), ("recv_from", recv_from)];
+        for (name, method) in methods {
+            let method_ref = vm.memory.alloc(MemObject::Function(Function::new(
+                name.to_string(),
+                vec![],
+                Engine::NativeAsync(method),
+            )));
+            shape.insert(name.to_string(), Value::Handle(method_ref));
+        }
+        let sync_methods: [(&str, NativeMethod); 2] =
+            [("set_timeout", udp_set_timeout), ("close", udp_close)];
+        for (name, method) in sync_methods {
+            let method_ref = vm.memory.alloc(MemObject::Function(Function::new(
+                name.to_string(),
+                vec![],
+                Engine::Native(method),
+            )));
+            shape.insert(name.to_string(), Value::Handle(method_ref));
+        }
+
+        let udp_socket = NetUdpSocket::new(bound, socket, shape);
+        Ok(Value::Handle(vm.memory.alloc(MemObject::NativeStruct(
+            NativeStruct::NetUdpSocket(udp_socket),
+        ))))
+    })
+}
+
+fn resolve_udp(vm: &mut Vm, _self: Option<Handle>) -> &mut NetUdpSocket {
+    let _self = _self.expect("udp socket methods are bound to a socket");
+    if let MemObject::NativeStruct(NativeStruct::NetUdpSocket(s)) = vm.memory.resolve_mut(&_self) {
+        s
     } else {
         unreachable!()
-    };
+    }
+}
 
-    let (stream, sock_addr) = match _self.listener.accept() {
-        Ok(v) => v,
-        Err(err) => {
-            panic!("test")
+// send_to(data, addr), data can be a string or bytes
+fn send_to(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> BoxFuture<'_, Result<Value, VMError>> {
+    Box::pin(async move {
+        let (Some(data), Some(target)) = (params.first(), params.get(1)) else {
+            return Err(error::throw(
+                VMErrorType::TypeError(TypeError::InvalidArgsCount {
+                    expected: 2,
+                    received: params.len() as u32,
+                }),
+                vm,
+            ));
+        };
+        let data = match data.as_bytes_obj(vm) {
+            Ok(data) => data,
+            Err(_) => data.as_string_obj(vm)?.into_bytes(),
+        };
+        let target = target.as_string_obj(vm)?;
+        let udp_socket = resolve_udp(vm, _self);
+        let socket = udp_socket.socket.clone();
+        if debug {
+            vm.trace(format!("NET.SEND_TO <- {} ({} bytes)", target, data.len()));
         }
+        let socket = socket.ok_or_else(|| net_error(NetErrors::Closed(target.clone()), vm))?;
+
+        let sent = vm
+            .with_events(socket.send_to(&data, &target))
+            .await?
+            .map_err(|_| net_error(NetErrors::WriteError(target), vm))?;
+        Ok(Value::RawValue(RawValue::U64(U64::new(sent as u64))))
+    })
+}
+
+// recv_from() or recv_from(max), waits for a datagram and returns
+// { data, from }, data being bytes
+fn recv_from(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> BoxFuture<'_, Result<Value, VMError>> {
+    Box::pin(async move {
+        let max = match params.first() {
+            Some(max) => max.as_usize(vm)?,
+            None => READ_SIZE,
+        };
+        let udp_socket = resolve_udp(vm, _self);
+        let (addr, socket, timeout) = (
+            udp_socket.addr.clone(),
+            udp_socket.socket.clone(),
+            udp_socket.read_timeout,
+        );
+        let socket = socket.ok_or_else(|| net_error(NetErrors::Closed(addr.clone()), vm))?;
+
+        let mut buffer = vec![0; max];
+        let (read, from) = vm
+            .with_events(timed_read(timeout, &addr, socket.recv_from(&mut buffer)))
+            .await?
+            .map_err(|e| net_error(e, vm))?;
+        buffer.truncate(read);
+        if debug {
+            vm.trace(format!("NET.RECV_FROM -> {} ({} bytes)", from, read));
+        }
+
+        let mut fields = HashMap::new();
+        fields.insert("data".to_string(), Value::Handle(put_bytes(vm, buffer)));
+        fields.insert(
+            "from".to_string(),
+            Value::Handle(put_string(vm, from.to_string())),
+        );
+        Ok(Value::Handle(vm.memory.alloc(MemObject::StructLiteral(
+            StructLiteral::new("UdpMessage".to_string(), fields),
+        ))))
+    })
+}
+
+fn udp_set_timeout(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let ms = params
+        .first()
+        .map(|ms| ms.as_usize(vm))
+        .transpose()?
+        .unwrap_or(0) as u64;
+    let udp_socket = resolve_udp(vm, _self);
+    udp_socket.read_timeout = match ms {
+        0 => None,
+        ms => Some(Duration::from_millis(ms)),
     };
+    if debug {
+        vm.trace(format!("NET.UDP.SET_TIMEOUT <- {}ms", ms));
+    }
+    Ok(Value::RawValue(RawValue::Nothing))
+}
 
-    let host = sock_addr.to_string();
-    let mut shape = HashMap::new();
-    let host_ref = put_string(vm, host.clone());
-    let write_ref = vm.memory.alloc(MemObject::Function(Function::new(
-        "write".to_string(),
-        vec![],
-        Engine::Native(write),
-    )));
-    let read_ref = vm.memory.alloc(MemObject::Function(Function::new(
-        "read".to_string(),
-        vec![],
-        Engine::Native(read),
-    )));
-    shape.insert("host".to_string(), Value::Handle(host_ref));
-    shape.insert("write".to_string(), Value::Handle(write_ref));
-    shape.insert("read".to_string(), Value::Handle(read_ref));
-    let net_stream = NetStream::new(host, StreamKind::Plain(stream), shape);
-    let net_stream_ref = vm
-        .memory
-        .alloc(MemObject::NativeStruct(NativeStruct::NetStream(net_stream)));
-    return Ok(Value::Handle(net_stream_ref));
+fn udp_close(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    _params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let udp_socket = resolve_udp(vm, _self);
+    udp_socket.socket = None;
+    let addr = udp_socket.addr.clone();
+    if debug {
+        vm.trace(format!("NET.UDP.CLOSE <- {}", addr));
+    }
+    Ok(Value::RawValue(RawValue::Nothing))
 }
@@ -4,7 +4,7 @@ pub mod utils;
 
 use crate::{
     memory::MemObject,
-    std::net::members::{connect_ref, listen_ref},
+    std::net::members::{connect_ref, listen_ref, udp_bind_ref, unix_connect_ref, unix_listen_ref},
 };
 
 pub fn generate_struct() -> (String, Vec<(String, MemObject)>) {
@@ -12,6 +12,9 @@ pub fn generate_struct() -> (String, Vec<(String, MemObject)>) {
 
     fields.push(("connect".to_string(), connect_ref()));
     fields.push(("listen".to_string(), listen_ref()));
+    fields.push(("unix_connect".to_string(), unix_connect_ref()));
+    fields.push(("unix_listen".to_string(), unix_listen_ref()));
+    fields.push(("udp_bind".to_string(), udp_bind_ref()));
 
     ("net".to_string(), fields)
 }
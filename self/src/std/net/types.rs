@@ -1,69 +1,129 @@
-use std::{
-    collections::HashMap,
-    net::{TcpListener, TcpStream},
-};
-
-use rustls::{ClientConnection, StreamOwned};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
-use crate::{
-    types::{object::structs::StructLiteral, Value},
-    vm::Vm,
+#[cfg(unix)]
+use tokio::net::{UnixListener, UnixStream};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream, UdpSocket},
+    sync::Mutex,
 };
+use tokio_rustls::{TlsAcceptor, TlsStream};
+
+use crate::types::{object::structs::StructLiteral, Value};
 
 #[derive(Debug)]
 pub enum StreamKind {
     Plain(TcpStream),
-    Tls(StreamOwned<ClientConnection, TcpStream>),
+    // client (net.connect) or server (accepted by a tls server) side
+    Tls(Box<TlsStream<TcpStream>>),
+    #[cfg(unix)]
+    Unix(UnixStream),
 }
 
-use std::io::{Read, Write};
-
-impl Read for StreamKind {
-    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+impl StreamKind {
+    pub async fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
         match self {
-            StreamKind::Plain(s) => s.read(buf),
-            StreamKind::Tls(s) => s.read(buf),
+            StreamKind::Plain(s) => s.read(buf).await,
+            StreamKind::Tls(s) => s.read(buf).await,
+            #[cfg(unix)]
+            StreamKind::Unix(s) => s.read(buf).await,
         }
     }
-}
-impl Write for StreamKind {
-    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+
+    pub async fn write_all(&mut self, buf: &[u8]) -> std::io::Result<()> {
         match self {
-            StreamKind::Plain(s) => s.write(buf),
-            StreamKind::Tls(s) => s.write(buf),
+            StreamKind::Plain(s) => s.write_all(buf).await,
+            StreamKind::Tls(s) => {
+                s.write_all(buf).await?;
+                // rustls buffers the records until flushed
+                s.flush().await
+            }
+            #[cfg(unix)]
+            StreamKind::Unix(s) => s.write_all(buf).await,
         }
     }
-    fn flush(&mut self) -> std::io::Result<()> {
+
+    pub async fn shutdown(&mut self) -> std::io::Result<()> {
         match self {
-            StreamKind::Plain(s) => s.flush(),
-            StreamKind::Tls(s) => s.flush(),
+            StreamKind::Plain(s) => s.shutdown().await,
+            StreamKind::Tls(s) => s.shutdown().await,
+            #[cfg(unix)]
+            StreamKind::Unix(s) => s.shutdown().await,
         }
     }
 }
 
 #[derive(Debug)]
+pub enum ListenerKind {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener),
+}
+
+impl ListenerKind {
+    // accepted stream and its peer, unix peers are usually unnamed
+    // so the socket path is used instead
+    pub async fn accept(&self, host: &str) -> std::io::Result<(StreamKind, String)> {
+        match self {
+            ListenerKind::Tcp(l) => {
+                let (stream, peer) = l.accept().await?;
+                Ok((StreamKind::Plain(stream), peer.to_string()))
+            }
+            #[cfg(unix)]
+            ListenerKind::Unix(l) => {
+                let (stream, _) = l.accept().await?;
+                Ok((StreamKind::Unix(stream), host.to_string()))
+            }
+        }
+    }
+}
+
 pub struct NetServer {
-    pub listener: TcpListener,
+    // address or socket path
+    pub host: String,
+    // none once closed
+    pub listener: Option<Arc<ListenerKind>>,
+    pub tls: Option<TlsAcceptor>,
     pub shape: StructLiteral,
 }
 
+impl std::fmt::Debug for NetServer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NetServer")
+            .field("host", &self.host)
+            .field("listener", &self.listener)
+            .field("tls", &self.tls.is_some())
+            .finish()
+    }
+}
+
 impl NetServer {
-    pub fn new(listener: TcpListener, shape: HashMap<String, Value>) -> NetServer {
+    pub fn new(
+        host: String,
+        listener: ListenerKind,
+        tls: Option<TlsAcceptor>,
+        shape: HashMap<String, Value>,
+    ) -> NetServer {
         NetServer {
-            listener,
+            host,
+            listener: Some(Arc::new(listener)),
+            tls,
             shape: StructLiteral::new("NetServer".to_string(), shape),
         }
     }
 
     pub fn to_string(&self) -> String {
-        "NetServer".to_string()
+        format!("NetServer({})", self.host)
     }
 }
 
 #[derive(Debug)]
 pub struct NetStream {
     pub host: String,
-    pub stream: StreamKind,
+    // shared with the pending reads and writes, so the vm can keep
+    // handling events meanwhile. none once closed
+    pub stream: Arc<Mutex<Option<StreamKind>>>,
+    pub read_timeout: Option<Duration>,
     pub shape: StructLiteral,
 }
 
@@ -71,25 +131,38 @@ impl NetStream {
     pub fn new(host: String, stream: StreamKind, shape: HashMap<String, Value>) -> NetStream {
         NetStream {
             host,
-            stream,
+            stream: Arc::new(Mutex::new(Some(stream))),
+            read_timeout: None,
             shape: StructLiteral::new("NetStream".to_string(), shape),
         }
     }
 
-    pub fn new_initialized(
-        host: String,
-        stream: StreamKind,
-        shape: HashMap<String, Value>,
-        vm: &Vm,
-    ) -> NetStream {
-        NetStream {
-            host,
-            stream,
-            shape: StructLiteral::new("NetStream".to_string(), shape),
+    pub fn to_string(&self) -> String {
+        format!("NetStream({})", self.host)
+    }
+}
+
+// socket created by `net.udp_bind`
+#[derive(Debug)]
+pub struct NetUdpSocket {
+    pub addr: String,
+    // none once closed
+    pub socket: Option<Arc<UdpSocket>>,
+    pub read_timeout: Option<Duration>,
+    pub shape: StructLiteral,
+}
+
+impl NetUdpSocket {
+    pub fn new(addr: String, socket: UdpSocket, shape: HashMap<String, Value>) -> NetUdpSocket {
+        NetUdpSocket {
+            addr,
+            socket: Some(Arc::new(socket)),
+            read_timeout: None,
+            shape: StructLiteral::new("NetUdpSocket".to_string(), shape),
         }
     }
 
     pub fn to_string(&self) -> String {
-        "NetStream".to_string()
+        format!("NetUdpSocket({})", self.addr)
     }
 }
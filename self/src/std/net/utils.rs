@@ -1,15 +1,28 @@
-use rustls::pki_types::ServerName;
-use rustls::{ClientConfig, ClientConnection, StreamOwned};
-use std::net::TcpStream;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+use rustls::{ClientConfig, ServerConfig};
 use std::sync::Arc;
+use tokio::net::TcpStream;
+use tokio_rustls::{TlsAcceptor, TlsConnector, TlsStream};
 
-pub fn tls(host_with_port: &str) -> Result<StreamOwned<ClientConnection, TcpStream>, String> {
+use crate::{
+    core::error::VMError,
+    types::{raw::RawValue, Value},
+    vm::Vm,
+};
+
+// only sets the crypto provider on the first execution
+fn install_crypto_provider() {
+    let _ = rustls::crypto::aws_lc_rs::default_provider().install_default();
+}
+
+pub async fn tls(host_with_port: &str) -> Result<TlsStream<TcpStream>, String> {
     let (domain, _) = host_with_port
         .rsplit_once(':')
         .ok_or("host must include the port")?;
 
     let roots = rustls::RootCertStore::from_iter(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
-    let _ = rustls::crypto::aws_lc_rs::default_provider().install_default(); // only sets the crypto provider on the first execution
+    install_crypto_provider();
 
     let cfg = Arc::new(
         ClientConfig::builder()
@@ -18,8 +31,83 @@ pub fn tls(host_with_port: &str) -> Result<StreamOwned<ClientConnection, TcpStre
     );
 
     // tcp + tls
-    let tcp = TcpStream::connect(host_with_port).map_err(|e| e.to_string())?;
+    let tcp = TcpStream::connect(host_with_port)
+        .await
+        .map_err(|e| e.to_string())?;
     let sn = ServerName::try_from(domain.to_owned()).map_err(|e| e.to_string())?;
-    let conn = ClientConnection::new(cfg, sn).map_err(|e| e.to_string())?;
-    Ok(StreamOwned::new(conn, tcp))
+    let stream = TlsConnector::from(cfg)
+        .connect(sn, tcp)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(TlsStream::Client(stream))
+}
+
+// acceptor for a tls server, from a PEM certificate chain and key
+pub fn tls_acceptor(cert_path: &str, key_path: &str) -> Result<TlsAcceptor, String> {
+    let certs = CertificateDer::pem_file_iter(cert_path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| format!("cannot read certificate {}: {}", cert_path, e))?;
+    let key = PrivateKeyDer::from_pem_file(key_path)
+        .map_err(|e| format!("cannot read key {}: {}", key_path, e))?;
+    install_crypto_provider();
+
+    let cfg = ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|e| e.to_string())?;
+    Ok(TlsAcceptor::from(Arc::new(cfg)))
+}
+
+// options struct: { host, cert, key }, cert and key are PEM files
+// and turn the server into a tls one
+pub struct ListenOptions {
+    pub host: String,
+    pub cert: Option<String>,
+    pub key: Option<String>,
+}
+
+impl ListenOptions {
+    // none for a plain server
+    pub fn tls_acceptor(&self) -> Result<Option<TlsAcceptor>, String> {
+        match (&self.cert, &self.key) {
+            (Some(cert), Some(key)) => tls_acceptor(cert, key).map(Some),
+            (None, None) => Ok(None),
+            _ => Err("a tls server needs both cert and key".to_string()),
+        }
+    }
+}
+
+pub fn listen_options(vm: &Vm, value: Option<&Value>) -> Result<ListenOptions, VMError> {
+    let mut options = ListenOptions {
+        host: "127.0.0.1".to_string(),
+        cert: None,
+        key: None,
+    };
+    let Some(value) = value else {
+        return Ok(options);
+    };
+    if let Value::RawValue(RawValue::Nothing) = value {
+        return Ok(options);
+    }
+    let fields = value.as_struct_obj(vm)?;
+    for (name, field) in &fields.fields {
+        if let Value::RawValue(RawValue::Nothing) = field {
+            continue;
+        }
+        match name.as_str() {
+            "host" => options.host = field.as_string_obj(vm)?,
+            "cert" => options.cert = Some(field.as_string_obj(vm)?),
+            "key" => options.key = Some(field.as_string_obj(vm)?),
+            _ => {}
+        }
+    }
+    Ok(options)
+}
+
+// ports are accepted as numbers or strings
+pub fn port_param(vm: &Vm, value: &Value) -> Result<String, VMError> {
+    match value {
+        Value::Handle(_) => value.as_string_obj(vm),
+        _ => Ok(value.as_usize(vm)?.to_string()),
+    }
 }
@@ -0,0 +1,403 @@
+use serde_json::Value as JsonValue;
+
+use crate::{
+    core::error::{self, os_errors::OsError, type_errors::TypeError, VMError, VMErrorType},
+    events::Event,
+    memory::{Handle, MemObject},
+    std::{
+        heap_utils::{put_string, put_vector},
+        NativeMember,
+    },
+    types::{
+        object::func::{Engine, Function},
+        raw::{u32::U32, RawValue},
+        Value,
+    },
+    vm::Vm,
+};
+
+fn os_error(err: OsError, vm: &Vm) -> VMError {
+    error::throw(VMErrorType::Os(err), vm)
+}
+
+fn args_count_error(expected: u32, received: usize, vm: &Vm) -> VMError {
+    error::throw(
+        VMErrorType::TypeError(TypeError::InvalidArgsCount {
+            expected,
+            received: received as u32,
+        }),
+        vm,
+    )
+}
+
+fn path_string(path: std::path::PathBuf, vm: &Vm) -> Result<String, VMError> {
+    match path.to_str() {
+        Some(path) => Ok(path.to_string()),
+        None => Err(os_error(
+            OsError::InvalidPath(format!("non utf8 path {}", path.display())),
+            vm,
+        )),
+    }
+}
+
+// get_cwd
+pub fn get_cwd_def() -> NativeMember {
+    NativeMember {
+        name: "get_cwd".to_string(),
+        description: "get the current working directory".to_string(),
+        params: None,
+    }
+}
+
+pub fn get_cwd_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "get_cwd".to_string(),
+        vec![],
+        Engine::Native(get_cwd),
+    ))
+}
+
+pub fn get_cwd(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    _params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let path =
+        std::env::current_dir().map_err(|e| os_error(OsError::InvalidPath(e.to_string()), vm))?;
+    let path = path_string(path, vm)?;
+    if debug {
+        vm.trace(format!("OS.GET_CWD -> {}", path));
+    }
+    Ok(Value::Handle(put_string(vm, path)))
+}
+
+// chdir
+pub fn chdir_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "chdir".to_string(),
+        vec!["path".to_string()],
+        Engine::Native(chdir),
+    ))
+}
+
+pub fn chdir(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let Some(path) = params.first() else {
+        return Err(args_count_error(1, params.len(), vm));
+    };
+    let path = path.as_string_obj(vm)?;
+    if debug {
+        vm.trace(format!("OS.CHDIR <- {}", path));
+    }
+    std::env::set_current_dir(&path).map_err(|e| {
+        os_error(
+            OsError::ChdirError {
+                path: path.clone(),
+                message: e.to_string(),
+            },
+            vm,
+        )
+    })?;
+    Ok(Value::RawValue(RawValue::Nothing))
+}
+
+// args
+pub fn args_def() -> NativeMember {
+    NativeMember {
+        name: "args".to_string(),
+        description: "arguments passed to the script after its path".to_string(),
+        params: None,
+    }
+}
+
+pub fn args_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "args".to_string(),
+        vec![],
+        Engine::Native(args),
+    ))
+}
+
+pub fn args(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    _params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    if debug {
+        vm.trace(format!("OS.ARGS -> {:?}", vm.script_args));
+    }
+    let args = vm.script_args.clone();
+    let args = args
+        .into_iter()
+        .map(|a| Value::Handle(put_string(vm, a)))
+        .collect();
+    Ok(Value::Handle(put_vector(vm, args)))
+}
+
+// exit
+pub fn exit_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "exit".to_string(),
+        vec![],
+        Engine::Native(exit),
+    ))
+}
+
+// the execution unwinds up to Vm::run, so pending cleanups (like
+// the mcp clients shutdown) still happen
+pub fn exit(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let code = match params.first() {
+        Some(Value::RawValue(RawValue::Nothing)) | None => 0,
+        Some(Value::RawValue(code)) if code.as_isize().is_some() => {
+            code.as_isize().unwrap_or(0) as i32
+        }
+        Some(code) => {
+            return Err(error::throw(
+                VMErrorType::TypeMismatch {
+                    expected: "number".to_string(),
+                    received: code.get_resolved_type(vm),
+                },
+                vm,
+            ))
+        }
+    };
+    if debug {
+        vm.trace(format!("OS.EXIT <- {}", code));
+    }
+    Err(error::throw(VMErrorType::Exit(code), vm))
+}
+
+// platform
+pub fn platform_def() -> NativeMember {
+    NativeMember {
+        name: "platform".to_string(),
+        description: "operating system name: linux, macos, windows...".to_string(),
+        params: None,
+    }
+}
+
+pub fn platform_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "platform".to_string(),
+        vec![],
+        Engine::Native(platform),
+    ))
+}
+
+pub fn platform(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    _params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    if debug {
+        vm.trace(format!("OS.PLATFORM -> {}", std::env::consts::OS));
+    }
+    Ok(Value::Handle(put_string(
+        vm,
+        std::env::consts::OS.to_string(),
+    )))
+}
+
+// pid
+pub fn pid_def() -> NativeMember {
+    NativeMember {
+        name: "pid".to_string(),
+        description: "id of the current process".to_string(),
+        params: None,
+    }
+}
+
+pub fn pid_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "pid".to_string(),
+        vec![],
+        Engine::Native(pid),
+    ))
+}
+
+pub fn pid(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    _params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let pid = std::process::id();
+    if debug {
+        vm.trace(format!("OS.PID -> {}", pid));
+    }
+    Ok(Value::RawValue(RawValue::U32(U32::new(pid))))
+}
+
+// hostname
+pub fn hostname_def() -> NativeMember {
+    NativeMember {
+        name: "hostname".to_string(),
+        description: "name of the host machine".to_string(),
+        params: None,
+    }
+}
+
+pub fn hostname_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "hostname".to_string(),
+        vec![],
+        Engine::Native(hostname),
+    ))
+}
+
+pub fn hostname(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    _params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let name = get_hostname().map_err(|e| os_error(OsError::HostnameError(e), vm))?;
+    if debug {
+        vm.trace(format!("OS.HOSTNAME -> {}", name));
+    }
+    Ok(Value::Handle(put_string(vm, name)))
+}
+
+#[cfg(unix)]
+fn get_hostname() -> Result<String, String> {
+    let mut buffer = [0u8; 256];
+    let result =
+        unsafe { libc::gethostname(buffer.as_mut_ptr() as *mut libc::c_char, buffer.len()) };
+    if result != 0 {
+        return Err(std::io::Error::last_os_error().to_string());
+    }
+    let end = buffer.iter().position(|b| *b == 0).unwrap_or(buffer.len());
+    Ok(String::from_utf8_lossy(&buffer[..end]).to_string())
+}
+
+#[cfg(not(unix))]
+fn get_hostname() -> Result<String, String> {
+    std::env::var("COMPUTERNAME").map_err(|e| e.to_string())
+}
+
+// temp_dir
+pub fn temp_dir_def() -> NativeMember {
+    NativeMember {
+        name: "temp_dir".to_string(),
+        description: "directory for temporary files".to_string(),
+        params: None,
+    }
+}
+
+pub fn temp_dir_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "temp_dir".to_string(),
+        vec![],
+        Engine::Native(temp_dir),
+    ))
+}
+
+pub fn temp_dir(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    _params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let path = path_string(std::env::temp_dir(), vm)?;
+    if debug {
+        vm.trace(format!("OS.TEMP_DIR -> {}", path));
+    }
+    Ok(Value::Handle(put_string(vm, path)))
+}
+
+// on_signal
+pub fn on_signal_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "on_signal".to_string(),
+        vec!["signal".to_string(), "callback".to_string()],
+        Engine::Native(on_signal),
+    ))
+}
+
+// the handler replaces the default behaviour of the signal, a
+// SIGINT handler has to call os.exit to stop the script
+pub fn on_signal(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let (Some(name), Some(callback)) = (params.first(), params.get(1)) else {
+        return Err(args_count_error(2, params.len(), vm));
+    };
+    let name = name.as_string_obj(vm)?.to_uppercase();
+    let name = if name.starts_with("SIG") {
+        name
+    } else {
+        format!("SIG{}", name)
+    };
+    let callback = callback.as_function_obj(vm)?;
+    if debug {
+        vm.trace(format!("OS.ON_SIGNAL <- {}", name));
+    }
+
+    let vm_notifier = vm.get_vm_notifier();
+    let mut signals = listen_signal(&name).map_err(|e| os_error(e, vm))?;
+    tokio::spawn(async move {
+        while signals.recv().await.is_some() {
+            let event = Event::Call(callback.clone(), vec![JsonValue::String(name.clone())]);
+            if vm_notifier.send(event).is_err() {
+                break;
+            }
+        }
+    });
+
+    Ok(Value::RawValue(RawValue::Nothing))
+}
+
+#[cfg(unix)]
+fn listen_signal(name: &str) -> Result<tokio::signal::unix::Signal, OsError> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let kind = match name {
+        "SIGINT" => SignalKind::interrupt(),
+        "SIGTERM" => SignalKind::terminate(),
+        "SIGHUP" => SignalKind::hangup(),
+        "SIGQUIT" => SignalKind::quit(),
+        "SIGUSR1" => SignalKind::user_defined1(),
+        "SIGUSR2" => SignalKind::user_defined2(),
+        "SIGWINCH" => SignalKind::window_change(),
+        "SIGCHLD" => SignalKind::child(),
+        "SIGPIPE" => SignalKind::pipe(),
+        "SIGALRM" => SignalKind::alarm(),
+        other => return Err(OsError::UnknownSignal(other.to_string())),
+    };
+    signal(kind).map_err(|e| OsError::SignalError {
+        signal: name.to_string(),
+        message: e.to_string(),
+    })
+}
+
+// only ctrl-c is available outside of unix
+#[cfg(not(unix))]
+fn listen_signal(name: &str) -> Result<tokio::sync::mpsc::UnboundedReceiver<()>, OsError> {
+    if name != "SIGINT" {
+        return Err(OsError::UnknownSignal(name.to_string()));
+    }
+    let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while tokio::signal::ctrl_c().await.is_ok() {
+            if sender.send(()).is_err() {
+                break;
+            }
+        }
+    });
+    Ok(receiver)
+}
@@ -1,60 +1,47 @@
-use crate::core::error::os_errors::OsError;
-use crate::core::error::{self, VMError, VMErrorType};
-use crate::memory::{Handle, MemObject};
-use crate::std::heap_utils::put_string;
-use crate::std::{NativeMember, NativeModuleDef};
-use crate::types::object::func::{Engine, Function};
-use crate::types::Value;
-use crate::vm::Vm;
+mod members;
 
-fn get_cwd(
-    vm: &mut Vm,
-    _self: Option<Handle>,
-    _params: Vec<Value>,
-    debug: bool,
-) -> Result<Value, VMError> {
-    match std::env::current_dir() {
-        Ok(path) => {
-            if let Some(path) = path.to_str() {
-                if debug {
-                    println!("OS.GET_CWD -> {}", path);
-                }
-                Ok(Value::Handle(put_string(vm, path.to_string())))
-            } else {
-                Err(error::throw(
-                    VMErrorType::Os(OsError::__placeholder("non utf8 path".to_string())),
-                    vm,
-                ))
-            }
-        }
-        Err(e) => Err(error::throw(
-            VMErrorType::Os(OsError::__placeholder(e.to_string())),
-            vm,
-        )),
-    }
-}
+use crate::{
+    memory::MemObject,
+    std::{
+        os::members::{
+            args_def, args_obj, chdir_obj, exit_obj, get_cwd_def, get_cwd_obj, hostname_def,
+            hostname_obj, on_signal_obj, pid_def, pid_obj, platform_def, platform_obj,
+            temp_dir_def, temp_dir_obj,
+        },
+        NativeModuleDef,
+    },
+};
 
 pub fn generate_struct() -> (String, Vec<(String, MemObject)>) {
-    (
-        "os".to_string(),
-        vec![(
-            "get_cwd".to_string(),
-            MemObject::Function(Function::new(
-                "get_cwd".to_string(),
-                vec![],
-                Engine::Native(get_cwd),
-            )),
-        )],
-    )
+    let mut fields = vec![];
+
+    fields.push(("get_cwd".to_string(), get_cwd_obj()));
+    fields.push(("chdir".to_string(), chdir_obj()));
+    fields.push(("args".to_string(), args_obj()));
+    fields.push(("exit".to_string(), exit_obj()));
+    fields.push(("platform".to_string(), platform_obj()));
+    fields.push(("pid".to_string(), pid_obj()));
+    fields.push(("hostname".to_string(), hostname_obj()));
+    fields.push(("temp_dir".to_string(), temp_dir_obj()));
+    fields.push(("on_signal".to_string(), on_signal_obj()));
+
+    ("os".to_string(), fields)
 }
 
+// chdir, exit and on_signal change the running script, they
+// are left out of the actions
 pub fn generate_mod_def() -> NativeModuleDef {
+    let members = vec![
+        get_cwd_def(),
+        args_def(),
+        platform_def(),
+        pid_def(),
+        hostname_def(),
+        temp_dir_def(),
+    ];
+
     NativeModuleDef {
         module: "os".to_string(),
-        members: vec![NativeMember {
-            name: "get_cwd".to_string(),
-            description: "get the current working directory".to_string(),
-            params: None,
-        }],
+        members,
     }
 }
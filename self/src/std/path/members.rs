@@ -0,0 +1,429 @@
+use std::path::{Path, PathBuf};
+
+use crate::core::error::{self, os_errors::OsError, VMError, VMErrorType};
+use crate::memory::{Handle, MemObject};
+use crate::std::heap_utils::{put_string, put_vector};
+use crate::std::NativeMember;
+use crate::types::object::func::{Engine, Function};
+use crate::types::raw::{bool::Bool, RawValue};
+use crate::types::Value;
+use crate::utils::path::{self as path_utils, normalize};
+use crate::vm::Vm;
+
+fn path_error(message: String, vm: &Vm) -> VMError {
+    error::throw(VMErrorType::Os(OsError::InvalidPath(message)), vm)
+}
+
+// paths are handed back as strings, non utf8 paths cannot be
+// represented so they are reported instead of panicking
+fn path_string(path: &Path, vm: &Vm) -> Result<String, VMError> {
+    match path.to_str() {
+        Some(path) => Ok(path.to_string()),
+        None => Err(path_error(format!("non utf8 path {}", path.display()), vm)),
+    }
+}
+
+fn path_value(path: &Path, vm: &mut Vm) -> Result<Value, VMError> {
+    let path = path_string(path, vm)?;
+    Ok(Value::Handle(put_string(vm, path)))
+}
+
+// join
+pub fn join_def() -> NativeMember {
+    NativeMember {
+        name: "join".to_string(),
+        description: "merges an arbitrary number of paths into a single path".to_string(),
+        params: Some(vec!["...path_segment".to_string()]),
+    }
+}
+
+pub fn join_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "join".to_string(),
+        vec!["...path_segment".to_string()],
+        Engine::Native(join),
+    ))
+}
+
+pub fn join(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let mut acc = PathBuf::new();
+    for value in params {
+        let value = value.as_string_obj(vm)?;
+        acc.push(value);
+    }
+    if debug {
+        vm.trace(format!("PATH.JOIN -> {}", acc.display()));
+    }
+    path_value(&acc, vm)
+}
+
+// basename
+pub fn basename_def() -> NativeMember {
+    NativeMember {
+        name: "basename".to_string(),
+        description: "last segment of the path, empty if the path ends in `..` or is a root"
+            .to_string(),
+        params: Some(vec!["path(string)".to_string()]),
+    }
+}
+
+pub fn basename_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "basename".to_string(),
+        vec!["path".to_string()],
+        Engine::Native(basename),
+    ))
+}
+
+pub fn basename(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let path = params[0].as_string_obj(vm)?;
+    let name = Path::new(&path).file_name().unwrap_or_default();
+    if debug {
+        vm.trace(format!("PATH.BASENAME <- {}", path));
+    }
+    path_value(Path::new(name), vm)
+}
+
+// dirname
+pub fn dirname_def() -> NativeMember {
+    NativeMember {
+        name: "dirname".to_string(),
+        description: "path without its last segment, `.` if there is no parent".to_string(),
+        params: Some(vec!["path(string)".to_string()]),
+    }
+}
+
+pub fn dirname_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "dirname".to_string(),
+        vec!["path".to_string()],
+        Engine::Native(dirname),
+    ))
+}
+
+pub fn dirname(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let path = params[0].as_string_obj(vm)?;
+    let path = Path::new(&path);
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        // a root is its own dirname
+        None if path.has_root() => path,
+        _ => Path::new("."),
+    };
+    if debug {
+        vm.trace(format!("PATH.DIRNAME <- {}", path.display()));
+    }
+    path_value(parent, vm)
+}
+
+// extension
+pub fn extension_def() -> NativeMember {
+    NativeMember {
+        name: "extension".to_string(),
+        description: "extension of the last segment without the dot, empty if there is none"
+            .to_string(),
+        params: Some(vec!["path(string)".to_string()]),
+    }
+}
+
+pub fn extension_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "extension".to_string(),
+        vec!["path".to_string()],
+        Engine::Native(extension),
+    ))
+}
+
+pub fn extension(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let path = params[0].as_string_obj(vm)?;
+    let ext = Path::new(&path).extension().unwrap_or_default();
+    if debug {
+        vm.trace(format!("PATH.EXTENSION <- {}", path));
+    }
+    path_value(Path::new(ext), vm)
+}
+
+// stem
+pub fn stem_def() -> NativeMember {
+    NativeMember {
+        name: "stem".to_string(),
+        description: "last segment of the path without its extension".to_string(),
+        params: Some(vec!["path(string)".to_string()]),
+    }
+}
+
+pub fn stem_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "stem".to_string(),
+        vec!["path".to_string()],
+        Engine::Native(stem),
+    ))
+}
+
+pub fn stem(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let path = params[0].as_string_obj(vm)?;
+    let stem = Path::new(&path).file_stem().unwrap_or_default();
+    if debug {
+        vm.trace(format!("PATH.STEM <- {}", path));
+    }
+    path_value(Path::new(stem), vm)
+}
+
+// with_extension
+pub fn with_extension_def() -> NativeMember {
+    NativeMember {
+        name: "with_extension".to_string(),
+        description: "replaces the extension of the path, an empty extension removes it"
+            .to_string(),
+        params: Some(vec![
+            "path(string)".to_string(),
+            "extension(string)".to_string(),
+        ]),
+    }
+}
+
+pub fn with_extension_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "with_extension".to_string(),
+        vec!["path".to_string(), "extension".to_string()],
+        Engine::Native(with_extension),
+    ))
+}
+
+pub fn with_extension(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let path = params[0].as_string_obj(vm)?;
+    let ext = params[1].as_string_obj(vm)?;
+    let path = Path::new(&path).with_extension(ext.trim_start_matches('.'));
+    if debug {
+        vm.trace(format!("PATH.WITH_EXTENSION -> {}", path.display()));
+    }
+    path_value(&path, vm)
+}
+
+// normalize
+pub fn normalize_def() -> NativeMember {
+    NativeMember {
+        name: "normalize".to_string(),
+        description: "removes `.` segments and resolves `..` without touching the filesystem"
+            .to_string(),
+        params: Some(vec!["path(string)".to_string()]),
+    }
+}
+
+pub fn normalize_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "normalize".to_string(),
+        vec!["path".to_string()],
+        Engine::Native(normalize_path),
+    ))
+}
+
+pub fn normalize_path(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let path = params[0].as_string_obj(vm)?;
+    let normalized = normalize(Path::new(&path));
+    if debug {
+        vm.trace(format!("PATH.NORMALIZE -> {}", normalized.display()));
+    }
+    path_value(&normalized, vm)
+}
+
+// absolute
+pub fn absolute_def() -> NativeMember {
+    NativeMember {
+        name: "absolute".to_string(),
+        description: "normalized absolute path, relative paths are resolved against the cwd"
+            .to_string(),
+        params: Some(vec!["path(string)".to_string()]),
+    }
+}
+
+pub fn absolute_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "absolute".to_string(),
+        vec!["path".to_string()],
+        Engine::Native(absolute),
+    ))
+}
+
+pub fn absolute(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let path = params[0].as_string_obj(vm)?;
+    let absolute =
+        path_utils::absolute(Path::new(&path)).map_err(|e| path_error(e.to_string(), vm))?;
+    if debug {
+        vm.trace(format!("PATH.ABSOLUTE -> {}", absolute.display()));
+    }
+    path_value(&absolute, vm)
+}
+
+// relative
+pub fn relative_def() -> NativeMember {
+    NativeMember {
+        name: "relative".to_string(),
+        description: "path that leads from the `from` path to the `to` path".to_string(),
+        params: Some(vec!["from(string)".to_string(), "to(string)".to_string()]),
+    }
+}
+
+pub fn relative_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "relative".to_string(),
+        vec!["from".to_string(), "to".to_string()],
+        Engine::Native(relative),
+    ))
+}
+
+pub fn relative(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let from = params[0].as_string_obj(vm)?;
+    let to = params[1].as_string_obj(vm)?;
+    let relative = path_utils::relative(Path::new(&from), Path::new(&to))
+        .map_err(|e| path_error(e.to_string(), vm))?;
+    if debug {
+        vm.trace(format!("PATH.RELATIVE -> {}", relative.display()));
+    }
+    path_value(&relative, vm)
+}
+
+// is_absolute
+pub fn is_absolute_def() -> NativeMember {
+    NativeMember {
+        name: "is_absolute".to_string(),
+        description: "true if the path starts at the root".to_string(),
+        params: Some(vec!["path(string)".to_string()]),
+    }
+}
+
+pub fn is_absolute_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "is_absolute".to_string(),
+        vec!["path".to_string()],
+        Engine::Native(is_absolute),
+    ))
+}
+
+pub fn is_absolute(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let path = params[0].as_string_obj(vm)?;
+    let absolute = Path::new(&path).is_absolute();
+    if debug {
+        vm.trace(format!("PATH.IS_ABSOLUTE <- {}", path));
+    }
+    Ok(Value::RawValue(RawValue::Bool(Bool::new(absolute))))
+}
+
+// split
+pub fn split_def() -> NativeMember {
+    NativeMember {
+        name: "split".to_string(),
+        description: "vector with the segments of the path, the root is kept as first segment"
+            .to_string(),
+        params: Some(vec!["path(string)".to_string()]),
+    }
+}
+
+pub fn split_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "split".to_string(),
+        vec!["path".to_string()],
+        Engine::Native(split),
+    ))
+}
+
+pub fn split(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let path = params[0].as_string_obj(vm)?;
+    if debug {
+        vm.trace(format!("PATH.SPLIT <- {}", path));
+    }
+    let mut segments = vec![];
+    for component in Path::new(&path).components() {
+        segments.push(path_value(Path::new(component.as_os_str()), vm)?);
+    }
+    Ok(Value::Handle(put_vector(vm, segments)))
+}
+
+// home_dir
+pub fn home_dir_def() -> NativeMember {
+    NativeMember {
+        name: "home_dir".to_string(),
+        description: "home directory of the current user".to_string(),
+        params: None,
+    }
+}
+
+pub fn home_dir_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "home_dir".to_string(),
+        vec![],
+        Engine::Native(home_dir),
+    ))
+}
+
+pub fn home_dir(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    _params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let var = if cfg!(windows) { "USERPROFILE" } else { "HOME" };
+    let home = match std::env::var_os(var) {
+        Some(home) if !home.is_empty() => PathBuf::from(home),
+        _ => return Err(path_error(format!("{} is not set", var), vm)),
+    };
+    if debug {
+        vm.trace(format!("PATH.HOME_DIR -> {}", home.display()));
+    }
+    path_value(&home, vm)
+}
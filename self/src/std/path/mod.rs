@@ -1,51 +1,56 @@
-use std::path::PathBuf;
+mod members;
 
-use crate::core::error::{self, VMError, VMErrorType};
-use crate::memory::{Handle, MemObject};
-use crate::std::heap_utils::put_string;
-use crate::std::{NativeMember, NativeModuleDef};
-use crate::types::object::func::{Engine, Function};
-use crate::types::Value;
-use crate::vm::Vm;
-
-fn join(
-    vm: &mut Vm,
-    _self: Option<Handle>,
-    _params: Vec<Value>,
-    debug: bool,
-) -> Result<Value, VMError> {
-    let mut acc = PathBuf::new();
-    for value in _params {
-        let value = value.as_string_obj(vm)?;
-        acc.push(value);
-    }
-    Ok(Value::Handle(put_string(
-        vm,
-        acc.to_str().unwrap().to_string(),
-    )))
-}
+use crate::{
+    memory::MemObject,
+    std::{
+        path::members::{
+            absolute_def, absolute_obj, basename_def, basename_obj, dirname_def, dirname_obj,
+            extension_def, extension_obj, home_dir_def, home_dir_obj, is_absolute_def,
+            is_absolute_obj, join_def, join_obj, normalize_def, normalize_obj, relative_def,
+            relative_obj, split_def, split_obj, stem_def, stem_obj, with_extension_def,
+            with_extension_obj,
+        },
+        NativeModuleDef,
+    },
+};
 
 pub fn generate_struct() -> (String, Vec<(String, MemObject)>) {
-    (
-        "path".to_string(),
-        vec![(
-            "join".to_string(),
-            MemObject::Function(Function::new(
-                "join".to_string(),
-                vec!["...path_segment".to_string()],
-                Engine::Native(join),
-            )),
-        )],
-    )
+    let mut fields = vec![];
+
+    fields.push(("join".to_string(), join_obj()));
+    fields.push(("basename".to_string(), basename_obj()));
+    fields.push(("dirname".to_string(), dirname_obj()));
+    fields.push(("extension".to_string(), extension_obj()));
+    fields.push(("stem".to_string(), stem_obj()));
+    fields.push(("with_extension".to_string(), with_extension_obj()));
+    fields.push(("normalize".to_string(), normalize_obj()));
+    fields.push(("absolute".to_string(), absolute_obj()));
+    fields.push(("relative".to_string(), relative_obj()));
+    fields.push(("is_absolute".to_string(), is_absolute_obj()));
+    fields.push(("split".to_string(), split_obj()));
+    fields.push(("home_dir".to_string(), home_dir_obj()));
+
+    ("path".to_string(), fields)
 }
 
 pub fn generate_mod_def() -> NativeModuleDef {
+    let members = vec![
+        join_def(),
+        basename_def(),
+        dirname_def(),
+        extension_def(),
+        stem_def(),
+        with_extension_def(),
+        normalize_def(),
+        absolute_def(),
+        relative_def(),
+        is_absolute_def(),
+        split_def(),
+        home_dir_def(),
+    ];
+
     NativeModuleDef {
         module: "path".to_string(),
-        members: vec![NativeMember {
-            name: "join".to_string(),
-            description: "merges an arbitrary number of paths into a single path".to_string(),
-            params: Some(vec!["...path_segment".to_string()]),
-        }],
+        members,
     }
 }
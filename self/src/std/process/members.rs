@@ -0,0 +1,583 @@
+use std::{collections::HashMap, process::Stdio, time::Duration};
+
+use futures::future::BoxFuture;
+use serde_json::Value as JsonValue;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncWriteExt, BufReader},
+    process::Command,
+    sync::{mpsc, watch},
+    time::sleep,
+};
+
+use crate::{
+    core::error::{
+        self, process_errors::ProcessError, type_errors::TypeError, VMError, VMErrorType,
+    },
+    events::Event,
+    memory::{Handle, MemObject},
+    std::{heap_utils::put_string, process::types::Process, utils::value_to_json, NativeMember},
+    types::{
+        object::{
+            func::{Engine, Function},
+            native_struct::NativeStruct,
+            structs::StructLiteral,
+        },
+        raw::{bool::Bool, i32::I32, u64::U64, RawValue},
+        Value,
+    },
+    vm::Vm,
+};
+
+// options struct shared by run and spawn:
+//   { cwd, env, stdin, timeout, on_stdout, on_stderr, on_exit }
+// callbacks are only used by spawn
+#[derive(Default)]
+struct ProcessOptions {
+    cwd: Option<String>,
+    env: Vec<(String, String)>,
+    stdin: Option<String>,
+    timeout: Option<u64>,
+    on_stdout: Option<Function>,
+    on_stderr: Option<Function>,
+    on_exit: Option<Function>,
+}
+
+fn process_options(vm: &Vm, value: Option<&Value>) -> Result<ProcessOptions, VMError> {
+    let mut options = ProcessOptions::default();
+    let Some(value) = value else {
+        return Ok(options);
+    };
+    if let Value::RawValue(RawValue::Nothing) = value {
+        return Ok(options);
+    }
+    let fields = value.as_struct_obj(vm)?;
+
+    for (name, field) in &fields.fields {
+        if let Value::RawValue(RawValue::Nothing) = field {
+            continue;
+        }
+        match name.as_str() {
+            "cwd" => options.cwd = Some(field.as_string_obj(vm)?),
+            "stdin" => options.stdin = Some(field.as_string_obj(vm)?),
+            "timeout" => options.timeout = Some(field.as_usize(vm)? as u64),
+            "env" => {
+                if let JsonValue::Object(env) = value_to_json(vm, field) {
+                    options.env = env
+                        .into_iter()
+                        .map(|(k, v)| match v {
+                            JsonValue::String(s) => (k, s),
+                            other => (k, other.to_string()),
+                        })
+                        .collect();
+                }
+            }
+            "on_stdout" => options.on_stdout = Some(field.as_function_obj(vm)?),
+            "on_stderr" => options.on_stderr = Some(field.as_function_obj(vm)?),
+            "on_exit" => options.on_exit = Some(field.as_function_obj(vm)?),
+            _ => {}
+        }
+    }
+    Ok(options)
+}
+
+// args can be a vector or a single string argument
+fn command_args(vm: &Vm, value: Option<&Value>) -> Result<Vec<String>, VMError> {
+    let Some(value) = value else {
+        return Ok(vec![]);
+    };
+    match value_to_json(vm, value) {
+        JsonValue::Null => Ok(vec![]),
+        JsonValue::Array(args) => Ok(args
+            .into_iter()
+            .map(|a| match a {
+                JsonValue::String(s) => s,
+                other => other.to_string(),
+            })
+            .collect()),
+        JsonValue::String(arg) => Ok(vec![arg]),
+        other => Ok(vec![other.to_string()]),
+    }
+}
+
+fn build_command(program: &str, args: &[String], options: &ProcessOptions) -> Command {
+    let mut command = Command::new(program);
+    command.args(args).kill_on_drop(true);
+    if let Some(cwd) = &options.cwd {
+        command.current_dir(cwd);
+    }
+    command.envs(options.env.iter().cloned());
+    command
+}
+
+fn process_error(err: ProcessError, vm: &Vm) -> VMError {
+    error::throw(VMErrorType::Process(err), vm)
+}
+
+fn args_count_error(expected: u32, received: usize, vm: &Vm) -> VMError {
+    error::throw(
+        VMErrorType::TypeError(TypeError::InvalidArgsCount {
+            expected,
+            received: received as u32,
+        }),
+        vm,
+    )
+}
+
+// run
+pub fn run_def() -> NativeMember {
+    NativeMember {
+        name: "run".to_string(),
+        description: "run a command until it exits and return its {status, success, stdout, stderr}. args is a vector of strings, options can set cwd, env, stdin and timeout (ms)".to_string(),
+        params: Some(vec![
+            "command".to_string(),
+            "args".to_string(),
+            "options".to_string(),
+        ]),
+    }
+}
+
+pub fn run_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "run".to_string(),
+        vec!["command".to_string()],
+        Engine::NativeAsync(run),
+    ))
+}
+
+pub fn run(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> BoxFuture<'_, Result<Value, VMError>> {
+    Box::pin(async move {
+        if params.len() < 1 {
+            return Err(args_count_error(1, params.len(), vm));
+        }
+        let program = params[0].as_string_obj(vm)?;
+        let args = command_args(vm, params.get(1))?;
+        let options = process_options(vm, params.get(2))?;
+
+        if debug {
+            vm.trace(format!("PROCESS.RUN <- {} {}", program, args.join(" ")));
+        }
+
+        let mut command = build_command(&program, &args, &options);
+        command
+            .stdin(if options.stdin.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        let mut child = command.spawn().map_err(|e| {
+            process_error(
+                ProcessError::SpawnError {
+                    command: program.clone(),
+                    message: e.to_string(),
+                },
+                vm,
+            )
+        })?;
+
+        if let (Some(input), Some(mut stdin)) = (&options.stdin, child.stdin.take()) {
+            // writing in its own task, a child that doesn't read its
+            // stdin would block us otherwise
+            let input = input.clone();
+            tokio::spawn(async move {
+                let _ = stdin.write_all(input.as_bytes()).await;
+            });
+        }
+
+        // the events keep being handled while the child runs
+        let output = child.wait_with_output();
+        let output = match options.timeout {
+            Some(millis) => match vm
+                .with_events(tokio::time::timeout(Duration::from_millis(millis), output))
+                .await?
+            {
+                Ok(output) => output,
+                Err(_) => {
+                    return Err(process_error(
+                        ProcessError::Timeout {
+                            command: program,
+                            millis,
+                        },
+                        vm,
+                    ))
+                }
+            },
+            None => vm.with_events(output).await?,
+        };
+        let output = output.map_err(|e| {
+            process_error(
+                ProcessError::IoError {
+                    command: program.clone(),
+                    message: e.to_string(),
+                },
+                vm,
+            )
+        })?;
+
+        let status = output.status.code().unwrap_or(-1);
+        let stdout = put_string(vm, String::from_utf8_lossy(&output.stdout).to_string());
+        let stderr = put_string(vm, String::from_utf8_lossy(&output.stderr).to_string());
+
+        let mut fields = HashMap::new();
+        fields.insert(
+            "status".to_string(),
+            Value::RawValue(RawValue::I32(I32::new(status))),
+        );
+        fields.insert(
+            "success".to_string(),
+            Value::RawValue(RawValue::Bool(Bool::new(output.status.success()))),
+        );
+        fields.insert("stdout".to_string(), Value::Handle(stdout));
+        fields.insert("stderr".to_string(), Value::Handle(stderr));
+        let result = StructLiteral::new("ProcessResult".to_string(), fields);
+
+        Ok(Value::Handle(
+            vm.memory.alloc(MemObject::StructLiteral(result)),
+        ))
+    })
+}
+
+// spawn
+pub fn spawn_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "spawn".to_string(),
+        vec!["command".to_string()],
+        Engine::Native(spawn),
+    ))
+}
+
+pub fn spawn(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    if params.len() < 1 {
+        return Err(args_count_error(1, params.len(), vm));
+    }
+    let program = params[0].as_string_obj(vm)?;
+    let args = command_args(vm, params.get(1))?;
+    let options = process_options(vm, params.get(2))?;
+
+    if debug {
+        vm.trace(format!("PROCESS.SPAWN <- {} {}", program, args.join(" ")));
+    }
+
+    let mut command = build_command(&program, &args, &options);
+    command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(if options.on_stderr.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::inherit()
+        });
+    let mut child = command.spawn().map_err(|e| {
+        process_error(
+            ProcessError::SpawnError {
+                command: program.clone(),
+                message: e.to_string(),
+            },
+            vm,
+        )
+    })?;
+
+    // output with a callback goes through the events queue, line by
+    // line. the vm waits for them while the child runs
+    let keep_alive =
+        (options.on_stdout.is_some() || options.on_stderr.is_some() || options.on_exit.is_some())
+            .then(|| vm.keep_alive());
+    let vm_notifier = vm.get_vm_notifier();
+    let mut readers = vec![];
+    let mut stdout = child.stdout.take().map(|s| BufReader::new(s).lines());
+    if let Some(on_stdout) = options.on_stdout {
+        if let Some(lines) = stdout.take() {
+            readers.push(forward_lines(lines, on_stdout, vm_notifier.clone()));
+        }
+    }
+    if let (Some(on_stderr), Some(stderr)) = (options.on_stderr, child.stderr.take()) {
+        let lines = BufReader::new(stderr).lines();
+        readers.push(forward_lines(lines, on_stderr, vm_notifier.clone()));
+    }
+
+    let pid = child.id();
+    let stdin = child.stdin.take();
+    let (status_sender, status) = watch::channel(None);
+    let (kill, mut kill_receiver) = mpsc::unbounded_channel::<()>();
+    let on_exit = options.on_exit;
+    let timeout = options.timeout;
+
+    // the watcher owns the child until it exits
+    tokio::spawn(async move {
+        let _keep_alive = keep_alive;
+        let timeout = async {
+            match timeout {
+                Some(millis) => sleep(Duration::from_millis(millis)).await,
+                None => std::future::pending().await,
+            }
+        };
+        let exit = tokio::select! {
+            exit = child.wait() => exit,
+            Some(_) = kill_receiver.recv() => {
+                let _ = child.start_kill();
+                child.wait().await
+            }
+            _ = timeout => {
+                let _ = child.start_kill();
+                child.wait().await
+            }
+        };
+        for reader in readers {
+            let _ = reader.await;
+        }
+
+        let code = exit.ok().and_then(|s| s.code()).unwrap_or(-1);
+        if let Some(on_exit) = on_exit {
+            let _ = vm_notifier.send(Event::Call(on_exit, vec![JsonValue::from(code)]));
+        }
+        let _ = status_sender.send(Some(code));
+    });
+
+    let process = Process::new_initialized(vm, program, pid, stdin, stdout, status, kill);
+    Ok(Value::Handle(vm.memory.alloc(MemObject::NativeStruct(
+        NativeStruct::Process(process),
+    ))))
+}
+
+fn forward_lines<R: AsyncRead + Unpin + Send + 'static>(
+    mut lines: tokio::io::Lines<BufReader<R>>,
+    callback: Function,
+    vm_notifier: mpsc::UnboundedSender<Event>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        while let Ok(Some(line)) = lines.next_line().await {
+            let _ = vm_notifier.send(Event::Call(callback.clone(), vec![JsonValue::String(line)]));
+        }
+    })
+}
+
+fn resolve_process(vm: &Vm, _self: Option<Handle>) -> &Process {
+    let _self = _self.expect("process methods are bound to a process");
+    if let MemObject::NativeStruct(NativeStruct::Process(p)) = vm.memory.resolve(&_self) {
+        p
+    } else {
+        unreachable!()
+    }
+}
+
+// Process type methods
+pub fn write_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "write".to_string(),
+        vec!["data".to_string()],
+        Engine::NativeAsync(write),
+    ))
+}
+
+pub fn write(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> BoxFuture<'_, Result<Value, VMError>> {
+    Box::pin(async move {
+        let Some(data) = params.first() else {
+            return Err(args_count_error(1, params.len(), vm));
+        };
+        let data = data.as_string_obj(vm)?;
+        write_stdin(vm, _self, data, debug).await
+    })
+}
+
+pub fn write_line_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "write_line".to_string(),
+        vec!["data".to_string()],
+        Engine::NativeAsync(write_line),
+    ))
+}
+
+// ego strings keep their escapes, so the newline is added here
+pub fn write_line(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> BoxFuture<'_, Result<Value, VMError>> {
+    Box::pin(async move {
+        let Some(data) = params.first() else {
+            return Err(args_count_error(1, params.len(), vm));
+        };
+        let data = data.as_string_obj(vm)? + "\n";
+        write_stdin(vm, _self, data, debug).await
+    })
+}
+
+async fn write_stdin(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    data: String,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let process = resolve_process(vm, _self);
+    let (stdin, command) = (process.stdin.clone(), process.command.clone());
+
+    if debug {
+        vm.trace(format!("PROCESS.WRITE <- {}", data));
+    }
+
+    let mut stdin = stdin.lock().await;
+    let result = match stdin.as_mut() {
+        Some(stdin) => match stdin.write_all(data.as_bytes()).await {
+            Ok(_) => stdin.flush().await.map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        },
+        None => Err("stdin is closed".to_string()),
+    };
+    match result {
+        Ok(_) => Ok(Value::RawValue(RawValue::U64(U64::new(data.len() as u64)))),
+        Err(message) => Err(process_error(
+            ProcessError::IoError { command, message },
+            vm,
+        )),
+    }
+}
+
+pub fn close_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "close".to_string(),
+        vec![],
+        Engine::NativeAsync(close),
+    ))
+}
+
+// closes the stdin, for commands that read until the end of input
+pub fn close(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    _params: Vec<Value>,
+    debug: bool,
+) -> BoxFuture<'_, Result<Value, VMError>> {
+    Box::pin(async move {
+        let process = resolve_process(vm, _self);
+        let stdin = process.stdin.clone();
+        if debug {
+            vm.trace(format!("PROCESS.CLOSE <- {}", process.command));
+        }
+        stdin.lock().await.take();
+        Ok(Value::RawValue(RawValue::Nothing))
+    })
+}
+
+pub fn read_line_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "read_line".to_string(),
+        vec![],
+        Engine::NativeAsync(read_line),
+    ))
+}
+
+// next stdout line, nothing once the output is over
+pub fn read_line(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    _params: Vec<Value>,
+    debug: bool,
+) -> BoxFuture<'_, Result<Value, VMError>> {
+    Box::pin(async move {
+        let process = resolve_process(vm, _self);
+        let (stdout, command) = (process.stdout.clone(), process.command.clone());
+
+        let mut stdout = stdout.lock().await;
+        let Some(lines) = stdout.as_mut() else {
+            return Err(process_error(
+                ProcessError::IoError {
+                    command,
+                    message: "stdout is read by the on_stdout callback".to_string(),
+                },
+                vm,
+            ));
+        };
+        let line = lines.next_line().await.map_err(|e| {
+            process_error(
+                ProcessError::IoError {
+                    command: command.clone(),
+                    message: e.to_string(),
+                },
+                vm,
+            )
+        })?;
+
+        if debug {
+            vm.trace(format!("PROCESS.READ_LINE -> {:?}", line));
+        }
+        match line {
+            Some(line) => Ok(Value::Handle(put_string(vm, line))),
+            None => Ok(Value::RawValue(RawValue::Nothing)),
+        }
+    })
+}
+
+pub fn wait_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "wait".to_string(),
+        vec![],
+        Engine::NativeAsync(wait),
+    ))
+}
+
+// waits for the exit and returns the status. the callbacks of the
+// process keep running meanwhile
+pub fn wait(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    _params: Vec<Value>,
+    debug: bool,
+) -> BoxFuture<'_, Result<Value, VMError>> {
+    Box::pin(async move {
+        let process = resolve_process(vm, _self);
+        let mut status = process.status.clone();
+        if debug {
+            vm.trace(format!("PROCESS.WAIT <- {}", process.command));
+        }
+
+        let code = vm
+            .with_events(status.wait_for(|s| s.is_some()))
+            .await?
+            .ok()
+            .and_then(|c| *c)
+            .unwrap_or(-1);
+        // on_exit and the last output lines
+        vm.drain_events().await?;
+
+        Ok(Value::RawValue(RawValue::I32(I32::new(code))))
+    })
+}
+
+pub fn kill_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "kill".to_string(),
+        vec![],
+        Engine::Native(kill),
+    ))
+}
+
+pub fn kill(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    _params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let process = resolve_process(vm, _self);
+    if debug {
+        vm.trace(format!("PROCESS.KILL <- {}", process.command));
+    }
+    let _ = process.kill.send(());
+    Ok(Value::RawValue(RawValue::Nothing))
+}
@@ -0,0 +1,29 @@
+mod members;
+pub mod types;
+
+use crate::{
+    memory::MemObject,
+    std::{
+        process::members::{run_def, run_obj, spawn_obj},
+        NativeModuleDef,
+    },
+};
+
+pub fn generate_struct() -> (String, Vec<(String, MemObject)>) {
+    let mut fields = vec![];
+
+    fields.push(("run".to_string(), run_obj()));
+    fields.push(("spawn".to_string(), spawn_obj()));
+
+    ("process".to_string(), fields)
+}
+
+// spawn is left out, its callbacks are not usable as actions
+pub fn generate_mod_def() -> NativeModuleDef {
+    let members = vec![run_def()];
+
+    NativeModuleDef {
+        module: "process".to_string(),
+        members,
+    }
+}
@@ -0,0 +1,87 @@
+use std::{collections::HashMap, sync::Arc};
+
+use tokio::{
+    io::{BufReader, Lines},
+    process::{ChildStdin, ChildStdout},
+    sync::{mpsc, watch, Mutex},
+};
+
+use crate::{
+    std::{
+        heap_utils::put_string,
+        process::members::{
+            close_obj, kill_obj, read_line_obj, wait_obj, write_line_obj, write_obj,
+        },
+    },
+    types::{
+        object::structs::StructLiteral,
+        raw::{u32::U32, RawValue},
+        Value,
+    },
+    vm::Vm,
+};
+
+// a process started with `process.spawn`. the child itself is
+// owned by a watcher task, the vm talks to it through channels
+#[derive(Debug)]
+pub struct Process {
+    pub command: String,
+    pub stdin: Arc<Mutex<Option<ChildStdin>>>,
+    // none when the stdout is sent to an on_stdout callback
+    pub stdout: Arc<Mutex<Option<Lines<BufReader<ChildStdout>>>>>,
+    // exit code, none while running
+    pub status: watch::Receiver<Option<i32>>,
+    pub kill: mpsc::UnboundedSender<()>,
+    pub shape: StructLiteral,
+}
+
+impl Process {
+    pub fn new_initialized(
+        vm: &mut Vm,
+        command: String,
+        pid: Option<u32>,
+        stdin: Option<ChildStdin>,
+        stdout: Option<Lines<BufReader<ChildStdout>>>,
+        status: watch::Receiver<Option<i32>>,
+        kill: mpsc::UnboundedSender<()>,
+    ) -> Process {
+        let mut fields = HashMap::new();
+        let command_handle = put_string(vm, command.clone());
+        fields.insert("command".to_string(), Value::Handle(command_handle));
+        fields.insert(
+            "pid".to_string(),
+            match pid {
+                Some(pid) => Value::RawValue(RawValue::U32(U32::new(pid))),
+                None => Value::RawValue(RawValue::Nothing),
+            },
+        );
+        for (name, obj) in [
+            ("write", write_obj()),
+            ("write_line", write_line_obj()),
+            ("close", close_obj()),
+            ("read_line", read_line_obj()),
+            ("wait", wait_obj()),
+            ("kill", kill_obj()),
+        ] {
+            let handle = vm.memory.alloc(obj);
+            fields.insert(name.to_string(), Value::Handle(handle));
+        }
+
+        Process {
+            command,
+            stdin: Arc::new(Mutex::new(stdin)),
+            stdout: Arc::new(Mutex::new(stdout)),
+            status,
+            kill,
+            shape: StructLiteral::new("Process".to_string(), fields),
+        }
+    }
+
+    pub fn to_string(&self) -> String {
+        format!("Process({})", self.command)
+    }
+
+    pub fn property_access(&self, property: &str) -> Option<Value> {
+        self.shape.property_access(property)
+    }
+}
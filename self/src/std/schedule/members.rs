@@ -1,13 +1,11 @@
-use std::time::Duration;
-
 use futures::future::BoxFuture;
 use tokio::time::{interval as tokio_interval, sleep};
 
 use crate::{
-    core::error::{self, type_errors::TypeError, VMError, VMErrorType},
+    core::error::{self, time_errors::TimeError, type_errors::TypeError, VMError, VMErrorType},
     events::Event,
     memory::{Handle, MemObject},
-    std::schedule::types::Interval,
+    std::{schedule::types::Interval, time::utils::duration_param},
     types::{
         object::{
             func::{Engine, Function},
@@ -22,7 +20,7 @@ use crate::{
 pub fn interval_obj() -> MemObject {
     MemObject::Function(Function::new(
         "interval".to_string(),
-        vec!["callback".to_string(), "milliseconds".to_string()],
+        vec!["callback".to_string(), "duration".to_string()],
         Engine::NativeAsync(interval),
     ))
 }
@@ -46,14 +44,21 @@ pub fn interval(
 
         let callback = &params[0].as_function_obj(vm)?;
         let callback = callback.clone();
-        let milliseconds = &params[1].as_usize(vm)?;
+        // a Duration, a text like 1h30m or a number of ms
+        let period = duration_param(vm, &params[1])?;
+        if period.is_zero() {
+            return Err(error::throw(
+                VMErrorType::Time(TimeError::OutOfRange("an interval can't be 0s".to_string())),
+                vm,
+            ));
+        }
         let vm_notifier = vm.get_vm_notifier();
 
         if debug {
-            println!("INTERVAL -> {}", milliseconds)
+            vm.trace(format!("INTERVAL -> {}ms", period.as_millis()))
         }
 
-        let mut tick = tokio_interval(Duration::from_millis(*milliseconds as u64));
+        let mut tick = tokio_interval(period);
         let interval_struct = Interval::new_initialized(vm);
         let interval_obj_handle = vm
             .memory
@@ -64,7 +69,7 @@ pub fn interval(
         tokio::spawn(async move {
             loop {
                 tick.tick().await;
-                let _ = vm_notifier.send(Event::Call(callback.clone()));
+                let _ = vm_notifier.send(Event::Call(callback.clone(), vec![]));
             }
         });
 
@@ -75,7 +80,7 @@ pub fn interval(
 pub fn timeout_obj() -> MemObject {
     MemObject::Function(Function::new(
         "timeout".to_string(),
-        vec!["callback".to_string(), "milliseconds".to_string()],
+        vec!["callback".to_string(), "duration".to_string()],
         Engine::NativeAsync(timeout),
     ))
 }
@@ -99,17 +104,17 @@ pub fn timeout(
 
         let callback = &params[0].as_function_obj(vm)?;
         let callback = callback.clone();
-        let milliseconds = &params[1].as_usize(vm)?;
+        // a Duration, a text like 1h30m or a number of ms
+        let delay = duration_param(vm, &params[1])?;
         let vm_notifier = vm.get_vm_notifier();
 
         if debug {
-            println!("TIMEOUT -> {}", milliseconds)
+            vm.trace(format!("TIMEOUT -> {}ms", delay.as_millis()))
         }
 
-        let timeout_millis = Duration::from_millis(*milliseconds as u64);
         tokio::spawn(async move {
-            sleep(timeout_millis).await;
-            let _ = vm_notifier.send(Event::Call(callback.clone()));
+            sleep(delay).await;
+            let _ = vm_notifier.send(Event::Call(callback.clone(), vec![]));
         });
 
         Ok(Value::RawValue(RawValue::Nothing))
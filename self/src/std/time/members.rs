@@ -0,0 +1,812 @@
+use std::collections::HashMap;
+use std::time::Instant;
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, TimeDelta};
+use futures::future::BoxFuture;
+
+use crate::core::error::time_errors::TimeError;
+use crate::core::error::{self, VMErrorType};
+use crate::memory::Handle;
+use crate::std::heap_utils::put_string;
+use crate::std::time::types::{Monotonic, Time, TimeDuration};
+use crate::std::time::utils::{duration_value, format_duration, number_param, Zone};
+use crate::std::NativeMember;
+use crate::types::object::native_struct::NativeStruct;
+use crate::types::object::structs::StructLiteral;
+use crate::types::raw::bool::Bool;
+use crate::types::raw::f64::F64;
+use crate::types::raw::i64::I64;
+use crate::types::raw::RawValue;
+use crate::{
+    core::error::VMError,
+    memory::MemObject,
+    types::{
+        object::func::{Engine, Function},
+        Value,
+    },
+    vm::Vm,
+};
+
+fn time_error(err: TimeError, vm: &Vm) -> VMError {
+    error::throw(VMErrorType::Time(err), vm)
+}
+
+fn alloc_time(vm: &mut Vm, value: DateTime<FixedOffset>) -> Value {
+    let time = Time::new_initialized(vm, value);
+    Value::Handle(
+        vm.memory
+            .alloc(MemObject::NativeStruct(NativeStruct::Time(time))),
+    )
+}
+
+fn alloc_duration(vm: &mut Vm, value: TimeDelta) -> Value {
+    let duration = TimeDuration::new_initialized(vm, value);
+    Value::Handle(
+        vm.memory
+            .alloc(MemObject::NativeStruct(NativeStruct::TimeDuration(
+                duration,
+            ))),
+    )
+}
+
+fn zone_param(vm: &Vm, value: &Value) -> Result<Zone, VMError> {
+    let zone = value.as_string_obj(vm)?;
+    Zone::parse(&zone).ok_or_else(|| time_error(TimeError::InvalidZone(zone), vm))
+}
+
+// a Time value, or none for anything else
+fn as_time(vm: &Vm, value: &Value) -> Option<DateTime<FixedOffset>> {
+    if let Value::Handle(handle) = value {
+        if let MemObject::NativeStruct(NativeStruct::Time(time)) = vm.memory.resolve(handle) {
+            return Some(time.value);
+        }
+    }
+    None
+}
+
+fn time_param(vm: &Vm, value: &Value) -> Result<DateTime<FixedOffset>, VMError> {
+    as_time(vm, value).ok_or_else(|| {
+        error::throw(
+            VMErrorType::TypeMismatch {
+                expected: "Time".to_string(),
+                received: value.get_resolved_type(vm),
+            },
+            vm,
+        )
+    })
+}
+
+fn out_of_range(vm: &Vm, message: String) -> VMError {
+    time_error(TimeError::OutOfRange(message), vm)
+}
+
+// now
+pub fn now_def() -> NativeMember {
+    NativeMember {
+        name: "now".to_string(),
+        description: "current time in the local zone, or in the given one. Zones are utc, local, an offset like +02:00 or an IANA name like Europe/Paris. Returns a Time with year, month, day, hour, minute, second, weekday and offset fields and the format, unix, unix_ms, add, sub, in_zone, before and after methods".to_string(),
+        params: Some(vec!["zone(string, optional)".to_string()]),
+    }
+}
+
+pub fn now_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "now".to_string(),
+        vec![],
+        Engine::Native(now),
+    ))
+}
+
+pub fn now(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let zone = match params.first() {
+        Some(zone) => zone_param(vm, zone)?,
+        None => Zone::Local,
+    };
+    let now = zone.convert(&Local::now().fixed_offset());
+    if debug {
+        vm.trace(format!("TIME.NOW -> {}", now.to_rfc3339()));
+    }
+    Ok(alloc_time(vm, now))
+}
+
+// monotonic
+pub fn monotonic_def() -> NativeMember {
+    NativeMember {
+        name: "monotonic".to_string(),
+        description: "reading of the monotonic clock, which never goes back like the wall clock can. Returns a value with an elapsed() method, the Duration since the reading".to_string(),
+        params: None,
+    }
+}
+
+pub fn monotonic_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "monotonic".to_string(),
+        vec![],
+        Engine::Native(monotonic),
+    ))
+}
+
+pub fn monotonic(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    _params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    if debug {
+        vm.trace("TIME.MONOTONIC".to_string());
+    }
+    let monotonic = Monotonic::new_initialized(vm, Instant::now());
+    Ok(Value::Handle(vm.memory.alloc(MemObject::NativeStruct(
+        NativeStruct::Monotonic(monotonic),
+    ))))
+}
+
+// from_unix
+pub fn from_unix_def() -> NativeMember {
+    NativeMember {
+        name: "from_unix".to_string(),
+        description: "Time (in utc) from the seconds since the unix epoch, which can have decimals"
+            .to_string(),
+        params: Some(vec!["seconds(number)".to_string()]),
+    }
+}
+
+pub fn from_unix_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "from_unix".to_string(),
+        vec!["seconds".to_string()],
+        Engine::Native(from_unix),
+    ))
+}
+
+pub fn from_unix(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let seconds = number_param(vm, &params[0])?;
+    if debug {
+        vm.trace(format!("TIME.FROM_UNIX <- {}", seconds));
+    }
+    let nanos = (seconds.fract() * 1e9).round() as i64;
+    let time = DateTime::from_timestamp(seconds.trunc() as i64, 0)
+        .and_then(|time| time.checked_add_signed(TimeDelta::nanoseconds(nanos)))
+        .ok_or_else(|| out_of_range(vm, format!("{} seconds", seconds)))?;
+    Ok(alloc_time(vm, time.fixed_offset()))
+}
+
+// from_unix_ms
+pub fn from_unix_ms_def() -> NativeMember {
+    NativeMember {
+        name: "from_unix_ms".to_string(),
+        description: "Time (in utc) from the milliseconds since the unix epoch".to_string(),
+        params: Some(vec!["ms(number)".to_string()]),
+    }
+}
+
+pub fn from_unix_ms_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "from_unix_ms".to_string(),
+        vec!["ms".to_string()],
+        Engine::Native(from_unix_ms),
+    ))
+}
+
+pub fn from_unix_ms(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let ms = number_param(vm, &params[0])? as i64;
+    if debug {
+        vm.trace(format!("TIME.FROM_UNIX_MS <- {}", ms));
+    }
+    let time = DateTime::from_timestamp_millis(ms)
+        .ok_or_else(|| out_of_range(vm, format!("{} ms", ms)))?;
+    Ok(alloc_time(vm, time.fixed_offset()))
+}
+
+// parse
+pub fn parse_def() -> NativeMember {
+    NativeMember {
+        name: "parse".to_string(),
+        description: "parse an RFC 3339 time (2024-05-01T10:00:00+02:00), or one written with a strftime pattern like %d/%m/%Y %H:%M. Times without an offset are read in the given zone, utc by default".to_string(),
+        params: Some(vec![
+            "text(string)".to_string(),
+            "pattern(string, optional)".to_string(),
+            "zone(string, optional)".to_string(),
+        ]),
+    }
+}
+
+pub fn parse_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "parse".to_string(),
+        vec!["text".to_string()],
+        Engine::Native(parse),
+    ))
+}
+
+fn check_pattern(vm: &Vm, pattern: &str) -> Result<(), VMError> {
+    if StrftimeItems::new(pattern).any(|item| matches!(item, Item::Error)) {
+        return Err(time_error(
+            TimeError::InvalidPattern(pattern.to_string()),
+            vm,
+        ));
+    }
+    Ok(())
+}
+
+// parse(text), parse(text, pattern) or parse(text, pattern, zone)
+pub fn parse(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let text = params[0].as_string_obj(vm)?;
+    let pattern = match params.get(1) {
+        Some(Value::RawValue(RawValue::Nothing)) | None => None,
+        Some(pattern) => Some(pattern.as_string_obj(vm)?),
+    };
+    let zone = match params.get(2) {
+        Some(zone) => zone_param(vm, zone)?,
+        None => Zone::Fixed(FixedOffset::east_opt(0).unwrap()),
+    };
+    if debug {
+        vm.trace(format!("TIME.PARSE <- {} ({:?})", text, pattern));
+    }
+
+    let parse_error = |message: String, vm: &Vm| {
+        time_error(
+            TimeError::ParseError {
+                text: text.clone(),
+                message,
+            },
+            vm,
+        )
+    };
+    let time = match &pattern {
+        None => DateTime::parse_from_rfc3339(&text).map_err(|e| parse_error(e.to_string(), vm))?,
+        Some(pattern) => {
+            check_pattern(vm, pattern)?;
+            // the pattern may have an offset, a date and time or
+            // only a date
+            match DateTime::parse_from_str(&text, pattern) {
+                Ok(time) => time,
+                Err(_) => {
+                    let naive = NaiveDateTime::parse_from_str(&text, pattern)
+                        .or_else(|err| {
+                            NaiveDate::parse_from_str(&text, pattern)
+                                .map(|date| date.and_hms_opt(0, 0, 0).unwrap())
+                                .map_err(|_| err)
+                        })
+                        .map_err(|e| parse_error(e.to_string(), vm))?;
+                    zone.from_naive(&naive).ok_or_else(|| {
+                        parse_error("the time doesn't exist in that zone".to_string(), vm)
+                    })?
+                }
+            }
+        }
+    };
+    Ok(alloc_time(vm, time))
+}
+
+// duration
+pub fn duration_def() -> NativeMember {
+    NativeMember {
+        name: "duration".to_string(),
+        description: "Duration from a text like 1h30m, 1.5s or 250ms (units ns, us, ms, s, m, h and d) or a number of ms. Durations have the ms, seconds, add, sub and mul methods and are accepted by schedule.timeout and schedule.interval".to_string(),
+        params: Some(vec!["value(string or number)".to_string()]),
+    }
+}
+
+pub fn duration_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "duration".to_string(),
+        vec!["value".to_string()],
+        Engine::Native(duration),
+    ))
+}
+
+pub fn duration(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let value = duration_value(vm, &params[0])?;
+    if debug {
+        vm.trace(format!("TIME.DURATION -> {}", format_duration(value)));
+    }
+    Ok(alloc_duration(vm, value))
+}
+
+// ms, seconds, minutes and hours build a duration of that unit
+fn unit_duration(vm: &mut Vm, params: &[Value], unit_ms: f64) -> Result<Value, VMError> {
+    let amount = number_param(vm, &params[0])?;
+    let nanos = amount * unit_ms * 1e6;
+    if !nanos.is_finite() || nanos.abs() >= i64::MAX as f64 {
+        return Err(out_of_range(vm, format!("{}", amount)));
+    }
+    Ok(alloc_duration(
+        vm,
+        TimeDelta::nanoseconds(nanos.round() as i64),
+    ))
+}
+
+pub fn unit_def(unit: &str) -> NativeMember {
+    NativeMember {
+        name: unit.to_string(),
+        description: format!(
+            "Duration of the given amount of {}, which can have decimals",
+            unit
+        ),
+        params: Some(vec!["amount(number)".to_string()]),
+    }
+}
+
+pub fn ms_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "ms".to_string(),
+        vec!["amount".to_string()],
+        Engine::Native(ms),
+    ))
+}
+
+pub fn ms(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    _debug: bool,
+) -> Result<Value, VMError> {
+    unit_duration(vm, &params, 1.0)
+}
+
+pub fn seconds_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "seconds".to_string(),
+        vec!["amount".to_string()],
+        Engine::Native(seconds),
+    ))
+}
+
+pub fn seconds(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    _debug: bool,
+) -> Result<Value, VMError> {
+    unit_duration(vm, &params, 1_000.0)
+}
+
+pub fn minutes_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "minutes".to_string(),
+        vec!["amount".to_string()],
+        Engine::Native(minutes),
+    ))
+}
+
+pub fn minutes(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    _debug: bool,
+) -> Result<Value, VMError> {
+    unit_duration(vm, &params, 60_000.0)
+}
+
+pub fn hours_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "hours".to_string(),
+        vec!["amount".to_string()],
+        Engine::Native(hours),
+    ))
+}
+
+pub fn hours(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    _debug: bool,
+) -> Result<Value, VMError> {
+    unit_duration(vm, &params, 3_600_000.0)
+}
+
+// measure
+pub fn measure_def() -> NativeMember {
+    NativeMember {
+        name: "measure".to_string(),
+        description: "call the function with the rest of the arguments and return a {result, elapsed} struct, elapsed being the Duration of the call".to_string(),
+        params: Some(vec![
+            "function(function)".to_string(),
+            "args(any, optional)".to_string(),
+        ]),
+    }
+}
+
+pub fn measure_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "measure".to_string(),
+        vec!["function".to_string()],
+        Engine::NativeAsync(measure),
+    ))
+}
+
+pub fn measure(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> BoxFuture<'_, Result<Value, VMError>> {
+    Box::pin(async move {
+        let function = params[0].as_function_obj(vm)?;
+        let args = params[1..].to_vec();
+
+        let start = Instant::now();
+        let execution = vm.run_function(&function, None, args, debug).await;
+        let elapsed = start.elapsed();
+        if let Some(err) = execution.error {
+            return Err(err);
+        }
+        let elapsed = TimeDelta::from_std(elapsed).unwrap_or(TimeDelta::MAX);
+        if debug {
+            vm.trace(format!(
+                "TIME.MEASURE -> {} took {}",
+                function.identifier,
+                format_duration(elapsed)
+            ));
+        }
+
+        let mut fields = HashMap::new();
+        fields.insert(
+            "result".to_string(),
+            execution
+                .result
+                .unwrap_or(Value::RawValue(RawValue::Nothing)),
+        );
+        fields.insert("elapsed".to_string(), alloc_duration(vm, elapsed));
+        Ok(Value::Handle(vm.memory.alloc(MemObject::StructLiteral(
+            StructLiteral::new("Measure".to_string(), fields),
+        ))))
+    })
+}
+
+///// Time methods
+fn resolve_time(vm: &Vm, _self: Option<Handle>) -> DateTime<FixedOffset> {
+    let _self = _self.expect("time methods are bound to a time");
+    if let MemObject::NativeStruct(NativeStruct::Time(time)) = vm.memory.resolve(&_self) {
+        time.value
+    } else {
+        unreachable!()
+    }
+}
+
+pub fn format_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "format".to_string(),
+        vec![],
+        Engine::Native(format),
+    ))
+}
+
+// format() gives RFC 3339, format(pattern) uses the strftime one
+pub fn format(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    _debug: bool,
+) -> Result<Value, VMError> {
+    let time = resolve_time(vm, _self);
+    let text = match params.first() {
+        Some(pattern) => {
+            let pattern = pattern.as_string_obj(vm)?;
+            check_pattern(vm, &pattern)?;
+            time.format(&pattern).to_string()
+        }
+        None => time.to_rfc3339(),
+    };
+    Ok(Value::Handle(put_string(vm, text)))
+}
+
+pub fn unix_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "unix".to_string(),
+        vec![],
+        Engine::Native(unix),
+    ))
+}
+
+pub fn unix(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    _params: Vec<Value>,
+    _debug: bool,
+) -> Result<Value, VMError> {
+    let time = resolve_time(vm, _self);
+    Ok(Value::RawValue(RawValue::I64(I64::new(time.timestamp()))))
+}
+
+pub fn unix_ms_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "unix_ms".to_string(),
+        vec![],
+        Engine::Native(unix_ms),
+    ))
+}
+
+pub fn unix_ms(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    _params: Vec<Value>,
+    _debug: bool,
+) -> Result<Value, VMError> {
+    let time = resolve_time(vm, _self);
+    Ok(Value::RawValue(RawValue::I64(I64::new(
+        time.timestamp_millis(),
+    ))))
+}
+
+pub fn time_add_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "add".to_string(),
+        vec!["duration".to_string()],
+        Engine::Native(time_add),
+    ))
+}
+
+pub fn time_add(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    _debug: bool,
+) -> Result<Value, VMError> {
+    let time = resolve_time(vm, _self);
+    let duration = duration_value(vm, &params[0])?;
+    let result = time
+        .checked_add_signed(duration)
+        .ok_or_else(|| out_of_range(vm, format!("{} + {}", time, format_duration(duration))))?;
+    Ok(alloc_time(vm, result))
+}
+
+pub fn time_sub_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "sub".to_string(),
+        vec!["other".to_string()],
+        Engine::Native(time_sub),
+    ))
+}
+
+// sub(time) gives the Duration between both, sub(duration)
+// the Time that much earlier
+pub fn time_sub(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    _debug: bool,
+) -> Result<Value, VMError> {
+    let time = resolve_time(vm, _self);
+    if let Some(other) = as_time(vm, &params[0]) {
+        return Ok(alloc_duration(vm, time.signed_duration_since(other)));
+    }
+    let duration = duration_value(vm, &params[0])?;
+    let result = time
+        .checked_sub_signed(duration)
+        .ok_or_else(|| out_of_range(vm, format!("{} - {}", time, format_duration(duration))))?;
+    Ok(alloc_time(vm, result))
+}
+
+pub fn in_zone_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "in_zone".to_string(),
+        vec!["zone".to_string()],
+        Engine::Native(in_zone),
+    ))
+}
+
+// the same instant as seen from another zone
+pub fn in_zone(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    _debug: bool,
+) -> Result<Value, VMError> {
+    let time = resolve_time(vm, _self);
+    let zone = zone_param(vm, &params[0])?;
+    Ok(alloc_time(vm, zone.convert(&time)))
+}
+
+pub fn before_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "before".to_string(),
+        vec!["other".to_string()],
+        Engine::Native(before),
+    ))
+}
+
+pub fn before(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    _debug: bool,
+) -> Result<Value, VMError> {
+    let time = resolve_time(vm, _self);
+    let other = time_param(vm, &params[0])?;
+    Ok(Value::RawValue(RawValue::Bool(Bool::new(time < other))))
+}
+
+pub fn after_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "after".to_string(),
+        vec!["other".to_string()],
+        Engine::Native(after),
+    ))
+}
+
+pub fn after(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    _debug: bool,
+) -> Result<Value, VMError> {
+    let time = resolve_time(vm, _self);
+    let other = time_param(vm, &params[0])?;
+    Ok(Value::RawValue(RawValue::Bool(Bool::new(time > other))))
+}
+
+///// Duration methods
+fn resolve_duration(vm: &Vm, _self: Option<Handle>) -> TimeDelta {
+    let _self = _self.expect("duration methods are bound to a duration");
+    if let MemObject::NativeStruct(NativeStruct::TimeDuration(duration)) = vm.memory.resolve(&_self)
+    {
+        duration.value
+    } else {
+        unreachable!()
+    }
+}
+
+pub fn ms_method_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "ms".to_string(),
+        vec![],
+        Engine::Native(ms_method),
+    ))
+}
+
+// whole milliseconds
+pub fn ms_method(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    _params: Vec<Value>,
+    _debug: bool,
+) -> Result<Value, VMError> {
+    let duration = resolve_duration(vm, _self);
+    Ok(Value::RawValue(RawValue::I64(I64::new(
+        duration.num_milliseconds(),
+    ))))
+}
+
+pub fn seconds_method_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "seconds".to_string(),
+        vec![],
+        Engine::Native(seconds_method),
+    ))
+}
+
+// seconds with decimals
+pub fn seconds_method(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    _params: Vec<Value>,
+    _debug: bool,
+) -> Result<Value, VMError> {
+    let duration = resolve_duration(vm, _self);
+    let seconds = duration.num_seconds() as f64 + duration.subsec_nanos() as f64 / 1e9;
+    Ok(Value::RawValue(RawValue::F64(F64::new(seconds))))
+}
+
+pub fn duration_add_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "add".to_string(),
+        vec!["duration".to_string()],
+        Engine::Native(duration_add),
+    ))
+}
+
+pub fn duration_add(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    _debug: bool,
+) -> Result<Value, VMError> {
+    let duration = resolve_duration(vm, _self);
+    let other = duration_value(vm, &params[0])?;
+    let result = duration
+        .checked_add(&other)
+        .ok_or_else(|| out_of_range(vm, "duration overflow".to_string()))?;
+    Ok(alloc_duration(vm, result))
+}
+
+pub fn duration_sub_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "sub".to_string(),
+        vec!["duration".to_string()],
+        Engine::Native(duration_sub),
+    ))
+}
+
+pub fn duration_sub(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    _debug: bool,
+) -> Result<Value, VMError> {
+    let duration = resolve_duration(vm, _self);
+    let other = duration_value(vm, &params[0])?;
+    let result = duration
+        .checked_sub(&other)
+        .ok_or_else(|| out_of_range(vm, "duration overflow".to_string()))?;
+    Ok(alloc_duration(vm, result))
+}
+
+pub fn duration_mul_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "mul".to_string(),
+        vec!["factor".to_string()],
+        Engine::Native(duration_mul),
+    ))
+}
+
+// mul(factor), the factor can have decimals
+pub fn duration_mul(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    _debug: bool,
+) -> Result<Value, VMError> {
+    let duration = resolve_duration(vm, _self);
+    let factor = number_param(vm, &params[0])?;
+    let nanos = duration
+        .num_nanoseconds()
+        .map(|nanos| nanos as f64 * factor)
+        .filter(|nanos| nanos.is_finite() && nanos.abs() < i64::MAX as f64)
+        .ok_or_else(|| out_of_range(vm, "duration overflow".to_string()))?;
+    Ok(alloc_duration(
+        vm,
+        TimeDelta::nanoseconds(nanos.round() as i64),
+    ))
+}
+
+///// Monotonic methods
+pub fn elapsed_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "elapsed".to_string(),
+        vec![],
+        Engine::Native(elapsed),
+    ))
+}
+
+pub fn elapsed(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    _params: Vec<Value>,
+    _debug: bool,
+) -> Result<Value, VMError> {
+    let _self = _self.expect("monotonic methods are bound to a reading");
+    let MemObject::NativeStruct(NativeStruct::Monotonic(monotonic)) = vm.memory.resolve(&_self)
+    else {
+        unreachable!()
+    };
+    let elapsed = TimeDelta::from_std(monotonic.value.elapsed()).unwrap_or(TimeDelta::MAX);
+    Ok(alloc_duration(vm, elapsed))
+}
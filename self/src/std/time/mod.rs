@@ -0,0 +1,54 @@
+mod members;
+pub mod types;
+pub mod utils;
+
+use crate::{
+    memory::MemObject,
+    std::{
+        time::members::{
+            duration_def, duration_obj, from_unix_def, from_unix_ms_def, from_unix_ms_obj,
+            from_unix_obj, hours_obj, measure_def, measure_obj, minutes_obj, monotonic_def,
+            monotonic_obj, ms_obj, now_def, now_obj, parse_def, parse_obj, seconds_obj, unit_def,
+        },
+        NativeModuleDef,
+    },
+};
+
+pub fn generate_struct() -> (String, Vec<(String, MemObject)>) {
+    let mut fields = vec![];
+
+    fields.push(("now".to_string(), now_obj()));
+    fields.push(("monotonic".to_string(), monotonic_obj()));
+    fields.push(("from_unix".to_string(), from_unix_obj()));
+    fields.push(("from_unix_ms".to_string(), from_unix_ms_obj()));
+    fields.push(("parse".to_string(), parse_obj()));
+    fields.push(("duration".to_string(), duration_obj()));
+    fields.push(("ms".to_string(), ms_obj()));
+    fields.push(("seconds".to_string(), seconds_obj()));
+    fields.push(("minutes".to_string(), minutes_obj()));
+    fields.push(("hours".to_string(), hours_obj()));
+    fields.push(("measure".to_string(), measure_obj()));
+
+    ("time".to_string(), fields)
+}
+
+pub fn generate_mod_def() -> NativeModuleDef {
+    let members = vec![
+        now_def(),
+        monotonic_def(),
+        from_unix_def(),
+        from_unix_ms_def(),
+        parse_def(),
+        duration_def(),
+        unit_def("ms"),
+        unit_def("seconds"),
+        unit_def("minutes"),
+        unit_def("hours"),
+        measure_def(),
+    ];
+
+    NativeModuleDef {
+        module: "time".to_string(),
+        members,
+    }
+}
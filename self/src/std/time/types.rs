@@ -0,0 +1,164 @@
+use std::{collections::HashMap, time::Instant};
+
+use chrono::{DateTime, Datelike, FixedOffset, SecondsFormat, TimeDelta, Timelike};
+
+use crate::{
+    memory::MemObject,
+    std::{
+        heap_utils::put_string,
+        time::{
+            members::{
+                after_obj, before_obj, duration_add_obj, duration_mul_obj, duration_sub_obj,
+                elapsed_obj, format_obj, in_zone_obj, ms_method_obj, seconds_method_obj,
+                time_add_obj, time_sub_obj, unix_ms_obj, unix_obj,
+            },
+            utils::format_duration,
+        },
+    },
+    types::{
+        object::structs::StructLiteral,
+        raw::{i32::I32, u32::U32, RawValue},
+        Value,
+    },
+    vm::Vm,
+};
+
+fn insert_methods(
+    vm: &mut Vm,
+    fields: &mut HashMap<String, Value>,
+    methods: Vec<(&str, MemObject)>,
+) {
+    for (name, obj) in methods {
+        let handle = vm.memory.alloc(obj);
+        fields.insert(name.to_string(), Value::Handle(handle));
+    }
+}
+
+// a point in time with the offset it's shown in, from `time.now`,
+// `time.parse` and the like
+#[derive(Debug)]
+pub struct Time {
+    pub value: DateTime<FixedOffset>,
+    pub shape: StructLiteral,
+}
+
+impl Time {
+    pub fn new_initialized(vm: &mut Vm, value: DateTime<FixedOffset>) -> Time {
+        let mut fields = HashMap::new();
+        fields.insert(
+            "year".to_string(),
+            Value::RawValue(RawValue::I32(I32::new(value.year()))),
+        );
+        for (name, component) in [
+            ("month", value.month()),
+            ("day", value.day()),
+            ("hour", value.hour()),
+            ("minute", value.minute()),
+            ("second", value.second()),
+        ] {
+            fields.insert(
+                name.to_string(),
+                Value::RawValue(RawValue::U32(U32::new(component))),
+            );
+        }
+        let weekday_handle = put_string(vm, value.format("%A").to_string());
+        fields.insert("weekday".to_string(), Value::Handle(weekday_handle));
+        let offset_handle = put_string(vm, value.offset().to_string());
+        fields.insert("offset".to_string(), Value::Handle(offset_handle));
+        insert_methods(
+            vm,
+            &mut fields,
+            vec![
+                ("format", format_obj()),
+                ("unix", unix_obj()),
+                ("unix_ms", unix_ms_obj()),
+                ("add", time_add_obj()),
+                ("sub", time_sub_obj()),
+                ("in_zone", in_zone_obj()),
+                ("before", before_obj()),
+                ("after", after_obj()),
+            ],
+        );
+
+        Time {
+            value,
+            shape: StructLiteral::new("Time".to_string(), fields),
+        }
+    }
+
+    pub fn to_string(&self) -> String {
+        self.value.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+    }
+
+    pub fn property_access(&self, property: &str) -> Option<Value> {
+        self.shape.property_access(property)
+    }
+}
+
+// signed, subtracting a later time gives a negative one
+#[derive(Debug)]
+pub struct TimeDuration {
+    pub value: TimeDelta,
+    pub shape: StructLiteral,
+}
+
+impl TimeDuration {
+    pub fn new_initialized(vm: &mut Vm, value: TimeDelta) -> TimeDuration {
+        let mut fields = HashMap::new();
+        insert_methods(
+            vm,
+            &mut fields,
+            vec![
+                ("ms", ms_method_obj()),
+                ("seconds", seconds_method_obj()),
+                ("add", duration_add_obj()),
+                ("sub", duration_sub_obj()),
+                ("mul", duration_mul_obj()),
+            ],
+        );
+
+        TimeDuration {
+            value,
+            shape: StructLiteral::new("Duration".to_string(), fields),
+        }
+    }
+
+    pub fn to_string(&self) -> String {
+        format_duration(self.value)
+    }
+
+    pub fn property_access(&self, property: &str) -> Option<Value> {
+        self.shape.property_access(property)
+    }
+}
+
+// reading of the monotonic clock from `time.monotonic`, only
+// useful to measure the time elapsed since then
+#[derive(Debug)]
+pub struct Monotonic {
+    pub value: Instant,
+    pub shape: StructLiteral,
+}
+
+impl Monotonic {
+    pub fn new_initialized(vm: &mut Vm, value: Instant) -> Monotonic {
+        let mut fields = HashMap::new();
+        insert_methods(vm, &mut fields, vec![("elapsed", elapsed_obj())]);
+
+        Monotonic {
+            value,
+            shape: StructLiteral::new("Monotonic".to_string(), fields),
+        }
+    }
+
+    pub fn to_string(&self) -> String {
+        format!(
+            "Monotonic({} ago)",
+            format_duration(TimeDelta::from_std(self.value.elapsed()).unwrap_or(TimeDelta::MAX))
+        )
+    }
+
+    pub fn property_access(&self, property: &str) -> Option<Value> {
+        self.shape.property_access(property)
+    }
+}
@@ -0,0 +1,221 @@
+use chrono::{DateTime, FixedOffset, Local, NaiveDateTime, TimeDelta, TimeZone};
+use chrono_tz::Tz;
+
+use crate::{
+    core::error::{self, time_errors::TimeError, VMError, VMErrorType},
+    memory::MemObject,
+    types::{object::native_struct::NativeStruct, raw::RawValue, Value},
+    vm::Vm,
+};
+
+// zones are utc, local, a fixed offset like +02:00 or an iana
+// name like Europe/Paris, which follows its dst changes
+pub enum Zone {
+    Local,
+    Fixed(FixedOffset),
+    Named(Tz),
+}
+
+impl Zone {
+    pub fn parse(text: &str) -> Option<Zone> {
+        match text.to_lowercase().as_str() {
+            "local" => Some(Zone::Local),
+            "utc" | "gmt" | "z" => Some(Zone::Fixed(FixedOffset::east_opt(0)?)),
+            _ => parse_offset(text)
+                .map(Zone::Fixed)
+                .or_else(|| text.parse::<Tz>().ok().map(Zone::Named)),
+        }
+    }
+
+    pub fn convert(&self, time: &DateTime<FixedOffset>) -> DateTime<FixedOffset> {
+        match self {
+            Zone::Local => time.with_timezone(&Local).fixed_offset(),
+            Zone::Fixed(offset) => time.with_timezone(offset),
+            Zone::Named(tz) => time.with_timezone(tz).fixed_offset(),
+        }
+    }
+
+    // none for local times skipped by a dst change
+    pub fn from_naive(&self, naive: &NaiveDateTime) -> Option<DateTime<FixedOffset>> {
+        match self {
+            Zone::Local => Local
+                .from_local_datetime(naive)
+                .earliest()
+                .map(|time| time.fixed_offset()),
+            Zone::Fixed(offset) => offset.from_local_datetime(naive).single(),
+            Zone::Named(tz) => tz
+                .from_local_datetime(naive)
+                .earliest()
+                .map(|time| time.fixed_offset()),
+        }
+    }
+}
+
+// +02:00, -0530 or +02
+fn parse_offset(text: &str) -> Option<FixedOffset> {
+    let sign = match text.chars().next()? {
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+    let digits: String = text[1..].chars().filter(|c| *c != ':').collect();
+    if !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let (hours, minutes) = match digits.len() {
+        2 => (digits.parse::<i32>().ok()?, 0),
+        4 => (
+            digits[..2].parse::<i32>().ok()?,
+            digits[2..].parse::<i32>().ok()?,
+        ),
+        _ => return None,
+    };
+    if minutes >= 60 {
+        return None;
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+// go-like durations: 1h30m, 1.5s, 250ms, -10m. units are ns, us,
+// ms, s, m, h and d
+pub fn parse_duration(text: &str) -> Option<TimeDelta> {
+    let text = text.trim();
+    let (negative, mut rest) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    if rest == "0" {
+        return Some(TimeDelta::zero());
+    }
+    if rest.is_empty() {
+        return None;
+    }
+
+    let mut nanos: i64 = 0;
+    while !rest.is_empty() {
+        let number_len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let (number, tail) = rest.split_at(number_len);
+        let unit_len = tail
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(unit_len);
+        rest = tail;
+
+        let scale: i64 = match unit {
+            "ns" => 1,
+            "us" | "µs" => 1_000,
+            "ms" => 1_000_000,
+            "s" => 1_000_000_000,
+            "m" => 60 * 1_000_000_000,
+            "h" => 3_600 * 1_000_000_000,
+            "d" => 86_400 * 1_000_000_000,
+            _ => return None,
+        };
+        let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+        if whole.is_empty() && fraction.is_empty() {
+            return None;
+        }
+        let whole = if whole.is_empty() {
+            0
+        } else {
+            whole.parse::<i64>().ok()?
+        };
+        let fraction = if fraction.is_empty() {
+            0.0
+        } else {
+            format!("0.{}", fraction).parse::<f64>().ok()?
+        };
+        nanos = whole
+            .checked_mul(scale)?
+            .checked_add((fraction * scale as f64).round() as i64)
+            .and_then(|part| nanos.checked_add(part))?;
+    }
+
+    let delta = TimeDelta::nanoseconds(nanos);
+    Some(if negative { -delta } else { delta })
+}
+
+// the shortest of 1h30m0s, 1.5s, 250ms, 20µs or 0s
+pub fn format_duration(delta: TimeDelta) -> String {
+    let sign = if delta < TimeDelta::zero() { "-" } else { "" };
+    let delta = delta.abs();
+    let seconds = delta.num_seconds();
+    let nanos = delta.subsec_nanos() as i64;
+
+    if seconds == 0 {
+        return match nanos {
+            0 => "0s".to_string(),
+            1_000_000.. => format!("{}{}ms", sign, nanos as f64 / 1e6),
+            1_000.. => format!("{}{}µs", sign, nanos as f64 / 1e3),
+            _ => format!("{}{}ns", sign, nanos),
+        };
+    }
+
+    let mut text = sign.to_string();
+    let (hours, minutes) = (seconds / 3600, seconds % 3600 / 60);
+    if hours > 0 {
+        text.push_str(&format!("{}h", hours));
+    }
+    if hours > 0 || minutes > 0 {
+        text.push_str(&format!("{}m", minutes));
+    }
+    let seconds = (seconds % 60) as f64 + nanos as f64 / 1e9;
+    text.push_str(&format!("{}s", seconds));
+    text
+}
+
+pub fn number_param(vm: &Vm, value: &Value) -> Result<f64, VMError> {
+    match value {
+        Value::RawValue(RawValue::I32(v)) => Ok(v.value as f64),
+        Value::RawValue(RawValue::I64(v)) => Ok(v.value as f64),
+        Value::RawValue(RawValue::U32(v)) => Ok(v.value as f64),
+        Value::RawValue(RawValue::U64(v)) => Ok(v.value as f64),
+        Value::RawValue(RawValue::F64(v)) => Ok(v.value),
+        _ => Err(error::throw(
+            VMErrorType::TypeMismatch {
+                expected: "number".to_string(),
+                received: value.get_resolved_type(vm),
+            },
+            vm,
+        )),
+    }
+}
+
+// durations are passed as Duration values, texts like 1h30m or
+// numbers of ms
+pub fn duration_value(vm: &Vm, value: &Value) -> Result<TimeDelta, VMError> {
+    if let Value::Handle(handle) = value {
+        if let MemObject::NativeStruct(NativeStruct::TimeDuration(duration)) =
+            vm.memory.resolve(handle)
+        {
+            return Ok(duration.value);
+        }
+        let text = value.as_string_obj(vm)?;
+        return parse_duration(&text)
+            .ok_or_else(|| error::throw(VMErrorType::Time(TimeError::InvalidDuration(text)), vm));
+    }
+    let ms = number_param(vm, value)?;
+    if !ms.is_finite() || ms.abs() >= i64::MAX as f64 / 1e6 {
+        return Err(error::throw(
+            VMErrorType::Time(TimeError::OutOfRange(format!("{}ms", ms))),
+            vm,
+        ));
+    }
+    Ok(TimeDelta::nanoseconds((ms * 1e6).round() as i64))
+}
+
+// waits (like schedule.timeout) can't be negative
+pub fn duration_param(vm: &Vm, value: &Value) -> Result<std::time::Duration, VMError> {
+    let delta = duration_value(vm, value)?;
+    delta.to_std().map_err(|_| {
+        error::throw(
+            VMErrorType::Time(TimeError::OutOfRange(format!(
+                "{} is a negative duration",
+                format_duration(delta)
+            ))),
+            vm,
+        )
+    })
+}
@@ -1,14 +1,100 @@
+use std::collections::HashMap;
+
 use serde_json::Value as JsonValue;
 
-use crate::types::{
-    raw::{bool::Bool, utf8::Utf8, RawValue},
-    Value,
+use crate::{
+    memory::MemObject,
+    std::heap_utils::{put_string, put_vector},
+    types::{
+        object::structs::StructLiteral,
+        raw::{bool::Bool, f64::F64, i32::I32, i64::I64, utf8::Utf8, RawValue},
+        Value,
+    },
+    vm::Vm,
 };
 
 pub fn cast_json_value(json: &JsonValue) -> Option<Value> {
     match json {
         JsonValue::String(x) => Some(Value::RawValue(RawValue::Utf8(Utf8::new(x.clone())))),
         JsonValue::Bool(x) => Some(Value::RawValue(RawValue::Bool(Bool::new(x.clone())))),
+        JsonValue::Number(n) => match n.as_i64() {
+            Some(v) if v >= i32::MIN as i64 && v <= i32::MAX as i64 => {
+                Some(Value::RawValue(RawValue::I32(I32::new(v as i32))))
+            }
+            _ => Some(Value::RawValue(RawValue::F64(F64::new(n.as_f64()?)))),
+        },
         _ => None,
     }
 }
+
+// allocates any json value on the vm heap. numbers follow the
+// same rules than the compiler does for literals (i32 when it
+// fits, f64 otherwise) so they can be operated with ego values
+pub fn json_to_value(vm: &mut Vm, json: &JsonValue) -> Value {
+    match json {
+        JsonValue::Null => Value::RawValue(RawValue::Nothing),
+        JsonValue::Bool(x) => Value::RawValue(RawValue::Bool(Bool::new(*x))),
+        JsonValue::Number(n) => {
+            if let Some(v) = n.as_i64() {
+                if v >= i32::MIN as i64 && v <= i32::MAX as i64 {
+                    Value::RawValue(RawValue::I32(I32::new(v as i32)))
+                } else {
+                    Value::RawValue(RawValue::I64(I64::new(v)))
+                }
+            } else {
+                Value::RawValue(RawValue::F64(F64::new(n.as_f64().unwrap_or(0.0))))
+            }
+        }
+        JsonValue::String(x) => Value::Handle(put_string(vm, x.clone())),
+        JsonValue::Array(items) => {
+            let elements = items.iter().map(|i| json_to_value(vm, i)).collect();
+            Value::Handle(put_vector(vm, elements))
+        }
+        JsonValue::Object(map) => {
+            let mut fields = HashMap::new();
+            for (k, v) in map {
+                let value = json_to_value(vm, v);
+                fields.insert(k.clone(), value);
+            }
+            Value::Handle(vm.memory.alloc(MemObject::StructLiteral(StructLiteral::new(
+                "StructLiteral".to_string(),
+                fields,
+            ))))
+        }
+    }
+}
+
+// inverse of json_to_value. handles that cannot be represented
+// as json (functions, native structs) are sent as their string form
+pub fn value_to_json(vm: &Vm, value: &Value) -> JsonValue {
+    match value {
+        Value::RawValue(raw) => match raw {
+            RawValue::I32(v) => JsonValue::from(v.value),
+            RawValue::I64(v) => JsonValue::from(v.value),
+            RawValue::U32(v) => JsonValue::from(v.value),
+            RawValue::U64(v) => JsonValue::from(v.value),
+            RawValue::F64(v) => JsonValue::from(v.value),
+            RawValue::Utf8(v) => JsonValue::String(v.value.clone()),
+            RawValue::Bool(v) => JsonValue::Bool(v.value),
+            RawValue::Nothing => JsonValue::Null,
+        },
+        Value::BoundAccess(b) => value_to_json(vm, &b.property),
+        Value::Handle(h) => match vm.memory.resolve(h) {
+            MemObject::String(s) => JsonValue::String(s.value.clone()),
+            MemObject::Vector(v) => {
+                JsonValue::Array(v.elements.iter().map(|e| value_to_json(vm, e)).collect())
+            }
+            MemObject::Bytes(b) => {
+                JsonValue::Array(b.value.iter().map(|b| JsonValue::from(*b)).collect())
+            }
+            MemObject::StructLiteral(s) => {
+                let mut map = serde_json::Map::new();
+                for (k, v) in &s.fields {
+                    map.insert(k.clone(), value_to_json(vm, v));
+                }
+                JsonValue::Object(map)
+            }
+            other => JsonValue::String(other.to_string(vm)),
+        },
+    }
+}
@@ -200,7 +200,7 @@ fn open(
             .map_err(|e| error::throw(VMErrorType::Any(e), vm))?;
 
         if debug {
-            println!("BROWSER.open -> {} bytes", html.len());
+            vm.trace(format!("BROWSER.open -> {} bytes", html.len()));
         }
 
         let content_obj = SelfString::new(html, vm);
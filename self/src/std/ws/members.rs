@@ -0,0 +1,474 @@
+use std::collections::HashMap;
+
+use async_tungstenite::tokio::{accept_async, client_async};
+use async_tungstenite::tungstenite::client::IntoClientRequest;
+use async_tungstenite::tungstenite::http::{HeaderName, HeaderValue};
+use async_tungstenite::tungstenite::Message;
+use futures::future::BoxFuture;
+use futures::{SinkExt, StreamExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio_rustls::TlsAcceptor;
+
+use crate::core::error::type_errors::TypeError;
+use crate::core::error::ws_errors::WsErrors;
+use crate::core::error::{self, VMErrorType};
+use crate::events::{ArgsBuilder, Event};
+use crate::memory::Handle;
+use crate::std::heap_utils::{put_bytes, put_string};
+use crate::std::net::utils::{listen_options, port_param, tls};
+use crate::std::ws::types::{WsConnection, WsServer, WsSocket, WsTransport};
+use crate::std::NativeMember;
+use crate::types::object::native_struct::NativeStruct;
+use crate::types::raw::u64::U64;
+use crate::types::raw::RawValue;
+use crate::{
+    core::error::VMError,
+    memory::MemObject,
+    types::{
+        object::func::{Engine, Function},
+        Value,
+    },
+    vm::Vm,
+};
+
+fn ws_error(err: WsErrors, vm: &Vm) -> VMError {
+    error::throw(VMErrorType::Ws(err), vm)
+}
+
+// connect
+pub fn connect_def() -> NativeMember {
+    NativeMember {
+        name: "connect".to_string(),
+        description: "open a WebSocket connection to a ws:// or wss:// url. Options: {headers(struct)}, underscores in the header names are sent as dashes. Returns a socket with send(data), on_message(callback) and close(). The callback gets each message and the socket, text frames are received as strings and binary frames as bytes".to_string(),
+        params: Some(vec![
+            "url(string)".to_string(),
+            "options(struct, optional)".to_string(),
+        ]),
+    }
+}
+
+pub fn connect_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "connect".to_string(),
+        vec!["url".to_string()],
+        Engine::NativeAsync(connect),
+    ))
+}
+
+// options struct: { headers }, header names can't have dashes
+// as struct fields, so x_api_key is sent as x-api-key
+fn connect_headers(vm: &Vm, value: Option<&Value>) -> Result<Vec<(String, String)>, VMError> {
+    let mut headers = vec![];
+    let Some(value) = value else {
+        return Ok(headers);
+    };
+    if let Value::RawValue(RawValue::Nothing) = value {
+        return Ok(headers);
+    }
+    let fields = value.as_struct_obj(vm)?;
+    let Some(header_fields) = fields.fields.get("headers") else {
+        return Ok(headers);
+    };
+    if let Value::RawValue(RawValue::Nothing) = header_fields {
+        return Ok(headers);
+    }
+    for (name, header) in &header_fields.as_struct_obj(vm)?.fields {
+        headers.push((name.replace('_', "-"), header.as_string_obj(vm)?));
+    }
+    Ok(headers)
+}
+
+// connect(url) or connect(url, options)
+pub fn connect(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> BoxFuture<'_, Result<Value, VMError>> {
+    Box::pin(async move {
+        let url = params[0].as_string_obj(vm)?;
+        let headers = connect_headers(vm, params.get(1))?;
+        if debug {
+            vm.trace(format!("WS.CONNECT <- {} ({} headers)", url, headers.len()));
+        }
+
+        let mut request = url
+            .as_str()
+            .into_client_request()
+            .map_err(|_| ws_error(WsErrors::InvalidUrl(url.clone()), vm))?;
+        for (name, value) in headers {
+            let header_name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| ws_error(WsErrors::InvalidHeader(name.clone()), vm))?;
+            let header_value = HeaderValue::from_str(&value)
+                .map_err(|_| ws_error(WsErrors::InvalidHeader(format!("value of {}", name)), vm))?;
+            request.headers_mut().insert(header_name, header_value);
+        }
+
+        let secure = match request.uri().scheme_str() {
+            Some("ws") => false,
+            Some("wss") => true,
+            _ => return Err(ws_error(WsErrors::InvalidUrl(url), vm)),
+        };
+        let Some(domain) = request.uri().host() else {
+            return Err(ws_error(WsErrors::InvalidUrl(url), vm));
+        };
+        let port = request
+            .uri()
+            .port_u16()
+            .unwrap_or(if secure { 443 } else { 80 });
+        let host = format!("{}:{}", domain, port);
+
+        let connection = vm
+            .with_events(async {
+                let transport: Box<dyn WsTransport> = if secure {
+                    Box::new(tls(&host).await?)
+                } else {
+                    Box::new(TcpStream::connect(&host).await.map_err(|e| e.to_string())?)
+                };
+                client_async(request, transport)
+                    .await
+                    .map(|(connection, _)| connection)
+                    .map_err(|e| e.to_string())
+            })
+            .await?
+            .map_err(|message| {
+                ws_error(
+                    WsErrors::ConnectError {
+                        url: url.clone(),
+                        message,
+                    },
+                    vm,
+                )
+            })?;
+
+        Ok(Value::Handle(alloc_socket(vm, url, connection)))
+    })
+}
+
+fn alloc_socket(vm: &mut Vm, url: String, connection: WsConnection) -> Handle {
+    let mut shape = HashMap::new();
+    let url_ref = put_string(vm, url.clone());
+    shape.insert("url".to_string(), Value::Handle(url_ref));
+    let send_ref = vm.memory.alloc(MemObject::Function(Function::new(
+        "send".to_string(),
+        vec!["data".to_string()],
+        Engine::NativeAsync(send),
+    )));
+    shape.insert("send".to_string(), Value::Handle(send_ref));
+    let on_message_ref = vm.memory.alloc(MemObject::Function(Function::new(
+        "on_message".to_string(),
+        vec!["callback".to_string()],
+        Engine::Native(on_message),
+    )));
+    shape.insert("on_message".to_string(), Value::Handle(on_message_ref));
+    let close_ref = vm.memory.alloc(MemObject::Function(Function::new(
+        "close".to_string(),
+        vec![],
+        Engine::NativeAsync(close),
+    )));
+    shape.insert("close".to_string(), Value::Handle(close_ref));
+
+    let socket = WsSocket::new(url, connection, shape);
+    vm.memory
+        .alloc(MemObject::NativeStruct(NativeStruct::WsSocket(socket)))
+}
+
+fn resolve_socket(vm: &mut Vm, _self: Option<Handle>) -> &mut WsSocket {
+    let _self = _self.expect("ws socket methods are bound to a socket");
+    if let MemObject::NativeStruct(NativeStruct::WsSocket(s)) = vm.memory.resolve_mut(&_self) {
+        s
+    } else {
+        unreachable!()
+    }
+}
+
+// send(data), strings are sent as text frames and bytes as
+// binary ones
+fn send(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> BoxFuture<'_, Result<Value, VMError>> {
+    Box::pin(async move {
+        let message = match params[0].as_bytes_obj(vm) {
+            Ok(data) => Message::Binary(data),
+            Err(_) => Message::Text(params[0].as_string_obj(vm)?),
+        };
+        let len = message.len();
+        let socket = resolve_socket(vm, _self);
+        let (url, sink) = (socket.url.clone(), socket.sink.clone());
+        if debug {
+            vm.trace(format!("WS.SEND <- {} ({} bytes)", url, len));
+        }
+        let mut sink = sink
+            .try_lock_owned()
+            .map_err(|_| ws_error(WsErrors::Busy(url.clone()), vm))?;
+
+        vm.with_events(async {
+            let sink = sink.as_mut().ok_or_else(|| WsErrors::Closed(url.clone()))?;
+            sink.send(message).await.map_err(|e| WsErrors::SendError {
+                url: url.clone(),
+                message: e.to_string(),
+            })
+        })
+        .await?
+        .map_err(|e| ws_error(e, vm))?;
+        Ok(Value::RawValue(RawValue::U64(U64::new(len as u64))))
+    })
+}
+
+// on_message(callback), the first call starts reading. later
+// calls replace the callback, which gets the message and the
+// socket, so it can reply
+fn on_message(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let callback = params[0].as_function_obj(vm)?;
+    let handle = _self.expect("ws socket methods are bound to a socket");
+    let socket = resolve_socket(vm, Some(handle.clone()));
+    *socket.callback.lock().unwrap() = Some(callback);
+    let (url, reader, callback) = (
+        socket.url.clone(),
+        socket.reader.take(),
+        socket.callback.clone(),
+    );
+    if debug {
+        vm.trace(format!("WS.ON_MESSAGE <- {}", url));
+    }
+    let Some(mut reader) = reader else {
+        return Ok(Value::RawValue(RawValue::Nothing));
+    };
+    // the reading task hands the socket to every callback, so it
+    // must outlive the scope it was received in (like the socket
+    // of a ws.serve callback). the task releases it once it stops
+    vm.memory
+        .retain(&handle)
+        .map_err(|err| error::throw(err, vm))?;
+
+    let vm_notifier = vm.get_vm_notifier();
+    let keep_alive = vm.keep_alive();
+    let (stop_sender, mut stop) = mpsc::unbounded_channel::<()>();
+    let socket_handle = handle.clone();
+    tokio::spawn(async move {
+        let _keep_alive = keep_alive;
+        loop {
+            let message = tokio::select! {
+                message = reader.next() => message,
+                _ = stop.recv() => break,
+            };
+            let socket = Value::Handle(socket_handle.clone());
+            let build_args: ArgsBuilder = match message {
+                Some(Ok(Message::Text(text))) => {
+                    Box::new(move |vm| vec![Value::Handle(put_string(vm, text)), socket])
+                }
+                Some(Ok(Message::Binary(data))) => {
+                    Box::new(move |vm| vec![Value::Handle(put_bytes(vm, data)), socket])
+                }
+                // pings are answered and the close reply is sent
+                // by the next read, which ends the stream
+                Some(Ok(_)) => continue,
+                Some(Err(_)) | None => break,
+            };
+            let Some(callback) = callback.lock().unwrap().clone() else {
+                continue;
+            };
+            if vm_notifier
+                .send(Event::CallWith(callback, build_args))
+                .is_err()
+            {
+                return;
+            }
+        }
+        // after the callbacks already sent, before the keep alive
+        // guard is dropped
+        let _ = vm_notifier.send(Event::Run(Box::new(move |vm| {
+            let _ = vm.memory.release(&socket_handle);
+        })));
+    });
+    resolve_socket(vm, Some(handle)).stop = Some(stop_sender);
+    Ok(Value::RawValue(RawValue::Nothing))
+}
+
+// sends the close frame and stops reading
+fn close(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    _params: Vec<Value>,
+    debug: bool,
+) -> BoxFuture<'_, Result<Value, VMError>> {
+    Box::pin(async move {
+        let socket = resolve_socket(vm, _self);
+        socket.reader = None;
+        if let Some(stop) = socket.stop.take() {
+            let _ = stop.send(());
+        }
+        let (url, sink) = (socket.url.clone(), socket.sink.clone());
+        if debug {
+            vm.trace(format!("WS.CLOSE <- {}", url));
+        }
+        let mut sink = sink
+            .try_lock_owned()
+            .map_err(|_| ws_error(WsErrors::Busy(url.clone()), vm))?;
+        // the peer may be gone already, there is nothing to report then
+        if let Some(mut sink) = sink.take() {
+            let _ = vm.with_events(sink.close()).await?;
+        }
+        Ok(Value::RawValue(RawValue::Nothing))
+    })
+}
+
+///// serve
+pub fn serve_def() -> NativeMember {
+    NativeMember {
+        name: "serve".to_string(),
+        description: "accept WebSocket connections on the given port and call the callback with a socket for each one, the same socket ws.connect returns. Options: {host(string), cert(string), key(string)}, cert and key are PEM files and serve wss://. Returns a server with host and close()".to_string(),
+        params: Some(vec![
+            "port(number)".to_string(),
+            "options(struct, optional)".to_string(),
+            "on_connection(function)".to_string(),
+        ]),
+    }
+}
+
+pub fn serve_obj() -> MemObject {
+    MemObject::Function(Function::new(
+        "serve".to_string(),
+        vec!["port".to_string(), "on_connection".to_string()],
+        Engine::NativeAsync(serve),
+    ))
+}
+
+// tls (if any) and websocket handshakes of an accepted stream
+async fn accept_connection(
+    stream: TcpStream,
+    tls: Option<TlsAcceptor>,
+) -> Result<WsConnection, String> {
+    let transport: Box<dyn WsTransport> = match tls {
+        Some(acceptor) => Box::new(acceptor.accept(stream).await.map_err(|e| e.to_string())?),
+        None => Box::new(stream),
+    };
+    accept_async(transport).await.map_err(|e| e.to_string())
+}
+
+// serve(port, on_connection) or serve(port, options, on_connection)
+pub fn serve(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    params: Vec<Value>,
+    debug: bool,
+) -> BoxFuture<'_, Result<Value, VMError>> {
+    Box::pin(async move {
+        let port = port_param(vm, &params[0])?;
+        let (options, callback) = match params.len() {
+            2 => (listen_options(vm, None)?, &params[1]),
+            3 => (listen_options(vm, Some(&params[1]))?, &params[2]),
+            received => {
+                return Err(error::throw(
+                    VMErrorType::TypeError(TypeError::InvalidArgsCount {
+                        expected: 3,
+                        received: received as u32,
+                    }),
+                    vm,
+                ))
+            }
+        };
+        let callback = callback.as_function_obj(vm)?;
+        let host = format!("{}:{}", options.host, port);
+        let serve_error = |message: String, vm: &Vm| {
+            ws_error(
+                WsErrors::ServeError {
+                    host: host.clone(),
+                    message,
+                },
+                vm,
+            )
+        };
+
+        let tls = options.tls_acceptor().map_err(|e| serve_error(e, vm))?;
+        let listener = TcpListener::bind(&host)
+            .await
+            .map_err(|e| serve_error(e.to_string(), vm))?;
+        // port 0 binds to a free one
+        let bound = listener
+            .local_addr()
+            .map_err(|e| serve_error(e.to_string(), vm))?
+            .to_string();
+        let scheme = if tls.is_some() { "wss" } else { "ws" };
+        if debug {
+            vm.trace(format!("WS.SERVE <- {}://{}", scheme, bound));
+        }
+
+        let vm_notifier = vm.get_vm_notifier();
+        let keep_alive = vm.keep_alive();
+        let (stop_sender, mut stop) = mpsc::unbounded_channel::<()>();
+        tokio::spawn(async move {
+            let _keep_alive = keep_alive;
+            loop {
+                let accepted = tokio::select! {
+                    accepted = listener.accept() => accepted,
+                    _ = stop.recv() => return,
+                };
+                let Ok((stream, peer)) = accepted else {
+                    continue;
+                };
+                let (vm_notifier, callback, tls) =
+                    (vm_notifier.clone(), callback.clone(), tls.clone());
+                // on its own task, so a slow handshake doesn't hold
+                // the next connections. failed handshakes are dropped
+                tokio::spawn(async move {
+                    let Ok(connection) = accept_connection(stream, tls).await else {
+                        return;
+                    };
+                    let url = format!("{}://{}", scheme, peer);
+                    let _ = vm_notifier.send(Event::CallWith(
+                        callback,
+                        Box::new(move |vm| {
+                            if debug {
+                                vm.trace(format!("WS.ACCEPT -> {}", url));
+                            }
+                            vec![Value::Handle(alloc_socket(vm, url, connection))]
+                        }),
+                    ));
+                });
+            }
+        });
+
+        let mut shape = HashMap::new();
+        let host_ref = put_string(vm, bound.clone());
+        shape.insert("host".to_string(), Value::Handle(host_ref));
+        let close_ref = vm.memory.alloc(MemObject::Function(Function::new(
+            "close".to_string(),
+            vec![],
+            Engine::Native(close_server),
+        )));
+        shape.insert("close".to_string(), Value::Handle(close_ref));
+
+        let server = WsServer::new(bound, stop_sender, shape);
+        Ok(Value::Handle(vm.memory.alloc(MemObject::NativeStruct(
+            NativeStruct::WsServer(server),
+        ))))
+    })
+}
+
+// stops accepting, the accepted sockets stay open
+fn close_server(
+    vm: &mut Vm,
+    _self: Option<Handle>,
+    _params: Vec<Value>,
+    debug: bool,
+) -> Result<Value, VMError> {
+    let _self = _self.expect("ws server methods are bound to a server");
+    let MemObject::NativeStruct(NativeStruct::WsServer(server)) = vm.memory.resolve(&_self) else {
+        unreachable!()
+    };
+    if debug {
+        vm.trace(format!("WS.SERVER.CLOSE <- {}", server.host));
+    }
+    let _ = server.stop.send(());
+    Ok(Value::RawValue(RawValue::Nothing))
+}
@@ -0,0 +1,28 @@
+mod members;
+pub mod types;
+
+use crate::{
+    memory::MemObject,
+    std::{
+        ws::members::{connect_def, connect_obj, serve_def, serve_obj},
+        NativeModuleDef,
+    },
+};
+
+pub fn generate_struct() -> (String, Vec<(String, MemObject)>) {
+    let mut fields = vec![];
+
+    fields.push(("connect".to_string(), connect_obj()));
+    fields.push(("serve".to_string(), serve_obj()));
+
+    ("ws".to_string(), fields)
+}
+
+pub fn generate_mod_def() -> NativeModuleDef {
+    let members = vec![connect_def(), serve_def()];
+
+    NativeModuleDef {
+        module: "ws".to_string(),
+        members,
+    }
+}
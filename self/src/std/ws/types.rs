@@ -0,0 +1,100 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex as StdMutex},
+};
+
+use async_tungstenite::{tokio::TokioAdapter, tungstenite::Message, WebSocketStream};
+use futures::{
+    stream::{SplitSink, SplitStream},
+    StreamExt,
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    sync::{mpsc, Mutex},
+};
+
+use crate::types::{
+    object::{func::Function, structs::StructLiteral},
+    Value,
+};
+
+// plain or tls connection under a websocket
+pub trait WsTransport: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> WsTransport for T {}
+
+pub type WsConnection = WebSocketStream<TokioAdapter<Box<dyn WsTransport>>>;
+pub type WsSink = SplitSink<WsConnection, Message>;
+pub type WsReader = SplitStream<WsConnection>;
+
+// socket from `ws.connect` or handed to the `ws.serve` callback.
+// nothing is read until on_message is called, then a task reads
+// the frames and sends them to the callback as vm events
+pub struct WsSocket {
+    // url for clients, ws://peer for accepted sockets
+    pub url: String,
+    // shared with the pending sends, so the vm can keep handling
+    // events meanwhile. none once closed
+    pub sink: Arc<Mutex<Option<WsSink>>>,
+    // taken by the first on_message
+    pub reader: Option<WsReader>,
+    // replaced by every on_message
+    pub callback: Arc<StdMutex<Option<Function>>>,
+    // stops the reading task, if any
+    pub stop: Option<mpsc::UnboundedSender<()>>,
+    pub shape: StructLiteral,
+}
+
+impl std::fmt::Debug for WsSocket {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WsSocket")
+            .field("url", &self.url)
+            .field("reading", &self.stop.is_some())
+            .finish()
+    }
+}
+
+impl WsSocket {
+    pub fn new(url: String, connection: WsConnection, shape: HashMap<String, Value>) -> WsSocket {
+        let (sink, reader) = connection.split();
+        WsSocket {
+            url,
+            sink: Arc::new(Mutex::new(Some(sink))),
+            reader: Some(reader),
+            callback: Arc::new(StdMutex::new(None)),
+            stop: None,
+            shape: StructLiteral::new("WsSocket".to_string(), shape),
+        }
+    }
+
+    pub fn to_string(&self) -> String {
+        format!("WsSocket({})", self.url)
+    }
+}
+
+// server from `ws.serve`. the accepting task owns the listener,
+// the vm only keeps the stop channel
+#[derive(Debug)]
+pub struct WsServer {
+    pub host: String,
+    pub stop: mpsc::UnboundedSender<()>,
+    pub shape: StructLiteral,
+}
+
+impl WsServer {
+    pub fn new(
+        host: String,
+        stop: mpsc::UnboundedSender<()>,
+        shape: HashMap<String, Value>,
+    ) -> WsServer {
+        WsServer {
+            host,
+            stop,
+            shape: StructLiteral::new("WsServer".to_string(), shape),
+        }
+    }
+
+    pub fn to_string(&self) -> String {
+        format!("WsServer({})", self.host)
+    }
+}
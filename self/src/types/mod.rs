@@ -267,6 +267,21 @@ impl Value {
         }
     }
 
+    pub fn as_bytes_obj(&self, vm: &Vm) -> Result<Vec<u8>, VMError> {
+        if let Value::Handle(r) = self {
+            if let MemObject::Bytes(b) = vm.memory.resolve(&r) {
+                return Ok(b.value.clone());
+            }
+        }
+        Err(error::throw(
+            VMErrorType::TypeMismatch {
+                expected: "bytes".to_string(),
+                received: self.get_resolved_type(vm),
+            },
+            vm,
+        ))
+    }
+
     pub fn as_function_obj(&self, vm: &Vm) -> Result<Function, VMError> {
         match self {
             Value::Handle(r) => {
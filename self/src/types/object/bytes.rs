@@ -0,0 +1,43 @@
+use std::collections::HashMap;
+
+use crate::{std::bytes::add_handlers, types::Value, vm::Vm};
+
+// printed bytes are cut after this length
+const PRINT_LIMIT: usize = 32;
+
+#[derive(Debug, Clone)]
+pub struct Bytes {
+    pub value: Vec<u8>,
+    pub members: HashMap<String, Value>,
+}
+
+impl Bytes {
+    // create new bytes with initialized members like: .len() or .slice()
+    pub fn new(value: Vec<u8>, vm: &mut Vm) -> Bytes {
+        let mut bytes = Bytes {
+            value,
+            members: HashMap::new(),
+        };
+
+        bytes.members = add_handlers(vm);
+        bytes
+    }
+
+    pub fn to_string(&self) -> String {
+        let hex: String = self
+            .value
+            .iter()
+            .take(PRINT_LIMIT)
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        if self.value.len() > PRINT_LIMIT {
+            format!("Bytes({}...; {} bytes)", hex, self.value.len())
+        } else {
+            format!("Bytes({})", hex)
+        }
+    }
+
+    pub fn property_access(&self, property: &str) -> Option<Value> {
+        self.members.get(property).cloned()
+    }
+}
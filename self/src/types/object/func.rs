@@ -1,4 +1,6 @@
-use crate::{core::error::VMError, memory::Handle, types::Value, vm::Vm};
+use crate::{
+    core::error::VMError, memory::Handle, std::native::plugin::PluginFunction, types::Value, vm::Vm,
+};
 use futures::future::BoxFuture;
 
 #[derive(Debug, Clone)]
@@ -13,6 +15,8 @@ pub enum Engine {
             bool,
         ) -> BoxFuture<'a, Result<Value, VMError>>,
     ),
+    // member of a c-abi plugin loaded with native.load_lib
+    Plugin(PluginFunction),
 }
 
 #[derive(Debug, Clone)]
@@ -0,0 +1,21 @@
+use std::{any::Any, fmt::Debug};
+
+use crate::{types::Value, vm::Vm};
+
+// types defined outside of the vm (embedders, extensions) are
+// stored as NativeStruct::Custom. methods are exposed the same
+// way as the builtin native structs: a property holding a handle
+// to a native function, which receives the object as `_self`
+pub trait HostObject: Debug + Send + Sync {
+    fn type_name(&self) -> String;
+
+    fn to_string(&self, _vm: &Vm) -> String {
+        self.type_name()
+    }
+
+    fn property_access(&self, property: &str) -> Option<Value>;
+
+    // allows natives to get back their concrete type
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
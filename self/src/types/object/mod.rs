@@ -1,6 +1,8 @@
 use crate::{memory::Handle, types::Value};
 
+pub mod bytes;
 pub mod func;
+pub mod host;
 pub mod native_struct;
 pub mod string;
 pub mod structs;
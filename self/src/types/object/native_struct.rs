@@ -2,13 +2,19 @@ use crate::{
     core::error::{self, type_errors, VMError, VMErrorType},
     std::{
         ai::types::{Action, Chain, Link, SessionEnd},
+        fs::types::Watcher,
+        http::types::HttpResponse,
+        io::types::StdinLines,
         mcp::types::{McpClient, McpTool},
-        native::types::NativeLib,
-        net::types::{NetServer, NetStream},
+        native::types::{NativeFn, NativeLib},
+        net::types::{NetServer, NetStream, NetUdpSocket},
+        process::types::Process,
         schedule::types::Interval,
+        time::types::{Monotonic, Time, TimeDuration},
         web::types::Browser,
+        ws::types::{WsServer, WsSocket},
     },
-    types::Value,
+    types::{object::host::HostObject, Value},
     vm::Vm,
 };
 
@@ -17,20 +23,39 @@ pub enum NativeStruct {
     // net
     NetServer(NetServer),
     NetStream(NetStream),
+    NetUdpSocket(NetUdpSocket),
+    // time
+    Time(Time),
+    TimeDuration(TimeDuration),
+    Monotonic(Monotonic),
+    // ws
+    WsSocket(WsSocket),
+    WsServer(WsServer),
     // ai
     Action(Action),
     Chain(Chain),
     Link(Link),
     SessionEnd(SessionEnd),
+    // fs
+    Watcher(Watcher),
+    // http
+    HttpResponse(HttpResponse),
+    // io
+    StdinLines(StdinLines),
     // mcp
     McpClient(McpClient),
     McpTool(McpTool),
     // native
     NativeLib(NativeLib),
+    NativeFn(NativeFn),
+    // process
+    Process(Process),
     // schedule
     Interval(Interval),
     // web
     Browser(Browser),
+    // defined outside of the vm, see HostObject
+    Custom(Box<dyn HostObject>),
 }
 
 impl NativeStruct {
@@ -38,15 +63,27 @@ impl NativeStruct {
         match self {
             NativeStruct::NetStream(x) => x.to_string(),
             NativeStruct::NetServer(x) => x.to_string(),
+            NativeStruct::NetUdpSocket(x) => x.to_string(),
+            NativeStruct::Time(x) => x.to_string(),
+            NativeStruct::TimeDuration(x) => x.to_string(),
+            NativeStruct::Monotonic(x) => x.to_string(),
+            NativeStruct::WsSocket(x) => x.to_string(),
+            NativeStruct::WsServer(x) => x.to_string(),
             NativeStruct::Action(x) => x.to_string(vm),
             NativeStruct::Chain(x) => x.to_string(vm),
             NativeStruct::Link(x) => x.to_string(vm),
             NativeStruct::SessionEnd(x) => x.to_string(),
+            NativeStruct::Watcher(x) => x.to_string(),
+            NativeStruct::HttpResponse(x) => x.to_string(),
+            NativeStruct::StdinLines(x) => x.to_string(),
             NativeStruct::McpClient(x) => x.to_string(),
             NativeStruct::McpTool(x) => x.to_string(),
             NativeStruct::NativeLib(x) => x.to_string(vm),
+            NativeStruct::NativeFn(x) => x.to_string(),
+            NativeStruct::Process(x) => x.to_string(),
             NativeStruct::Interval(x) => x.to_string(vm),
             NativeStruct::Browser(x) => x.to_string(vm),
+            NativeStruct::Custom(x) => x.to_string(vm),
         }
     }
 
@@ -57,15 +94,27 @@ impl NativeStruct {
         match self {
             NativeStruct::NetStream(x) => x.shape.property_access(property),
             NativeStruct::NetServer(x) => x.shape.property_access(property),
+            NativeStruct::NetUdpSocket(x) => x.shape.property_access(property),
+            NativeStruct::Time(x) => x.property_access(property),
+            NativeStruct::TimeDuration(x) => x.property_access(property),
+            NativeStruct::Monotonic(x) => x.property_access(property),
+            NativeStruct::WsSocket(x) => x.shape.property_access(property),
+            NativeStruct::WsServer(x) => x.shape.property_access(property),
             NativeStruct::Action(x) => x.property_access(property),
             NativeStruct::Chain(x) => x.shape.property_access(property),
             NativeStruct::Link(x) => x.shape.property_access(property),
             NativeStruct::SessionEnd(x) => x.property_access(property),
+            NativeStruct::Watcher(x) => x.property_access(property),
+            NativeStruct::HttpResponse(x) => x.property_access(property),
+            NativeStruct::StdinLines(x) => x.property_access(property),
             NativeStruct::McpClient(x) => x.shape.property_access(property),
             NativeStruct::McpTool(x) => x.shape.property_access(property),
             NativeStruct::NativeLib(x) => x.property_access(property),
+            NativeStruct::NativeFn(x) => x.property_access(property),
+            NativeStruct::Process(x) => x.property_access(property),
             NativeStruct::Interval(x) => x.property_access(property),
             NativeStruct::Browser(x) => x.property_access(property),
+            NativeStruct::Custom(x) => x.property_access(property),
         }
     }
 
@@ -77,6 +126,20 @@ impl NativeStruct {
         }
     }
 
+    pub fn as_custom<T: 'static>(&self) -> Option<&T> {
+        match self {
+            NativeStruct::Custom(x) => x.as_any().downcast_ref::<T>(),
+            _ => None,
+        }
+    }
+
+    pub fn as_custom_mut<T: 'static>(&mut self) -> Option<&mut T> {
+        match self {
+            NativeStruct::Custom(x) => x.as_any_mut().downcast_mut::<T>(),
+            _ => None,
+        }
+    }
+
     pub fn as_link(&self, vm: &Vm) -> Result<Link, VMError> {
         match self {
             NativeStruct::Link(x) => Ok(x.clone()),
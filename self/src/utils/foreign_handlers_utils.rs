@@ -1,17 +1,261 @@
-use crate::core::handlers::foreign_handlers::ForeignHandlersToml;
-use std::env;
-use std::fs;
-use toml;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
 
-pub fn get_foreign_handlers() -> Option<ForeignHandlersToml> {
+use serde::Deserialize;
+
+use crate::core::handlers::foreign_handlers::FOREIGN_TYPES;
+pub use crate::core::handlers::foreign_handlers::{
+    Argument, ForeignHandler, ForeignHandlersToml, ForeignProtocol,
+};
+
+const HANDLERS_FILE: &str = "foreign.toml";
+const PROJECT_FILE: &str = "ego.toml";
+
+#[derive(Debug, Clone)]
+pub struct ForeignConfigError {
+    pub path: PathBuf,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl ForeignConfigError {
+    fn new(path: &Path, line: Option<usize>, message: String) -> ForeignConfigError {
+        ForeignConfigError {
+            path: path.to_path_buf(),
+            line,
+            message,
+        }
+    }
+
+    pub fn to_string(&self) -> String {
+        match self.line {
+            Some(line) => format!("{}:{}: {}", self.path.display(), line, self.message),
+            None => format!("{}: {}", self.path.display(), self.message),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ForeignHandlersConfig {
+    pub files: Vec<PathBuf>,
+    pub handlers: Vec<ForeignHandler>,
+    pub errors: Vec<ForeignConfigError>,
+}
+
+// ego.toml
+//   [foreign]
+//   files = ["foreign.toml", "handlers/python.toml"]
+#[derive(Debug, Deserialize)]
+struct ProjectToml {
+    foreign: Option<ProjectForeign>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProjectForeign {
+    #[serde(default)]
+    files: Vec<String>,
+}
+
+// handlers visible from `entry` (the module being run, or the cwd
+// if none): the files of its project, listed on ego.toml or the
+// foreign.toml next to it, plus the foreign.toml next to the entry
+pub fn get_foreign_handlers(entry: Option<&Path>) -> ForeignHandlersConfig {
+    let mut config = ForeignHandlersConfig::default();
+
+    let dir = entry_dir(entry);
+    let mut files = vec![];
+    if let Some(root) = find_project_root(&dir) {
+        files.extend(project_handler_files(&root, &mut config.errors));
+    }
+    let local = dir.join(HANDLERS_FILE);
+    if local.exists() {
+        files.push(local);
+    }
+
+    for file in files {
+        let file = fs::canonicalize(&file).unwrap_or(file);
+        if config.files.contains(&file) {
+            continue;
+        }
+        load_file(&file, &mut config);
+        config.files.push(file);
+    }
+    config
+}
+
+// absolute directory of `entry`, the cwd if none
+fn entry_dir(entry: Option<&Path>) -> PathBuf {
     let cwd = env::current_dir().unwrap_or_default();
-    let file_path = cwd.join("foreign.toml");
+    match entry.and_then(|e| e.parent()) {
+        Some(parent) if parent.as_os_str().is_empty() => cwd,
+        Some(parent) => cwd.join(parent),
+        None => cwd,
+    }
+}
+
+// root of the project `entry` belongs to (where its ego.toml is),
+// or the directory of `entry` when it's not part of one
+pub fn project_dir(entry: Option<&Path>) -> PathBuf {
+    let dir = entry_dir(entry);
+    find_project_root(&dir).unwrap_or(dir)
+}
+
+pub fn find_project_root(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .find(|d| d.join(PROJECT_FILE).exists())
+        .map(Path::to_path_buf)
+}
+
+fn project_handler_files(root: &Path, errors: &mut Vec<ForeignConfigError>) -> Vec<PathBuf> {
+    let project_file = root.join(PROJECT_FILE);
+    let content = fs::read_to_string(&project_file).unwrap_or_default();
+    let project: ProjectToml = match toml::from_str(&content) {
+        Ok(project) => project,
+        Err(e) => {
+            errors.push(toml_error(&project_file, e));
+            return vec![];
+        }
+    };
+
+    let Some(foreign) = project.foreign else {
+        let default = root.join(HANDLERS_FILE);
+        return if default.exists() {
+            vec![default]
+        } else {
+            vec![]
+        };
+    };
+
+    let mut files = vec![];
+    for file in foreign.files {
+        let path = root.join(&file);
+        if path.exists() {
+            files.push(path);
+        } else {
+            errors.push(ForeignConfigError::new(
+                &project_file,
+                line_of(&content, &format!("\"{}\"", file)),
+                format!("handlers file '{}' not found", file),
+            ));
+        }
+    }
+    files
+}
+
+fn load_file(path: &Path, config: &mut ForeignHandlersConfig) {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => {
+            config
+                .errors
+                .push(ForeignConfigError::new(path, None, e.to_string()));
+            return;
+        }
+    };
+    let parsed: ForeignHandlersToml = match toml::from_str(&content) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            config.errors.push(toml_error(path, e));
+            return;
+        }
+    };
+
+    let dir = path.parent().unwrap_or(Path::new("."));
+    for mut handler in parsed.functions {
+        let line = line_of(&content, &format!("\"{}\"", handler.name));
+        if let Err(message) = validate(&handler, &config.handlers) {
+            config
+                .errors
+                .push(ForeignConfigError::new(path, line, message));
+            continue;
+        }
+
+        // scripts are relative to the file declaring them
+        let script = dir.join(&handler.script);
+        if Path::new(&handler.script).is_relative() && script.exists() {
+            handler.script = script.to_string_lossy().to_string();
+        }
+        handler.source = path.to_path_buf();
+        config.handlers.push(handler);
+    }
+}
 
-    if !file_path.exists() {
-        return None;
+fn validate(handler: &ForeignHandler, loaded: &[ForeignHandler]) -> Result<(), String> {
+    if handler.name.is_empty() {
+        return Err("handler name cannot be empty".to_string());
     }
+    if let Some(previous) = loaded.iter().find(|h| h.name == handler.name) {
+        return Err(format!(
+            "handler '{}' is already declared in {}",
+            handler.name,
+            previous.source.display()
+        ));
+    }
+    if handler.runtime.is_empty() {
+        return Err(format!("handler '{}' has no runtime", handler.name));
+    }
+
+    let declared_types = handler
+        .args
+        .iter()
+        .flatten()
+        .map(|a| &a.arg_type)
+        .chain(handler.returns.iter());
+    for data_type in declared_types {
+        if !FOREIGN_TYPES.contains(&data_type.as_str()) {
+            return Err(format!(
+                "unknown type '{}' on handler '{}', expected one of: {}",
+                data_type,
+                handler.name,
+                FOREIGN_TYPES.join(", ")
+            ));
+        }
+    }
+
+    if handler.worker && handler.protocol != ForeignProtocol::Json {
+        return Err(format!(
+            "handler '{}' can only be a worker with protocol = \"json\"",
+            handler.name
+        ));
+    }
+    if handler.timeout == Some(0) {
+        return Err(format!(
+            "handler '{}' timeout must be positive",
+            handler.name
+        ));
+    }
+    Ok(())
+}
+
+// runtimes are either paths or binaries looked up on PATH
+pub fn find_runtime(runtime: &str) -> Option<PathBuf> {
+    let path = Path::new(runtime);
+    if path.components().count() > 1 {
+        return path.exists().then(|| path.to_path_buf());
+    }
+    let paths = env::var_os("PATH")?;
+    env::split_paths(&paths)
+        .map(|dir| dir.join(runtime))
+        .find(|candidate| candidate.is_file())
+}
+
+fn toml_error(path: &Path, err: toml::de::Error) -> ForeignConfigError {
+    // toml lines and columns are 0-based
+    let line = err.line_col().map(|(line, _)| line + 1);
+    let message = err.to_string();
+    // the message already carries its position
+    let message = match message.find(" at line ") {
+        Some(index) => message[..index].to_string(),
+        None => message,
+    };
+    ForeignConfigError::new(path, line, message)
+}
 
-    let content = fs::read_to_string(&file_path).unwrap_or_default();
-    let toml_content: ForeignHandlersToml = toml::from_str(&content).expect("Failed to parse TOML");
-    Some(toml_content)
+fn line_of(content: &str, needle: &str) -> Option<usize> {
+    content
+        .lines()
+        .position(|line| line.contains(needle))
+        .map(|index| index + 1)
 }
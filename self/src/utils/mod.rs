@@ -1,5 +1,6 @@
 pub mod foreign_handlers_utils;
 pub mod from_bytes;
+pub mod path;
 pub mod to_bytes;
 
 pub enum Number {
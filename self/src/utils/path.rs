@@ -0,0 +1,73 @@
+use std::path::{Component, Path, PathBuf};
+
+// lexical normalization, removes `.` and resolves `..` against the
+// previous segment without touching the filesystem. Leading `..` are
+// kept on relative paths and dropped at the root
+pub fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    // number of segments that a `..` can pop
+    let mut depth = 0;
+    for component in path.components() {
+        match component {
+            Component::Prefix(_) | Component::RootDir => {
+                normalized.push(component.as_os_str());
+                depth = 0;
+            }
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if depth > 0 {
+                    normalized.pop();
+                    depth -= 1;
+                } else if !normalized.has_root() {
+                    normalized.push("..");
+                }
+            }
+            Component::Normal(segment) => {
+                normalized.push(segment);
+                depth += 1;
+            }
+        }
+    }
+
+    if normalized.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        normalized
+    }
+}
+
+// makes the path absolute against the current working directory
+pub fn absolute(path: &Path) -> std::io::Result<PathBuf> {
+    if path.is_absolute() {
+        return Ok(normalize(path));
+    }
+    Ok(normalize(&std::env::current_dir()?.join(path)))
+}
+
+// path that goes from `from` to `to`, both are made absolute first
+pub fn relative(from: &Path, to: &Path) -> std::io::Result<PathBuf> {
+    let from = absolute(from)?;
+    let to = absolute(to)?;
+    let from: Vec<Component> = from.components().collect();
+    let to: Vec<Component> = to.components().collect();
+
+    let common = from
+        .iter()
+        .zip(to.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut relative = PathBuf::new();
+    for _ in common..from.len() {
+        relative.push("..");
+    }
+    for component in &to[common..] {
+        relative.push(component.as_os_str());
+    }
+
+    if relative.as_os_str().is_empty() {
+        Ok(PathBuf::from("."))
+    } else {
+        Ok(relative)
+    }
+}
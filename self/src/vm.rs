@@ -1,21 +1,30 @@
 use futures::future::BoxFuture;
-use tokio::sync::mpsc;
+use serde_json::Value as JsonValue;
+use tokio::sync::{mpsc, watch};
 
+use crate::core::error;
 use crate::core::error::struct_errors::StructError;
+use crate::core::error::type_errors::TypeError;
 use crate::core::error::InvalidBinaryOperation;
+use crate::core::error::VMError;
 use crate::core::error::VMErrorType;
 use crate::core::execution::VMExecutionResult;
 use crate::core::handlers::call_handler::call_handler;
 use crate::core::handlers::foreign_handlers::ForeignHandlers;
 use crate::core::handlers::print_handler::print_handler;
-use crate::events::Event;
+use crate::events::{Event, KeepAlive};
 use crate::memory::Handle;
 use crate::memory::MemObject;
 use crate::memory::MemoryManager;
 use crate::opcodes::DataType;
 use crate::opcodes::Opcode;
+use crate::output::{OutputStream, StdOutput, VmOutput};
+use crate::std::ai::context::AIContext;
 use crate::std::bootstrap_default_lib;
+use crate::std::bytes::decode_utf8;
 use crate::std::heap_utils::put_string;
+use crate::std::mcp::types::McpClientRef;
+use crate::std::utils::{json_to_value, value_to_json};
 use crate::std::vector;
 use crate::std::{generate_native_module, get_native_module_type};
 use crate::types::object::func::Engine;
@@ -28,9 +37,10 @@ use crate::types::object::BoundAccess;
 use crate::types::raw::utf8::Utf8;
 use crate::types::raw::RawValue;
 use crate::types::raw::{bool::Bool, f64::F64, i32::I32, i64::I64, u32::U32, u64::U64};
-use crate::utils::foreign_handlers_utils::get_foreign_handlers;
+use crate::utils::foreign_handlers_utils::project_dir;
 use std::collections::HashMap;
 use std::path::Path;
+use std::sync::{Arc, Mutex};
 
 use super::stack::*;
 use super::types::*;
@@ -45,6 +55,17 @@ pub struct Vm {
     ffi_handlers: ForeignHandlers,
     events_queue: mpsc::UnboundedReceiver<Event>,
     events_sender: mpsc::UnboundedSender<Event>,
+    pub ai_context: AIContext,
+    mcp_clients: Vec<McpClientRef>,
+    host_modules: HashMap<String, Vec<Function>>,
+    // arguments for the script, see os.args
+    pub script_args: Vec<String>,
+    // producers holding a KeepAlive guard
+    keep_alive: Arc<watch::Sender<usize>>,
+    // print/println, error reports and debug traces
+    output: Mutex<Box<dyn VmOutput>>,
+    // events being handled, see drain_events
+    events_depth: usize,
 }
 
 impl Vm {
@@ -52,15 +73,9 @@ impl Vm {
         //let mut translator = Translator::new(bytecode);
         //let instructions = translator.translate();
 
-        // load ffi_handlers
-        let mut ffi_handlers = ForeignHandlers::new();
-        let foreign_handlers = get_foreign_handlers();
-
-        if let Some(loaded_handlers) = foreign_handlers {
-            for handler in loaded_handlers.functions {
-                ffi_handlers.add(handler);
-            }
-        }
+        // load ffi_handlers, relative to the cwd until the entry
+        // module is known
+        let ffi_handlers = ForeignHandlers::load(None);
 
         // events queue
         let (events_sender, events_receiver) = mpsc::unbounded_channel::<Event>();
@@ -75,14 +90,62 @@ impl Vm {
             ffi_handlers,
             events_queue: events_receiver,
             events_sender,
+            ai_context: AIContext::new(),
+            mcp_clients: vec![],
+            host_modules: HashMap::new(),
+            script_args: vec![],
+            keep_alive: Arc::new(watch::channel(0).0),
+            output: Mutex::new(Box::new(StdOutput)),
+            events_depth: 0,
         }
     }
 
+    // replaces the sink where the vm writes its output
+    pub fn set_output(&mut self, output: impl VmOutput + 'static) {
+        self.flush_output();
+        self.output = Mutex::new(Box::new(output));
+    }
+
+    // replaces the sink and returns the previous one, to put it
+    // back once done
+    pub fn swap_output(&mut self, output: Box<dyn VmOutput>) -> Box<dyn VmOutput> {
+        self.flush_output();
+        std::mem::replace(self.output.get_mut().unwrap(), output)
+    }
+
+    pub fn write_output(&self, stream: OutputStream, text: &str) {
+        self.output.lock().unwrap().write(stream, text);
+    }
+
+    pub fn flush_output(&self) {
+        self.output.lock().unwrap().flush();
+    }
+
+    // reports an execution error on the stderr stream
+    pub fn report_error(&self, err: &VMError) {
+        self.write_output(
+            OutputStream::Stderr,
+            &format!(
+                "\x1b[31m[ERR] \x1b[0m{}: {}\n",
+                err.message, err.semantic_message
+            ),
+        );
+    }
+
+    // debug (-d) trace line
+    pub fn trace(&self, text: String) {
+        self.write_output(OutputStream::Debug, &format!("{}\n", text));
+    }
+
     pub async fn run(&mut self, args: &Vec<String>) -> VMExecutionResult {
-        let debug = args.contains(&"-d".to_string());
+        let debug = has_flag(args, "-d");
+        if has_flag(args, "--no-ai-cache") {
+            self.ai_context.cache.enabled = false;
+        }
+        self.script_args = script_args(args);
         if debug {
-            println!("last PC value: {}", self.bytecode.len());
-            println!("-");
+            self.trace(format!("last PC value: {}", self.bytecode.len()));
+            self.trace("-".to_string());
         }
 
         // load builtin handlers
@@ -94,7 +157,18 @@ impl Vm {
         }
         self.handlers = handlers;
 
-        self.run_bytecode(debug).await
+        let mut result = self.run_bytecode(debug).await;
+        if result.error.is_none() {
+            if let Err(err) = self.wait_keep_alive().await {
+                result = VMExecutionResult {
+                    error: Some(err),
+                    result: None,
+                };
+            }
+        }
+        self.shutdown_mcp_clients().await;
+        self.flush_output();
+        result
     }
 
     fn run_bytecode<'a>(&'a mut self, debug: bool) -> BoxFuture<'a, VMExecutionResult> {
@@ -111,11 +185,20 @@ impl Vm {
                         let (data_type, value_bytes) = self.get_value_length();
 
                         // execution
-                        let (value, printable_value) = self.bytes_to_data(&data_type, &value_bytes);
+                        let (value, printable_value) =
+                            match self.bytes_to_data(&data_type, &value_bytes) {
+                                Ok(data) => data,
+                                Err(err) => {
+                                    return VMExecutionResult::terminate_with_errors(
+                                        err.error_type,
+                                        self,
+                                    )
+                                }
+                            };
 
                         self.push_to_stack(value, None);
                         if debug {
-                            println!("LOAD_CONST <- {:?}({printable_value})", data_type);
+                            self.trace(format!("LOAD_CONST <- {:?}({printable_value})", data_type));
                         }
 
                         self.pc += 1;
@@ -132,14 +215,21 @@ impl Vm {
                         if identifier_data_type != DataType::Utf8 {
                             panic!("Identifier type should be a string encoded as utf8")
                         }
-                        let identifier_name = String::from_utf8(identifier_bytes)
-                            .expect("Identifier bytes should be valid UTF-8");
+                        let identifier_name = match decode_utf8(identifier_bytes) {
+                            Ok(name) => name,
+                            Err(err) => {
+                                return VMExecutionResult::terminate_with_errors(
+                                    VMErrorType::Bytes(err),
+                                    self,
+                                )
+                            }
+                        };
 
                         let identifier_value = self.call_stack.resolve(&identifier_name);
                         if let Some(v) = identifier_value {
                             self.push_to_stack(v, Some(identifier_name.clone()));
                             if debug {
-                                println!("LOAD_VAR <- {identifier_name}");
+                                self.trace(format!("LOAD_VAR <- {identifier_name}"));
                             }
                         } else {
                             return VMExecutionResult::terminate_with_errors(
@@ -173,8 +263,15 @@ impl Vm {
                         if identifier_data_type != DataType::Utf8 {
                             panic!("Identifier type should be a string encoded as utf8")
                         }
-                        let identifier_name = String::from_utf8(identifier_bytes)
-                            .expect("Identifier bytes should be valid UTF-8");
+                        let identifier_name = match decode_utf8(identifier_bytes) {
+                            Ok(name) => name,
+                            Err(err) => {
+                                return VMExecutionResult::terminate_with_errors(
+                                    VMErrorType::Bytes(err),
+                                    self,
+                                )
+                            }
+                        };
 
                         // release the handle if we are going to reassign
                         if let Some(i) = self.call_stack.resolve(&identifier_name) {
@@ -207,13 +304,13 @@ impl Vm {
                             }
 
                             if debug {
-                                println!(
+                                self.trace(format!(
                                     "STORE_VAR[{}] <- {:?}({}) as {}",
                                     if mutable { "MUT" } else { "INMUT" },
                                     datatype,
                                     printable_value,
                                     identifier_name,
-                                );
+                                ));
                             }
                         } else {
                             // todo: use self-vm errors
@@ -256,10 +353,10 @@ impl Vm {
                             Value::RawValue(v) => match v {
                                 RawValue::Bool(execute_if) => {
                                     if debug {
-                                        println!(
+                                        self.trace(format!(
                                             "JUMP_IF_FALSE <- {:?}({})",
                                             execute_if.value, offset
-                                        );
+                                        ));
                                     }
                                     if !execute_if.value {
                                         self.pc += offset as usize;
@@ -281,7 +378,7 @@ impl Vm {
 
                         let target_pc = (self.pc as isize) + offset as isize;
                         if debug {
-                            println!("JUMP <- {:?}", target_pc);
+                            self.trace(format!("JUMP <- {:?}", target_pc));
                         }
                         self.pc = target_pc as usize;
                     }
@@ -295,7 +392,7 @@ impl Vm {
                                 Err(e) => return VMExecutionResult::terminate_with_errors(e, self),
                             }
                         }
-                        print_handler(resolved_args, debug, false);
+                        print_handler(self, resolved_args, debug, false);
                     }
                     Opcode::Println => {
                         self.pc += 1; // consume print opcode
@@ -307,7 +404,7 @@ impl Vm {
                                 Err(e) => return VMExecutionResult::terminate_with_errors(e, self),
                             }
                         }
-                        print_handler(resolved_args, debug, true);
+                        print_handler(self, resolved_args, debug, true);
                     }
                     Opcode::FuncDec => {
                         // skip FuncDec opcode
@@ -325,8 +422,15 @@ impl Vm {
                         if identifier_data_type != DataType::Utf8 {
                             panic!("Identifier type should be a string encoded as utf8")
                         }
-                        let identifier_name = String::from_utf8(identifier_bytes)
-                            .expect("Identifier bytes should be valid UTF-8");
+                        let identifier_name = match decode_utf8(identifier_bytes) {
+                            Ok(name) => name,
+                            Err(err) => {
+                                return VMExecutionResult::terminate_with_errors(
+                                    VMErrorType::Bytes(err),
+                                    self,
+                                )
+                            }
+                        };
 
                         // parameters
                         if self.pc + 4 >= self.bytecode.len() {
@@ -399,9 +503,15 @@ impl Vm {
                             panic!("Identifier type should be a string encoded as utf8")
                         }
 
-                        // TODO: use self-vm errors
-                        let identifier_name = String::from_utf8(identifier_bytes)
-                            .expect("Identifier bytes should be valid UTF-8");
+                        let identifier_name = match decode_utf8(identifier_bytes) {
+                            Ok(name) => name,
+                            Err(err) => {
+                                return VMExecutionResult::terminate_with_errors(
+                                    VMErrorType::Bytes(err),
+                                    self,
+                                )
+                            }
+                        };
 
                         // read fields number
                         self.pc += 1;
@@ -419,8 +529,15 @@ impl Vm {
                                 // TODO: use self-vm errors
                                 panic!("Identifier type should be a string encoded as utf8")
                             }
-                            let field_name = String::from_utf8(field_bytes)
-                                .expect("Field bytes should be valid UTF-8"); // TODO: use self-vm errors
+                            let field_name = match decode_utf8(field_bytes) {
+                                Ok(name) => name,
+                                Err(err) => {
+                                    return VMExecutionResult::terminate_with_errors(
+                                        VMErrorType::Bytes(err),
+                                        self,
+                                    )
+                                }
+                            };
                             self.pc += 1;
 
                             // annotation
@@ -471,11 +588,11 @@ impl Vm {
                         let property = self.memory.resolve(&property_handle);
 
                         if debug {
-                            println!(
+                            self.trace(format!(
                                 "GET_PROPERTY <- {}({:?})",
                                 object.to_string(self),
                                 property.to_string(self)
-                            );
+                            ));
                         }
 
                         if let MemObject::String(property_key) = property {
@@ -537,6 +654,25 @@ impl Vm {
                                         );
                                     }
                                 }
+                                MemObject::Bytes(x) => {
+                                    let value = x.property_access(&property_key.value);
+                                    if let Some(prop) = value {
+                                        let bound_access =
+                                            BoundAccess::new(object_handle.clone(), Box::new(prop));
+                                        self.push_to_stack(
+                                            Value::BoundAccess(bound_access),
+                                            Some(object.to_string(self)),
+                                        );
+                                    } else {
+                                        return VMExecutionResult::terminate_with_errors(
+                                            VMErrorType::Struct(StructError::FieldNotFound {
+                                                field: property_key.to_string(),
+                                                struct_type: object.to_string(self),
+                                            }),
+                                            self,
+                                        );
+                                    }
+                                }
                                 MemObject::String(x) => {
                                     let value = x.property_access(&property_key.value);
                                     if let Some(prop) = value {
@@ -599,7 +735,7 @@ impl Vm {
                             // FOR NAMED FUNCTIONS ACCESS
                             MemObject::String(identifier_name) => {
                                 if debug {
-                                    println!("CALL -> {}", identifier_name.to_string())
+                                    self.trace(format!("CALL -> {}", identifier_name.to_string()))
                                 };
                                 match identifier_name.value.as_str() {
                                     // BUILTIN FUNCTIONS
@@ -772,8 +908,8 @@ impl Vm {
                                 }
                             }
 
-                            // FOR VECTOR CALLABLE MEMBERS
-                            MemObject::Vector(caller) => {
+                            // FOR VECTOR AND BYTES CALLABLE MEMBERS
+                            caller @ (MemObject::Vector(_) | MemObject::Bytes(_)) => {
                                 let callee_handle = if let Some(c) = callee_handle {
                                     c
                                 } else {
@@ -825,11 +961,12 @@ impl Vm {
 
                         if let Value::Handle(mod_handle) = module_name_value {
                             let module_name = self.memory.resolve(&mod_handle).to_string(self);
-                            let native_module = get_native_module_type(module_name.as_str());
+                            let native_module = get_native_module_type(module_name.as_str())
+                                .map(generate_native_module)
+                                .or_else(|| self.host_module(&module_name));
                             // native module
-                            if let Some(nm) = native_module {
+                            if let Some(module_def) = native_module {
                                 // load native module fields
-                                let module_def = generate_native_module(nm);
                                 let mut module_fields = HashMap::new();
                                 for field in module_def.1 {
                                     let field_handle = self.memory.alloc(field.1);
@@ -885,7 +1022,7 @@ impl Vm {
                             let arg = self.memory.resolve(&r);
                             if let MemObject::String(s) = arg {
                                 if debug {
-                                    println!("EXPORT -> {}", s.value)
+                                    self.trace(format!("EXPORT -> {}", s.value))
                                 }
                                 self.call_stack.add_export(s.to_string());
                             } else {
@@ -1058,18 +1195,40 @@ impl Vm {
                     }
                     Opcode::FFI_Call => {
                         self.pc += 1; // consume call opcode
-                        let args = self.get_function_call_args();
-                        let mut resolved_args = Vec::new();
-                        for val in args {
-                            match self.value_to_string(val) {
-                                Ok(v) => resolved_args.push(v),
-                                Err(e) => return VMExecutionResult::terminate_with_errors(e, self),
-                            }
+                        let mut args = self.get_function_call_args();
+                        if args.is_empty() {
+                            return VMExecutionResult::terminate_with_errors(
+                                VMErrorType::Any("ffi_call requires a handler name".to_string()),
+                                self,
+                            );
                         }
+                        let handler_name = match self.value_to_string(args.remove(0)) {
+                            Ok(v) => v,
+                            Err(e) => return VMExecutionResult::terminate_with_errors(e, self),
+                        };
+                        let args = args.iter().map(|a| value_to_json(self, a)).collect();
                         if debug {
-                            println!("CALL -> {}", resolved_args[0].to_string())
+                            self.trace(format!("CALL -> {}", handler_name))
+                        }
+                        match call_handler(
+                            &mut self.ffi_handlers,
+                            &self.output,
+                            &handler_name,
+                            args,
+                        )
+                        .await
+                        {
+                            Ok(result) => {
+                                let value = json_to_value(self, &result);
+                                self.push_to_stack(value, Some(handler_name));
+                            }
+                            Err(e) => {
+                                return VMExecutionResult::terminate_with_errors(
+                                    VMErrorType::Foreign(e),
+                                    self,
+                                )
+                            }
                         }
-                        call_handler(&self.ffi_handlers, resolved_args);
                     }
                     _ => {
                         println!("unhandled opcode");
@@ -1081,7 +1240,12 @@ impl Vm {
                 // if we only get one event on each execution
                 // could happen that we have more events than
                 // iterations on the vm
-                self.drain_events().await;
+                if let Err(err) = self.drain_events().await {
+                    return VMExecutionResult {
+                        error: Some(err),
+                        result: None,
+                    };
+                }
             }
 
             VMExecutionResult::terminate(None)
@@ -1096,6 +1260,19 @@ impl Vm {
         let left = operands.0;
         let right = operands.1;
 
+        // any value can be checked against nothing, natives return it
+        // for missing values (end of input, no result...)
+        if let (Value::RawValue(RawValue::Nothing), other)
+        | (other, Value::RawValue(RawValue::Nothing)) = (&left.value, &right.value)
+        {
+            if operator == "==" || operator == "!=" {
+                let is_nothing = matches!(other, Value::RawValue(RawValue::Nothing));
+                let result = (operator == "==") == is_nothing;
+                self.push_to_stack(Value::RawValue(RawValue::Bool(Bool::new(result))), None);
+                return None;
+            }
+        }
+
         let value: Value;
         // cloned here, to be able to use later on
         // different VMErrors
@@ -1387,13 +1564,16 @@ impl Vm {
             }
             Engine::Native(native) => {
                 if args.len() < func.parameters.len() {
-                    // TODO: use self-vm errors system
-                    panic!(
-                        "function '{}' requires {} parameters, provided {}",
-                        func.identifier,
-                        func.parameters.len(),
-                        args.len()
-                    )
+                    return VMExecutionResult {
+                        error: Some(error::throw(
+                            VMErrorType::TypeError(TypeError::InvalidArgsCount {
+                                expected: func.parameters.len() as u32,
+                                received: args.len() as u32,
+                            }),
+                            self,
+                        )),
+                        result: None,
+                    };
                 }
                 let execution_result = native(self, caller, args, debug);
                 if let Ok(result) = execution_result {
@@ -1412,13 +1592,16 @@ impl Vm {
             }
             Engine::NativeAsync(async_native) => {
                 if args.len() < func.parameters.len() {
-                    // TODO: use self-vm errors system
-                    panic!(
-                        "function '{}' requires {} parameters, provided {}",
-                        func.identifier,
-                        func.parameters.len(),
-                        args.len()
-                    )
+                    return VMExecutionResult {
+                        error: Some(error::throw(
+                            VMErrorType::TypeError(TypeError::InvalidArgsCount {
+                                expected: func.parameters.len() as u32,
+                                received: args.len() as u32,
+                            }),
+                            self,
+                        )),
+                        result: None,
+                    };
                 }
                 let execution_result = async_native(self, caller, args, debug).await;
                 if let Ok(result) = execution_result {
@@ -1435,6 +1618,16 @@ impl Vm {
                     }
                 }
             }
+            Engine::Plugin(plugin) => match plugin.call(self, args) {
+                Ok(result) => VMExecutionResult {
+                    error: None,
+                    result: Some(result),
+                },
+                Err(err) => VMExecutionResult {
+                    error: Some(err),
+                    result: None,
+                },
+            },
         };
 
         return execution_result;
@@ -1458,12 +1651,7 @@ impl Vm {
                     panic!("bad utf8 value length")
                 }
 
-                let (string_length, _) = self.bytes_to_data(&DataType::U32, &value);
-                if let Value::RawValue(RawValue::U32(val)) = string_length {
-                    val.value as usize
-                } else {
-                    panic!("Unexpected value type for string length");
-                }
+                Vm::read_offset(&value) as u32 as usize
             }
             DataType::StructLiteral => 4, // fields count
             DataType::Vector => 4,        // elements count
@@ -1491,8 +1679,11 @@ impl Vm {
         (data_type, value_bytes)
     }
 
-    // REFACTOR: this function should return a Result<(Value, String), VMError>
-    pub fn bytes_to_data(&mut self, data_type: &DataType, value: &Vec<u8>) -> (Value, String) {
+    pub fn bytes_to_data(
+        &mut self,
+        data_type: &DataType,
+        value: &Vec<u8>,
+    ) -> Result<(Value, String), VMError> {
         let printable_value;
         let value = match data_type {
             DataType::I32 => {
@@ -1546,8 +1737,8 @@ impl Vm {
                 Value::RawValue(RawValue::F64(F64::new(value)))
             }
             DataType::Utf8 => {
-                let value =
-                    String::from_utf8(value.clone()).expect("Provided value is not valid UTF-8");
+                let value = decode_utf8(value.clone())
+                    .map_err(|e| error::throw(VMErrorType::Bytes(e), self))?;
                 printable_value = value.to_string();
 
                 let string_obj = SelfString::new(value, self);
@@ -1684,7 +1875,7 @@ impl Vm {
             }
         };
 
-        (value, printable_value)
+        Ok((value, printable_value))
     }
 
     fn value_to_string(&mut self, value: Value) -> Result<String, VMErrorType> {
@@ -1762,18 +1953,161 @@ impl Vm {
     }
 
     // events queue methods
-    pub async fn drain_events(&mut self) {
-        match self.events_queue.try_recv() {
-            Ok(event) => match event {
-                Event::Call(f) => {
-                    self.run_function(&f, None, vec![], false).await;
+    pub async fn drain_events(&mut self) -> Result<(), VMError> {
+        // handle every pending event, otherwise producers faster
+        // than the vm iterations (like ai streams) pile up. not
+        // while running a callback, the next events would run
+        // before it ends and in reverse order
+        if self.events_depth > 0 {
+            return Ok(());
+        }
+        while let Ok(event) = self.events_queue.try_recv() {
+            self.handle_event(event).await?;
+        }
+        Ok(())
+    }
+
+    // waits for the next event, used by natives that keep the
+    // vm alive while serving (like mcp.serve)
+    pub async fn next_event(&mut self) -> Option<Event> {
+        self.events_queue.recv().await
+    }
+
+    pub fn keep_alive(&self) -> KeepAlive {
+        KeepAlive::new(self.keep_alive.clone())
+    }
+
+    // awaits a native operation handling the events meanwhile, so
+    // timers and callbacks don't freeze while it's pending
+    pub async fn with_events<T>(
+        &mut self,
+        operation: impl std::future::Future<Output = T>,
+    ) -> Result<T, VMError> {
+        tokio::pin!(operation);
+        loop {
+            let event = tokio::select! {
+                result = &mut operation => return Ok(result),
+                event = self.next_event() => event,
+            };
+            if let Some(event) = event {
+                self.handle_event(event).await?;
+            }
+        }
+    }
+
+    // once the bytecode ends, events are handled while any
+    // KeepAlive guard is held
+    async fn wait_keep_alive(&mut self) -> Result<(), VMError> {
+        let mut active = self.keep_alive.subscribe();
+        while *active.borrow_and_update() > 0 {
+            let event = tokio::select! {
+                _ = active.changed() => None,
+                event = self.next_event() => event,
+            };
+            if let Some(event) = event {
+                self.handle_event(event).await?;
+            }
+        }
+        self.drain_events().await
+    }
+
+    // callbacks errors are dropped, except os.exit, which
+    // keeps unwinding the execution
+    pub async fn handle_event(&mut self, event: Event) -> Result<(), VMError> {
+        self.events_depth += 1;
+        let result = self.run_event(event).await;
+        self.events_depth -= 1;
+        result
+    }
+
+    async fn run_event(&mut self, event: Event) -> Result<(), VMError> {
+        match event {
+            Event::Call(f, args) => {
+                let args = args.iter().map(|a| json_to_value(self, a)).collect();
+                let execution = self.run_function(&f, None, args, false).await;
+                if let Some(err) = execution.error {
+                    if let VMErrorType::Exit(_) = err.error_type {
+                        return Err(err);
+                    }
                 }
-                _ => {
-                    println!("unknown event");
+            }
+            Event::Request(f, args, reply) => {
+                let args = args.iter().map(|a| json_to_value(self, a)).collect();
+                let execution = self.run_function(&f, None, args, false).await;
+                let result = match execution.error {
+                    Some(err) => Err(format!("{}: {}", err.message, err.semantic_message)),
+                    None => Ok(execution
+                        .result
+                        .map(|v| value_to_json(self, &v))
+                        .unwrap_or(JsonValue::Null)),
+                };
+                let _ = reply.send(result);
+            }
+            Event::CallWith(f, build_args) => {
+                let args = build_args(self);
+                let execution = self.run_function(&f, None, args, false).await;
+                if let Some(err) = execution.error {
+                    if let VMErrorType::Exit(_) = err.error_type {
+                        return Err(err);
+                    }
                 }
-            },
-            _ => (),
-        };
+            }
+            Event::Run(task) => task(self),
+        }
+        Ok(())
+    }
+
+    // foreign handlers are looked up from the project of the
+    // module being run
+    pub fn load_foreign_handlers(&mut self, entry: &Path) {
+        self.ffi_handlers = ForeignHandlers::load(Some(entry));
+    }
+
+    // loads what depends on the project of the module being run,
+    // its foreign handlers and the ai cache dir
+    pub fn load_project(&mut self, entry: &Path) {
+        self.load_foreign_handlers(entry);
+        self.ai_context.cache.set_project(&project_dir(Some(entry)));
+    }
+
+    // registers a module defined by the host application. it's
+    // imported as any stdlib module (`import name`), stdlib names
+    // can't be overridden
+    pub fn register_module(&mut self, name: &str, members: Vec<Function>) {
+        self.host_modules.insert(name.to_string(), members);
+    }
+
+    // stdlib or host module, their names can't be taken by others
+    pub fn is_module(&self, name: &str) -> bool {
+        get_native_module_type(name).is_some() || self.host_modules.contains_key(name)
+    }
+
+    fn host_module(&self, name: &str) -> Option<(String, Vec<(String, MemObject)>)> {
+        let members = self.host_modules.get(name)?;
+        let fields = members
+            .iter()
+            .map(|f| (f.identifier.clone(), MemObject::Function(f.clone())))
+            .collect();
+        Some((name.to_string(), fields))
+    }
+
+    // summary of the ai usage of the run, none if no ai calls were made
+    pub fn ai_usage_report(&self) -> Option<String> {
+        self.ai_context.report()
+    }
+
+    // mcp clients are shut down when the vm exits, so stdio
+    // servers don't outlive the script
+    pub fn register_mcp_client(&mut self, client: McpClientRef) {
+        self.mcp_clients.push(client);
+    }
+
+    async fn shutdown_mcp_clients(&mut self) {
+        for client in self.mcp_clients.drain(..) {
+            if let Some(service) = client.lock().await.take() {
+                let _ = service.cancel().await;
+            }
+        }
     }
 
     pub fn get_vm_notifier(&self) -> mpsc::UnboundedSender<Event> {
@@ -1781,9 +2115,9 @@ impl Vm {
     }
 
     pub fn debug_bytecode(&mut self) {
-        println!("\n--- BYTECODE ----------\n");
+        self.trace("\n--- BYTECODE ----------\n".to_string());
         for (index, byte) in self.bytecode.iter().enumerate() {
-            println!("[{index}] {}", byte)
+            self.trace(format!("[{index}] {}", byte))
         }
         // -------
         // THIS CODE IS COMMENTED FOR THE REASON THAT
@@ -1837,3 +2171,27 @@ impl Vm {
         //println!("{:#?}", Translator::new(self.bytecode.clone()).translate());
     }
 }
+
+// ego flags, only the ones before `--` count
+pub fn has_flag(args: &[String], flag: &str) -> bool {
+    args.iter().take_while(|a| *a != "--").any(|a| a == flag)
+}
+
+// args[0] is the module path. the ego flags are left out, unless
+// they come after `--`
+fn script_args(args: &[String]) -> Vec<String> {
+    const EGO_FLAGS: [&str; 3] = ["-d", "--bytes", "--no-ai-cache"];
+
+    let mut script_args = vec![];
+    let mut verbatim = false;
+    for arg in args.iter().skip(1) {
+        if verbatim {
+            script_args.push(arg.clone());
+        } else if arg == "--" {
+            verbatim = true;
+        } else if !EGO_FLAGS.contains(&arg.as_str()) {
+            script_args.push(arg.clone());
+        }
+    }
+    script_args
+}